@@ -0,0 +1,58 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// The About window: which build this is, where updates come from, and the licenses of everything built into it.
+
+use slint::ComponentHandle;
+
+use crate::{app, config, error::ErscomError, report::UIError, AboutWindow};
+
+// The list of crates up top, then each of their license files. Regenerate it when the dependencies change.
+const THIRD_PARTY_LICENSES: &str = include_str!("assets/third-party-licenses.txt");
+
+/// Just the list of crates and their licenses, without the (long) license texts.
+fn license_summary() -> &'static str {
+    THIRD_PARTY_LICENSES.split("\n\n=====").next().unwrap_or(THIRD_PARTY_LICENSES)
+}
+
+pub fn show_about_window() {
+    let win = AboutWindow::new().try_error()?;
+    let config = config::get();
+    win.set_theme(config.theme.color_theme());
+    win.set_high_contrast(config.high_contrast);
+    win.set_version(option_env!("VERSION").unwrap_or("0.0.0-local").into());
+    win.set_commit(option_env!("GIT_COMMIT"/* Set by build.rs */).unwrap_or("unknown").into());
+    win.set_copyright(env!("COPYRIGHT").into());
+    win.set_manager_channel(if config.check_for_updates { format!("{}, checked at startup", config.manager_channel.label()) } else { "Not checking for updates".to_string() }.into());
+    win.set_mod_source(app::release_source().map(|s| s.location()).unwrap_or_default().into());
+    win.set_mod_channel(if config.show_beta { "Stable and beta releases" } else { "Stable releases" }.into());
+    win.set_licenses(license_summary().into());
+
+    win.on_open_url(|url| {
+        let _ = webbrowser::open(&url);
+    });
+    win.on_show_license_texts(|| {
+        let path = std::env::temp_dir().join("erscom-third-party-licenses.txt");
+        std::fs::write(&path, THIRD_PARTY_LICENSES).map_err(ErscomError::io(&path)).try_error()?;
+        crate::open_path(&path).try_error()?;
+    });
+    win.on_close({
+        let weak_win = win.as_weak();
+        move || {
+            weak_win.unwrap().hide().try_log("closing about window")?;
+        }
+    });
+    win.show().try_log("showing about window")?;
+}
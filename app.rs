@@ -0,0 +1,212 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// The GUI's state, and where it changes. Things that happen (a refresh finishing, an install) get sent here as a
+// [Msg] from whatever thread they happened on, and are handled one at a time on the event loop. So nothing is ever
+// halfway through a `borrow_mut()` of the manager when something else wants it, and the window is always redrawn from
+// the whole state instead of bits of it being poked at from all over.
+
+use std::{cell::RefCell, path::PathBuf, rc::Rc, sync::Arc};
+
+use tracing::{debug, info, warn};
+
+use crate::{config, error::ErscomError, manage::{self, EldenRingManager, Release}, report::{self, UIError}, source::ReleaseSource, MainWindow};
+
+pub enum Msg {
+    /// Fetch the release list in the background. `quiet` ones (the auto-refresh) only log failures.
+    RefreshStarted { quiet: bool },
+    /// The manager that did the fetching.
+    ReleasesLoaded(Box<EldenRingManager>),
+    RefreshFailed(ErscomError),
+    RefreshErrorDismissed,
+    CurrentDetected(Option<Release>),
+    /// What [manage::mixed_install] found.
+    MixedInstall(Option<Vec<(PathBuf, Vec<String>)>>),
+    InstallFinished(Release),
+    /// Something that shouldn't be interrupted (downloading, installing) started. Says what, for the window.
+    Started(String),
+    Finished,
+    Error(ErscomError),
+    /// The newest version of the manager itself, if it's newer than this one.
+    ManagerUpgrade(Option<String>),
+}
+
+struct State {
+    manager: Rc<RefCell<EldenRingManager>>,
+    refreshing: Option<bool>, // Some(quiet) while a refresh is running
+    refresh_error: Option<String>,
+    busy: Option<String>,
+    mixed: Option<Vec<(PathBuf, Vec<String>)>>,
+    detecting: usize, // detect_current_release()s that haven't come back yet
+    check_manager_upgrade: bool, // done along with the first refresh
+    manager_upgrade: Option<String>,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+pub fn init(manager: &Rc<RefCell<EldenRingManager>>) {
+    STATE.with(|s| *s.borrow_mut() = Some(State { manager: manager.clone(), refreshing: None, refresh_error: None, busy: None, mixed: None, detecting: 0,
+                                                  check_manager_upgrade: config::get().check_for_updates, manager_upgrade: None }));
+}
+
+/// Queue up `msg` to be handled on the event loop. Can be called from any thread.
+pub fn send(msg: Msg) {
+    match slint::invoke_from_event_loop(move || update(msg)) {
+        Ok(()) => {},
+        Err(e) => warn!("Couldn't queue message: {}", e),
+    }
+}
+
+/// Handle `msg` right now. Only for things already on the event loop that can't go on until the state's changed.
+pub fn update(msg: Msg) {
+    let Some(win) = crate::main_window() else { return };
+    let Some(manager) = STATE.with(|s| s.borrow().as_ref().map(|s| s.manager.clone())) else { return };
+    let with_state = |f: &mut dyn FnMut(&mut State)| STATE.with(|s| if let Some(ref mut state) = *s.borrow_mut() { f(state) });
+    match msg {
+        Msg::RefreshStarted { quiet } => {
+            let mut already = false;
+            with_state(&mut |state| { already = state.refreshing.is_some() || state.busy.is_some(); if !already { state.refreshing = Some(quiet) } });
+            if already { return debug!("Already refreshing, or busy") }
+            info!("Refreshing{}", if quiet { " (auto)" } else { "" });
+            let mut fetcher = manager.borrow().clone();
+            let mut check_manager_upgrade = false;
+            with_state(&mut |state| check_manager_upgrade = std::mem::take(&mut state.check_manager_upgrade));
+            tokio::task::spawn_blocking(move || {
+                send(match fetcher.fetch_releases() {
+                    Ok(()) => Msg::ReleasesLoaded(Box::new(fetcher)),
+                    Err(e) => Msg::RefreshFailed(e),
+                });
+                if check_manager_upgrade { find_manager_upgrade() }
+            });
+        },
+        Msg::ReleasesLoaded(fetched) => {
+            with_state(&mut |state| { state.refreshing = None; state.refresh_error = None });
+            {
+                // Only what fetching changes--an install could have happened in the meantime
+                let mut manager = manager.borrow_mut();
+                manager.releases = fetched.releases;
+                manager.offline = fetched.offline;
+                manager.updated_at = fetched.updated_at;
+                manager.rate_limit = fetched.rate_limit;
+            }
+            detect_current_release(&manager);
+        },
+        Msg::RefreshFailed(e) => {
+            warn!("Refreshing releases failed: {}", e);
+            // There's already a list on the screen, so it's not fatal like it is at startup
+            with_state(&mut |state| {
+                if state.refreshing.take() == Some(false) {
+                    state.refresh_error = Some(match e.hint() {
+                        Some(hint) => format!("Couldn't refresh: {} {}", e, hint),
+                        None => format!("Couldn't refresh: {}", e),
+                    });
+                }
+            });
+        },
+        Msg::RefreshErrorDismissed => with_state(&mut |state| state.refresh_error = None),
+        Msg::CurrentDetected(current) => {
+            debug!("Installed version is {:?}", current.as_ref().map(|r| &r.tag));
+            with_state(&mut |state| state.detecting = state.detecting.saturating_sub(1));
+            manager.borrow_mut().current = current;
+            manager.borrow().save_snapshot().try_log("saving install snapshot");
+        },
+        Msg::MixedInstall(mixed) => {
+            if let Some(ref files) = mixed { warn!("Mixed install: {:?}", files) }
+            with_state(&mut |state| state.mixed = mixed.clone());
+        },
+        Msg::InstallFinished(release) => {
+            manager.borrow_mut().current = Some(release);
+            manager.borrow().save_snapshot().try_log("saving install snapshot");
+            detect_current_release(&manager); // To see if that cleared up a mixed install
+        },
+        Msg::Started(activity) => with_state(&mut |state| state.busy = Some(activity.clone())),
+        Msg::Finished => with_state(&mut |state| state.busy = None),
+        Msg::Error(e) => report::error(e.into()),
+        Msg::ManagerUpgrade(version) => with_state(&mut |state| state.manager_upgrade = version.clone()),
+    }
+    view(&win);
+}
+
+/// See if there's a newer manager (in the background), unless that's already been done or it's turned off. Normally
+/// the first refresh does it, this is for when there isn't one.
+pub fn check_manager_upgrade() {
+    let check = STATE.with(|s| s.borrow_mut().as_mut().map(|s| std::mem::take(&mut s.check_manager_upgrade)).unwrap_or(false));
+    if check { tokio::task::spawn_blocking(find_manager_upgrade); }
+}
+
+// It has to ask github, so not on the event loop. Comes back as [Msg::ManagerUpgrade].
+fn find_manager_upgrade() {
+    match manage::self_upgrade_version() {
+        Ok(version) => send(Msg::ManagerUpgrade(version)),
+        Err(e) => warn!("Couldn't check for a newer manager: {}", e),
+    }
+}
+
+/// Figure out which version is installed without holding up the UI, and whether it's all one version. It comes back
+/// as [Msg::CurrentDetected] and [Msg::MixedInstall].
+pub fn detect_current_release(manager: &Rc<RefCell<EldenRingManager>>) {
+    let (releases, dir) = { let manager = manager.borrow(); (manager.releases.clone(), manager.dir.clone()) };
+    let Some(dir) = dir else { return };
+    STATE.with(|s| if let Some(ref mut state) = *s.borrow_mut() { state.detecting += 1 });
+    tokio::spawn(async move {
+        send(Msg::CurrentDetected(manage::find_installed(releases.clone(), dir.clone()).await));
+        match tokio::task::spawn_blocking(move || manage::mixed_install(&releases, &dir)).await {
+            Ok(mixed) => send(Msg::MixedInstall(mixed)),
+            Err(e) => warn!("Checking for a mixed install failed: {}", e),
+        }
+    });
+}
+
+/// The releases with files in a mixed install (see [manage::mixed_install]).
+pub fn mixed_releases() -> Vec<String> {
+    let mut tags: Vec<String> = STATE.with(|s| s.borrow().as_ref().and_then(|s| s.mixed.clone())).unwrap_or_default()
+        .into_iter().flat_map(|(_, tags)| tags).collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// The GUI's manager, for windows that weren't handed it.
+pub fn manager() -> Option<Rc<RefCell<EldenRingManager>>> {
+    STATE.with(|s| s.borrow().as_ref().map(|s| s.manager.clone()))
+}
+
+/// Where the running manager gets its releases from.
+pub fn release_source() -> Option<Arc<dyn ReleaseSource>> {
+    STATE.with(|s| s.borrow().as_ref().map(|s| s.manager.borrow().source.clone()))
+}
+
+/// Has everything [detect_current_release()] was asked to check come back? If so, the manager's `current` is up to date.
+pub fn current_detected() -> bool {
+    STATE.with(|s| s.borrow().as_ref().is_none_or(|s| s.detecting == 0))
+}
+
+pub fn busy() -> bool {
+    STATE.with(|s| s.borrow().as_ref().is_some_and(|s| s.busy.is_some()))
+}
+
+/// Make the window match the state.
+pub fn view(win: &MainWindow) {
+    let Some((manager, refreshing, refresh_error, busy, mixed, manager_upgrade)) = STATE.with(|s| s.borrow().as_ref().map(|s| (s.manager.clone(), s.refreshing.is_some(), s.refresh_error.clone(), s.busy.clone(), s.mixed.clone(), s.manager_upgrade.clone()))) else { return };
+    win.set_mixed_install(mixed.map(|files| files.iter().map(|(path, tags)| format!("{} ({})", path.display(), tags.join(", "))).collect::<Vec<_>>().join(", ")).unwrap_or_default().into());
+    win.set_refreshing(refreshing);
+    win.set_busy(busy.is_some());
+    win.set_activity(busy.unwrap_or_default().into());
+    win.set_refresh_error(refresh_error.unwrap_or_default().into());
+    win.set_my_upgrade_version(manager_upgrade.unwrap_or_default().into());
+    crate::show_releases(win, &manager);
+}
@@ -0,0 +1,116 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Serialize, Deserialize};
+use tracing::{debug, info};
+
+use crate::{config, error::ErscomError, paths};
+
+const MAX_BACKUPS: usize = 10;
+
+/// How often to back up the game's saves (checked when the game is launched).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackupSchedule {
+    Never,
+    EveryLaunch,
+    Daily,
+    Weekly,
+}
+
+impl BackupSchedule {
+    // In the same order as the ComboBox in PreferencesWindow
+    pub const ALL: [BackupSchedule; 4] = [BackupSchedule::Never, BackupSchedule::EveryLaunch, BackupSchedule::Daily, BackupSchedule::Weekly];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BackupSchedule::Never       => "Never",
+            BackupSchedule::EveryLaunch => "Every launch",
+            BackupSchedule::Daily       => "Daily",
+            BackupSchedule::Weekly      => "Weekly",
+        }
+    }
+
+    fn interval(self) -> Option<chrono::Duration> {
+        match self {
+            BackupSchedule::Never       => None,
+            BackupSchedule::EveryLaunch => Some(chrono::Duration::zero()),
+            BackupSchedule::Daily       => Some(chrono::Duration::days(1)),
+            BackupSchedule::Weekly      => Some(chrono::Duration::weeks(1)),
+        }
+    }
+}
+
+/// Where Elden Ring keeps its saves (`%APPDATA%\EldenRing`, with a subdirectory per steam id).
+pub fn save_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("EldenRing"))
+}
+
+pub fn backup_dir() -> PathBuf {
+    paths::data_dir().join("save backups")
+}
+
+/// Back up the saves if the schedule in the config says it's time. Returns where the backup went, if one was made.
+pub fn backup_if_due() -> Result<Option<PathBuf>, ErscomError> {
+    let config = config::get();
+    let Some(interval) = config.backup_schedule.interval() else { return Ok(None) };
+    let now = chrono::Local::now();
+    if let Some(last) = config.last_backup {
+        if now.timestamp() - last < interval.num_seconds() {
+            debug!("Last backup was at {}, not due yet", last);
+            return Ok(None);
+        }
+    }
+    let Some(saves) = save_dir().filter(|d| d.is_dir()) else {
+        info!("No save directory found, nothing to back up");
+        return Ok(None);
+    };
+    let dest = backup_dir().join(now.format("%Y-%m-%d %H.%M.%S").to_string());
+    info!("Backing up {} to {}", saves.display(), dest.display());
+    copy_dir(&saves, &dest)?;
+    config::update(|c| c.last_backup = Some(now.timestamp()))?;
+    prune()?;
+    Ok(Some(dest))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), ErscomError> {
+    std::fs::create_dir_all(to).map_err(ErscomError::io(to))?;
+    for entry in std::fs::read_dir(from).map_err(ErscomError::io(from))? {
+        let entry = entry.map_err(ErscomError::io(from))?;
+        let (src, dest) = (entry.path(), to.join(entry.file_name()));
+        if entry.file_type().map_err(ErscomError::io(&src))?.is_dir() {
+            copy_dir(&src, &dest)?;
+        } else {
+            std::fs::copy(&src, &dest).map_err(ErscomError::io(&src))?;
+        }
+    }
+    Ok(())
+}
+
+// The backups are named by date so sorting them by name puts the oldest first.
+fn prune() -> Result<(), ErscomError> {
+    let dir = backup_dir();
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(&dir).map_err(ErscomError::io(&dir))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_dir())
+        .collect();
+    backups.sort();
+    for old in &backups[..backups.len().saturating_sub(MAX_BACKUPS)] {
+        info!("Removing old backup {}", old.display());
+        std::fs::remove_dir_all(old).map_err(ErscomError::io(old))?;
+    }
+    Ok(())
+}
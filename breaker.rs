@@ -19,6 +19,43 @@ pub struct Breaker<T>(std::ops::ControlFlow<Empty,T>); // Wrap ControlFlow for t
 impl<T> Breaker<T> {
     pub fn brk() -> Self { Breaker(std::ops::ControlFlow::Break(Empty)) }
     pub fn cont(t: T) -> Self { Breaker(std::ops::ControlFlow::Continue(t)) }
+
+    pub fn is_brk(&self) -> bool { self.0.is_break() }
+}
+
+#[allow(dead_code)] // Not all of these have callers yet
+impl<T> Breaker<T> {
+    // These work like their Result/Option namesakes, with a break playing the part of Err/None.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Breaker<U> {
+        Breaker::cont(f(self?))
+    }
+    pub fn and_then<U>(self, f: impl FnOnce(T) -> Breaker<U>) -> Breaker<U> {
+        f(self?)
+    }
+    /// Try something else if this broke (the error has already been reported by then).
+    pub fn or_else(self, f: impl FnOnce() -> Breaker<T>) -> Breaker<T> {
+        if self.is_brk() { f() } else { self }
+    }
+    /// Run `f` only if this broke, eg: `.try_error().inspect_brk(|| win.set_busy(false))?`
+    pub fn inspect_brk(self, f: impl FnOnce()) -> Self {
+        if self.is_brk() { f() }
+        self
+    }
+}
+
+/// Runs a closure when it goes out of scope, so cleanup happens no matter which `?` bails out:
+///
+///     let _idle = finally(|| win.set_busy(false));
+///     win.set_busy(true);
+///     thing().try_error()?;
+pub struct Finally<F: FnOnce()>(Option<F>);
+
+pub fn finally<F: FnOnce()>(f: F) -> Finally<F> { Finally(Some(f)) }
+
+impl<F: FnOnce()> Drop for Finally<F> {
+    fn drop(&mut self) {
+        if let Some(f) = self.0.take() { f() }
+    }
 }
 
 // fn x() { y()? }
@@ -33,6 +70,19 @@ impl std::ops::FromResidual<Empty> for bool {
         false
     }
 }
+// fn x() -> Option<T> { let y = z()?; Some(y) }
+impl<T> std::ops::FromResidual<Empty> for Option<T> {
+    fn from_residual(_residual: Empty) -> Self {
+        None
+    }
+}
+// fn x() -> Result<(), ErscomError> { y()?; Ok(()) }. The error has already been reported, so E just has to be able
+// to say so (see ErscomError::Reported).
+impl<E: From<Empty>> std::ops::FromResidual<Empty> for Result<(), E> {
+    fn from_residual(residual: Empty) -> Self {
+        Err(E::from(residual))
+    }
+}
 impl<T> std::ops::FromResidual<Empty> for Breaker<T> {
     fn from_residual(residual: Empty) -> Self {
         Breaker(std::ops::ControlFlow::Break(residual))
@@ -18,13 +18,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .ok_or(format!("Missing copyright in README"))?.trim().to_owned();
     println!("cargo::rerun-if-changed={}", env!("CARGO_PKG_README"));
     println!("cargo::rustc-env=COPYRIGHT={copyright}");
+    // For the About window. Builds from a tarball don't have one.
+    println!("cargo::rerun-if-changed=.git/HEAD");
+    println!("cargo::rerun-if-changed=.git/refs/heads");
+    if let Ok(out) = std::process::Command::new("git").args(["rev-parse", "--short", "HEAD"]).output() {
+        if out.status.success() {
+            println!("cargo::rustc-env=GIT_COMMIT={}", String::from_utf8_lossy(&out.stdout).trim());
+        }
+    }
+    println!("cargo::rerun-if-changed=translations");
+    slint_build::compile_with_config("erscom.slint", slint_build::CompilerConfiguration::new()
+                                     .with_bundled_translations("translations"))?;
     if std::env::var("CARGO_CFG_TARGET_OS").unwrap() == "windows" { // We can cross compile, so don't use cfg!(target_os = "windows")
         let mut res = winresource::WindowsResource::new();
         if which::which("x86_64-w64-mingw32-windres").is_ok() { // Are we cross-compiling?
             res.set_windres_path("x86_64-w64-mingw32-windres");
             res.set_ar_path("x86_64-w64-mingw32-ar");
         }
+        println!("cargo::rerun-if-changed=assets/erscom.exe.manifest");
         res.set_icon("assets/eldenringlogo.ico")
+            .set_manifest_file("assets/erscom.exe.manifest")
             .set("ProductName", "Elden Ring Seamless CoOp Manager")
             .set("InternalName", "Elden-Ring-Seamless-Co-Op-Manager")
             .set("LegalCopyright", copyright.trim_start_matches("Copyright "))
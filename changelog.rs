@@ -0,0 +1,79 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Picking out the parts of a release's notes that people really shouldn't miss.
+
+use std::sync::OnceLock;
+
+// How the mod's author tends to say "this one breaks things"
+const BREAKING: &[&str] = &[
+    r"(?i)(requires?|needs?|use|set) (a )?new (co-?op )?password",
+    r"(?i)password.*(changed|reset|no longer works)",
+    r"(?i)saves?( files?)?.*(incompatible|not compatible|won't work|will not work|wiped|deleted)",
+    r"(?i)(incompatible|not compatible) with (older|previous|earlier|old)",
+    r"(?i)(all|every(one)?|both) (players?|users?|of you)?.*(must|need to|have to|should) (update|upgrade|be on|use the same)",
+    r"(?i)breaking change",
+    r"(?i)(delete|replace|reset) (your )?(old )?(ersc_)?settings(\.ini)?",
+];
+
+fn regexes() -> &'static Vec<regex::Regex> {
+    static REGEXES: OnceLock<Vec<regex::Regex>> = OnceLock::new();
+    REGEXES.get_or_init(|| BREAKING.iter().map(|p| regex::Regex::new(p).unwrap()).collect())
+}
+
+/// The lines of `changelog` that warn about breaking changes, tidied up for showing on their own.
+pub fn breaking_changes(changelog: &str) -> Vec<String> {
+    changelog.lines()
+        .filter(|line| regexes().iter().any(|re| re.is_match(line)))
+        .map(|line| line.trim().trim_start_matches(['-', '*', '#', ' ']).trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Where `query` turns up in a release's notes.
+pub struct Match {
+    /// The line it's on, tidied up like [breaking_changes()] does.
+    pub line: String,
+    /// How far into the notes it is, from 0 to 1. Good enough for scrolling to it.
+    pub position: f32,
+}
+
+/// The lines of `changelog` with `query` in them (ignoring case and extra spaces).
+pub fn search(changelog: &str, query: &str) -> Vec<Match> {
+    let query = normalize(query);
+    if query.is_empty() { return vec![] }
+    let mut offset = 0;
+    changelog.split_inclusive('\n').filter_map(|line| {
+        let position = offset as f32 / changelog.len().max(1) as f32;
+        offset += line.len();
+        normalize(line).contains(&query).then(|| Match {
+            line: line.trim().trim_start_matches(['-', '*', '#', ' ']).trim().to_string(),
+            position,
+        })
+    }).collect()
+}
+
+/// `changelog` with the lines `query` is in marked, so they stand out once it's been scrolled to.
+pub fn highlight(changelog: &str, query: &str) -> String {
+    let query = normalize(query);
+    if query.is_empty() { return changelog.to_string() }
+    changelog.lines()
+        .map(|line| if normalize(line).contains(&query) { format!("▶ {}", line) } else { line.to_string() })
+        .collect::<Vec<_>>().join("\n")
+}
+
+fn normalize(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
@@ -0,0 +1,109 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Known-good SHA-256 hashes of the mod's release zips, published in the erscom repo. If a release has been swapped out
+// upstream (or messed with on the way here) the download won't match and doesn't get installed. The list is trusted
+// because it comes from the erscom repo over https; there's no separate signature. Releases that aren't in it (new
+// ones, usually) are installed like they always were.
+
+use std::{collections::HashMap, io::Read, path::Path, sync::Mutex};
+
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+
+use crate::{error::ErscomError, report, source::http_client};
+
+const MANIFEST_URL: &str = "https://raw.githubusercontent.com/caldwell/erscom/main/release-checksums.json";
+
+/// The published hash for `tag` (lowercase hex SHA-256 of its zip), if there is one. The list is fetched once per
+/// run, but not fetching it isn't remembered: the next call tries again.
+fn published(tag: &str) -> Result<Option<String>, ErscomError> {
+    static MANIFEST: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+    let mut manifest = MANIFEST.lock().unwrap();
+    if manifest.is_none() {
+        let fetched = fetch()?;
+        info!("Got checksums for {} releases", fetched.len());
+        *manifest = Some(fetched);
+    }
+    Ok(manifest.as_ref().and_then(|m| m.get(tag).cloned()))
+}
+
+fn fetch() -> Result<HashMap<String, String>, ErscomError> {
+    tokio::task::block_in_place(|| {
+        info!("GET {}", MANIFEST_URL);
+        let resp = http_client()?.get(MANIFEST_URL).header("User-Agent", "erscom 1.0").send()?;
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND { return Ok(HashMap::new()) } // Nothing published yet
+        if !status.is_success() { Err(ErscomError::GitHubApi { status: status.as_u16(), message: format!("Fetching {} failed", MANIFEST_URL) })? }
+        let manifest: HashMap<String, String> = resp.json()?;
+        Ok(manifest.into_iter().map(|(tag, hash)| (tag, hash.to_lowercase())).collect())
+    })
+}
+
+pub fn sha256(path: &Path) -> Result<String, ErscomError> {
+    let mut file = std::fs::File::open(path).map_err(ErscomError::io(path))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 64*1024];
+    loop {
+        let n = file.read(&mut buf).map_err(ErscomError::io(path))?;
+        if n == 0 { break }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Check `actual`, the SHA-256 of a freshly downloaded zip for `tag`, against the published hash, if there is one. If
+/// the list of hashes can't be fetched the zip is let through, but not without saying so.
+pub fn verify(tag: &str, actual: &str) -> Result<(), ErscomError> {
+    match published(tag) {
+        Ok(expected) => compare(tag, expected.as_deref(), actual),
+        Err(e) => {
+            warn!("Couldn't get the release checksums: {}", e);
+            report::warn(format!("Couldn't check {} against its published checksum: {}", tag, e).into());
+            Ok(())
+        },
+    }
+}
+
+fn compare(tag: &str, expected: Option<&str>, actual: &str) -> Result<(), ErscomError> {
+    let Some(expected) = expected else {
+        debug!("No published checksum for {}", tag);
+        return Ok(())
+    };
+    if actual != expected {
+        Err(ErscomError::ChecksumMismatch { tag: tag.to_string(), expected: expected.to_string(), actual: actual.to_string() })?
+    }
+    info!("{} matches its published checksum", tag);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpublished_tags_pass() {
+        assert!(compare("v0.0.0-unknown", None, "0123abcd").is_ok());
+    }
+
+    #[test]
+    fn mismatched_hashes_fail() {
+        assert!(compare("v1.0.0", Some("0123abcd"), "0123abcd").is_ok());
+        match compare("v1.0.0", Some("0123abcd"), "ffffffff") {
+            Err(ErscomError::ChecksumMismatch { tag, expected, actual }) => assert_eq!((tag.as_str(), expected.as_str(), actual.as_str()), ("v1.0.0", "0123abcd", "ffffffff")),
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+    }
+}
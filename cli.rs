@@ -0,0 +1,400 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{io::IsTerminal, path::{Path, PathBuf}, sync::Arc, time::Duration};
+
+use crate::error::ErscomError;
+use crate::manage::{self, EldenRingDir, EldenRingManager, FileErrorAction};
+use crate::report::{self, EXIT_USAGE};
+use crate::secret::Secret;
+use crate::source::{CacheSource, DirSource, GithubSource, ReleaseSource, StaticSource};
+
+const MOD_REPO: &str = "LukeYui/EldenRingSeamlessCoopRelease";
+// MOD_REPO's release list, published by .github/workflows/releases-json.yml
+const MOD_RELEASES_JSON: &str = "https://caldwell.github.io/erscom/releases.json";
+const MAX_RETRIES: usize = 3; // For --on-file-error retry
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    List,
+    Install(String),
+    Uninstall,
+    Launch,
+    Password(Option<Secret<String>>),
+    ExportState { path: PathBuf, include_releases: bool },
+    ImportState(PathBuf),
+    CheckGame,
+    Help,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    pub command: Option<Command>, // None means run the GUI
+    pub offline: bool,
+    pub releases_dir: Option<PathBuf>,
+    pub game_dir: Option<PathBuf>, // Instead of autodetecting it
+    pub cache_dir: Option<PathBuf>, // Instead of what's in the config
+    pub portable: bool, // Like there's a portable.txt next to the exe
+    pub repo: Option<String>, // Github project to get releases from
+    pub verbose: bool,
+    pub quick_launch: bool, // --launch
+    pub on_file_error: Option<FileErrorAction>, // None means ask, if there's a terminal to ask on
+    pub upgrade: bool, // install only replaces what changed from the installed version
+    pub show_panic: Option<String>, // See crash::install_panic_hook()
+}
+
+impl Args {
+    /// Where releases should come from, given the command line options.
+    pub fn release_source(&self) -> Result<Arc<dyn ReleaseSource>, ErscomError> {
+        Ok(match (self.offline, &self.releases_dir, &self.repo) {
+            (_, Some(dir), _)         => Arc::new(DirSource::new(dir)),
+            (true, None, _)           => Arc::new(CacheSource::new(&manage::cache_dir()?)),
+            (false, None, Some(repo)) => Arc::new(GithubSource::new(repo)),
+            (false, None, None)       => Arc::new(StaticSource::new(MOD_RELEASES_JSON, GithubSource::new(MOD_REPO))),
+        })
+    }
+
+    pub fn manager(&self) -> Result<EldenRingManager, ErscomError> {
+        let mut manager = EldenRingManager::with_source(self.release_source()?);
+        if let Some(ref dir) = self.game_dir {
+            if !dir.is_dir() { Err(ErscomError::NotFound(format!("Game directory {} doesn't exist", dir.display())))? }
+            manager.dir = Some(EldenRingDir::new(dir));
+        }
+        Ok(manager)
+    }
+
+    /// Make the options that aren't just about the manager take effect everywhere. Call before anything else uses them.
+    pub fn apply(&self) {
+        if self.portable {
+            crate::paths::set_portable();
+        }
+        if let Some(ref dir) = self.cache_dir {
+            manage::override_cache_dir(dir.clone());
+        }
+    }
+}
+
+const USAGE: &str = "\
+Usage: elden-ring-seamless-co-op-manager [options] [command]
+
+With no command the GUI is started.
+
+Options:
+  --offline               Only use releases that have already been downloaded
+  --releases-dir <dir>    Get releases from a local directory instead of github
+                          (a releases.json plus a <tag>.zip for each release)
+  --repo <owner/name>     Get releases from a different github project
+  --game-dir <dir>        Elden Ring's Game directory, instead of looking for it
+  --cache-dir <dir>       Where to keep downloaded releases (overrides
+                          Preferences)
+  --on-file-error <what>  What to do when a file can't be installed: retry (a
+                          few times), skip, or abort (undoing the install).
+                          Without this you get asked, or it aborts if there's
+                          no terminal to ask on.
+  --upgrade               Make install only replace the files that changed
+                          from the installed version, instead of uninstalling
+                          it first (if both are downloaded)
+  --portable              Keep the config, logs, and downloaded releases next
+                          to the exe instead of in your user folder (same as
+                          putting a portable.txt there)
+  --launch                Launch the game like the Launch button does, without
+                          the rest of the GUI. Errors are shown in a dialog.
+                          For desktop shortcuts.
+  -v, --verbose           Log more detail (and show it on stderr). Setting the
+                          ERSCOM_LOG environment variable (eg, ERSCOM_LOG=trace)
+                          overrides the log filter entirely.
+
+Commands:
+  list               List available mod versions
+  install <version>  Install a mod version (uninstalling the current one,
+                     unless --upgrade)
+  uninstall          Uninstall the current mod version
+  launch             Launch Elden Ring with the mod (installing a new version
+                     first if \"Check for a new version of the mod when
+                     launching\" is on in Preferences)
+  password [new]     Show the co-op password, or set it to <new>
+  export-state [--with-releases] <file>
+                     Save the manager's settings and the mod's settings (and
+                     the downloaded releases) to <file>, for moving to another
+                     PC
+  import-state <file>
+                     Bring in what export-state saved
+  check-game         Check the game folder for the basics the mod needs
+  help               Show this message";
+
+pub fn parse(mut args: impl Iterator<Item=String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--offline"      => { parsed.offline = true; continue },
+            "--verbose" | "-v" => { parsed.verbose = true; continue },
+            "--launch"       => { parsed.quick_launch = true; continue },
+            "--portable"     => { parsed.portable = true; continue },
+            "--upgrade"      => { parsed.upgrade = true; continue },
+            crate::crash::SHOW_PANIC_FLAG => { parsed.show_panic = args.next(); continue },
+            "--releases-dir" => { parsed.releases_dir = Some(args.next().ok_or(format!("--releases-dir needs a directory"))?.into()); continue },
+            "--game-dir"     => { parsed.game_dir = Some(args.next().ok_or("--game-dir needs a directory".to_string())?.into()); continue },
+            "--cache-dir"    => { parsed.cache_dir = Some(args.next().ok_or("--cache-dir needs a directory".to_string())?.into()); continue },
+            "--repo"         => { parsed.repo = Some(args.next().ok_or("--repo needs a github project (owner/name)".to_string())?); continue },
+            "--on-file-error" => {
+                parsed.on_file_error = Some(match args.next().as_deref() {
+                    Some("retry") => FileErrorAction::Retry,
+                    Some("skip")  => FileErrorAction::Skip,
+                    Some("abort") => FileErrorAction::Abort,
+                    _ => Err("--on-file-error needs retry, skip, or abort".to_string())?,
+                });
+                continue
+            },
+            _ => {},
+        }
+        if parsed.command.is_some() { Err(format!("Unexpected argument: {}", arg))? }
+        parsed.command = Some(match arg.as_str() {
+            "list"                => Command::List,
+            "install"             => Command::Install(args.next().ok_or(format!("install needs a version"))?),
+            "uninstall"           => Command::Uninstall,
+            "launch"              => Command::Launch,
+            "password"            => Command::Password(args.next().map(Secret::new)),
+            "export-state"        => {
+                let mut next = args.next();
+                let include_releases = next.as_deref() == Some("--with-releases");
+                if include_releases { next = args.next() }
+                Command::ExportState { path: next.ok_or("export-state needs a file to save to".to_string())?.into(), include_releases }
+            },
+            "import-state"        => Command::ImportState(args.next().ok_or("import-state needs a file".to_string())?.into()),
+            "check-game"          => Command::CheckGame,
+            "help" | "--help" | "-h" => Command::Help,
+            _ => Err(format!("Unknown command: {}", arg))?,
+        });
+    }
+    Ok(parsed)
+}
+
+/// Print a usage error and return the exit code for it.
+pub fn usage_error(message: &str) -> i32 {
+    eprintln!("{}\n\n{}", message, USAGE);
+    EXIT_USAGE
+}
+
+/// Run a CLI command to completion, returning the process exit code.
+pub fn run(args: &Args, command: Command) -> i32 {
+    report::set_cli_mode();
+    attach_console();
+    if let Some(notice) = crate::config::recovery_notice() { report::warn(notice.into()) }
+    if let Err(e) = run_command(args, command) {
+        report::error(e.into());
+    }
+    report::exit_code()
+}
+
+fn run_command(args: &Args, command: Command) -> Result<(), ErscomError> {
+    if command == Command::Help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let mut manager = args.manager()?;
+    manager.fetch_releases()?;
+    manager.detect_current_release();
+    let current_tag = manager.current.as_ref().map(|r| r.tag.clone());
+
+    match command {
+        Command::List => {
+            for r in manager.releases.iter() {
+                println!("{:<12} {}  {:<16} {}", r.tag, r.date, r.downloads.map(crate::status::downloads).unwrap_or_default(),
+                         match (r.downloaded(), current_tag.as_ref()) {
+                             (_, Some(cur_tag)) if cur_tag == &r.tag => "[ Installed ]",
+                             (true, _) => "[ Downloaded ]",
+                             _ => ""
+                         });
+            }
+        },
+        Command::Install(tag) => {
+            let Some(ref installdir) = manager.dir else { Err(ErscomError::NotFound(format!("Couldn't find Elden Ring directory")))? };
+            let release = manager.releases.iter().find(|r| r.tag == tag).ok_or(ErscomError::NotFound(format!("No mod version named {}", tag)))?;
+            // Better than failing halfway through
+            if !installdir.writable() { Err(ErscomError::NotWritable { path: installdir.path().to_path_buf() })? }
+            let skipped = match manager.current {
+                Some(ref current) if args.upgrade && release.can_upgrade_from(current) => {
+                    println!("Upgrading {} to {}", current.tag, release.tag);
+                    release.upgrade_from(current, installdir, file_error_handler(args))?
+                },
+                ref current => {
+                    if let Some(current) = current {
+                        println!("Uninstalling {}", current.tag);
+                        current.uninstall(installdir)?;
+                    }
+                    println!("Installing {}", release.tag);
+                    release.install_with(installdir, file_error_handler(args))?
+                },
+            };
+            report_skipped(&skipped);
+        },
+        Command::Uninstall => {
+            let (installdir, current) = manager.ok()?;
+            println!("Uninstalling {}", current.tag);
+            current.uninstall(installdir)?;
+        },
+        Command::Launch => {
+            if crate::config::get().update_before_launch {
+                update(&mut manager)?;
+            }
+            if let Some(backup) = crate::backup::backup_if_due()? {
+                println!("Backed up saves to {}", backup.display());
+            }
+            crate::launch(manager.launcher_path()?)?;
+        },
+        Command::Password(None) => {
+            println!("{}", manager.get_password()?.expose());
+        },
+        Command::Password(Some(password)) => {
+            manager.set_password(&password)?;
+        },
+        Command::ExportState { path, include_releases } => {
+            crate::transfer::export(&manager, &path, include_releases)?;
+            println!("Saved {}", path.display());
+        },
+        Command::ImportState(path) => {
+            println!("{}", crate::transfer::import(&manager, &path)?.describe());
+        },
+        Command::CheckGame => {
+            let Some(ref installdir) = manager.dir else { Err(ErscomError::NotFound("Couldn't find Elden Ring directory".to_string()))? };
+            let checks = crate::gamecheck::run(installdir);
+            for check in checks.iter() {
+                println!("[{}] {}: {}", if check.passed { " OK " } else { "FAIL" }, check.name, check.detail);
+                if !check.passed { println!("       {}", check.fix) }
+            }
+            let failed = checks.iter().filter(|c| !c.passed).count();
+            if failed > 0 { Err(format!("{} of {} checks failed", failed, checks.len()))? }
+        },
+        Command::Help => unreachable!(),
+    }
+    Ok(())
+}
+
+/// `--launch`: check the mod is installed, update it if the config says to, start the game, and exit. There's no
+/// console when it's run from a shortcut, so errors get the fatal dialog. Returns the process exit code.
+pub fn quick_launch(args: &Args) -> i32 {
+    crate::i18n::apply(crate::config::get().language.as_deref());
+    match quick_launch_game(args) {
+        Ok(()) => report::EXIT_OK,
+        Err(e) => {
+            report::fatal(e.into());
+            let _ = slint::run_event_loop(); // Until they click Abort
+            report::EXIT_ERROR
+        },
+    }
+}
+
+fn quick_launch_game(args: &Args) -> Result<(), ErscomError> {
+    let config = crate::config::get();
+    let mut manager = args.manager()?;
+    if config.update_before_launch {
+        manager.fetch_releases()?;
+    } else {
+        manager.load_snapshot(); // Only needed to find out what's installed, so don't bother github
+    }
+    manager.detect_current_release();
+    if manager.dir.is_none() { Err(ErscomError::NotFound("Couldn't find the Elden Ring directory. Run the manager to set it up.".to_string()))? }
+    if manager.current.is_none() { Err(ErscomError::NotFound("The co-op mod isn't installed. Run the manager to install it.".to_string()))? }
+    if config.update_before_launch {
+        update(&mut manager)?;
+    }
+    if let Err(e) = crate::backup::backup_if_due() {
+        tracing::warn!("Couldn't back up saves: {}", e); // Not worth stopping them from playing over
+    }
+    crate::launch(manager.launcher_path()?)?;
+    if config.copy_password_on_launch {
+        let password = manager.get_password()?;
+        if let Err(e) = arboard::Clipboard::new().and_then(|mut c| c.set_text(password.expose())) {
+            tracing::warn!("Couldn't copy the password: {}", e);
+        }
+    }
+    Ok(())
+}
+
+// Install the newest release if it's newer than what's there, keeping the password
+fn update(manager: &mut EldenRingManager) -> Result<(), ErscomError> {
+    let Some(newer) = manager.update_available(crate::config::get().show_beta).cloned() else { return Ok(()) };
+    let (installdir, current) = manager.ok()?;
+    let installdir = installdir.clone();
+    let password = manager.get_password().ok();
+    println!("Updating {} to {}", current.tag, newer.tag);
+    if installdir.writable() && newer.can_upgrade_from(current) {
+        newer.upgrade_from(current, &installdir, |_, _| FileErrorAction::Abort)?;
+    } else if installdir.writable() {
+        current.uninstall(&installdir)?;
+        newer.install(&installdir)?;
+    } else {
+        crate::elevate::install(&installdir, &newer, true)?;
+    }
+    manager.current = Some(newer);
+    if let Some(password) = password {
+        manager.set_password(&password)?;
+    }
+    Ok(())
+}
+
+/// What to do about a file that can't be installed: whatever --on-file-error says, or ask if someone's there to ask.
+fn file_error_handler(args: &Args) -> impl FnMut(&Path, &ErscomError) -> FileErrorAction {
+    let policy = args.on_file_error;
+    let mut retries = 0;
+    move |path, error| {
+        eprintln!("Couldn't install {}: {}", path.display(), error);
+        match policy {
+            Some(FileErrorAction::Retry) if retries < MAX_RETRIES => {
+                retries += 1;
+                std::thread::sleep(RETRY_DELAY);
+                FileErrorAction::Retry
+            },
+            Some(FileErrorAction::Retry) => { eprintln!("Giving up after {} retries", MAX_RETRIES); FileErrorAction::Abort },
+            Some(action) => action,
+            None if std::io::stdin().is_terminal() => ask_about_file(),
+            None => FileErrorAction::Abort,
+        }
+    }
+}
+
+fn ask_about_file() -> FileErrorAction {
+    loop {
+        eprint!("[R]etry, [S]kip this file, or [A]bort (undoing the install)? ");
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).unwrap_or(0) == 0 { return FileErrorAction::Abort } // EOF
+        match answer.trim().to_lowercase().chars().next() {
+            Some('r') => return FileErrorAction::Retry,
+            Some('s') => return FileErrorAction::Skip,
+            Some('a') => return FileErrorAction::Abort,
+            _ => {},
+        }
+    }
+}
+
+fn report_skipped(skipped: &[PathBuf]) {
+    if skipped.is_empty() { return }
+    eprintln!("These files were skipped and aren't installed:");
+    for path in skipped { eprintln!("  {}", path.display()) }
+}
+
+// Release builds use the "windows" subsystem so they don't get a console. Borrow our parent's (if there is one) so
+// that stdout and stderr go somewhere visible.
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    extern "system" { fn AttachConsole(process_id: u32) -> i32; }
+    const ATTACH_PARENT_PROCESS: u32 = -1i32 as u32;
+    unsafe { AttachConsole(ATTACH_PARENT_PROCESS); }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
@@ -0,0 +1,93 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// The host usually posts the co-op password in chat, and everyone else copies it and pastes it into the password box.
+// With this turned on, copying it is enough: while the window's in front, anything new on the clipboard that could be a
+// password gets offered with a "Use This Password" button.
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use slint::ComponentHandle;
+use tracing::{debug, info};
+
+use crate::{config, manage::{self, EldenRingManager}, report::UIError, secret::Secret, MainWindow};
+
+const POLL_EVERY: Duration = Duration::from_secs(1);
+// Passwords get typed in by hand, so they're short. Anything longer is probably a chat message or a link.
+const MAX_LEN: usize = 32;
+
+thread_local! {
+    static WATCHER: RefCell<Option<slint::Timer>> = const { RefCell::new(None) };
+    // Creating one of these is slow on some platforms, so it's kept around
+    static CLIPBOARD: RefCell<Option<arboard::Clipboard>> = const { RefCell::new(None) };
+    // What was on the clipboard last time it was checked (a hash, so it isn't holding on to anything private)
+    static LAST_SEEN: RefCell<Option<u32>> = const { RefCell::new(None) };
+    static OFFERED: RefCell<Option<Secret<String>>> = const { RefCell::new(None) };
+}
+
+/// Does this look like something that was copied to be the co-op password?
+pub fn looks_like_password(text: &str) -> Option<&str> {
+    let text = text.trim();
+    Some(text).filter(|t| manage::valid_password(t) && t.chars().count() <= MAX_LEN && !t.contains(char::is_whitespace) && !t.contains("://"))
+}
+
+/// Keep an eye on the clipboard while the window is in front (if it's turned on in the config).
+pub fn watch(win: &MainWindow, manager: &Rc<RefCell<EldenRingManager>>) {
+    let timer = slint::Timer::default();
+    let (weak_win, manager) = (win.as_weak(), manager.clone());
+    timer.start(slint::TimerMode::Repeated, POLL_EVERY, move || {
+        let Some(win) = weak_win.upgrade() else { return };
+        if !config::get().watch_clipboard || !crate::window_active(&win) { return }
+        let Some(text) = clipboard_text() else { return };
+        let hash = crc32fast::hash(text.as_bytes());
+        if LAST_SEEN.with(|l| *l.borrow()) == Some(hash) { return }
+        let Ok(manager) = manager.try_borrow() else { return }; // Busy, it'll still be there next time
+        LAST_SEEN.with(|l| *l.borrow_mut() = Some(hash));
+        let Some(password) = looks_like_password(&text) else { return };
+        if !manager.found_dir() { return }
+        if manager.get_password().ok().is_some_and(|p| p.expose() == password) { return } // Probably copied it from us
+        debug!("Found a possible co-op password on the clipboard");
+        offer(&win, Some(Secret::new(password.to_string())));
+    });
+    WATCHER.with(|w| *w.borrow_mut() = Some(timer));
+}
+
+fn clipboard_text() -> Option<String> {
+    CLIPBOARD.with(|c| {
+        let mut c = c.borrow_mut();
+        if c.is_none() { *c = arboard::Clipboard::new().ok() }
+        c.as_mut()?.get_text().ok()
+    })
+}
+
+fn offer(win: &MainWindow, password: Option<Secret<String>>) {
+    win.set_clipboard_password(password.as_ref().map(|p| p.expose().as_str()).unwrap_or_default().into());
+    OFFERED.with(|o| *o.borrow_mut() = password);
+}
+
+/// Make the offered password the co-op password.
+pub fn accept(win: &MainWindow, manager: &EldenRingManager) {
+    let Some(password) = OFFERED.with(|o| o.borrow_mut().take()) else { return };
+    offer(win, None);
+    manager.set_password(&password).try_error()?;
+    win.set_password(password.expose().into());
+    info!("Using the co-op password from the clipboard");
+    crate::toast("Using the co-op password from the clipboard");
+}
+
+/// Don't use it. Nothing's offered again until something else gets copied.
+pub fn dismiss(win: &MainWindow) {
+    offer(win, None);
+}
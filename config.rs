@@ -0,0 +1,368 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{collections::{BTreeMap, BTreeSet}, fs::File, path::{Path, PathBuf}, sync::{Mutex, OnceLock, atomic::{AtomicBool, Ordering}}};
+
+use serde_json::Value;
+
+use serde::{Serialize, Deserialize};
+
+use crate::{backup::BackupSchedule, error::ErscomError, paths, secret::Secret};
+
+/// The manager's own settings (as opposed to the mod's, which live in its ini file).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)] // So old config files keep working as things get added
+pub struct Config {
+    /// Which [CONFIG_VERSION] wrote it. Files from before there was one are 0.
+    pub config_version: u32,
+    pub window: Option<WindowGeometry>,
+    pub settings_view: SettingsView,
+    /// Tag of the release last picked in the version dropdown.
+    pub selected_release: Option<String>,
+    /// Where the game was, and which release was installed there, last time we looked. Lets the GUI show something
+    /// right away at startup instead of waiting on github and the install check.
+    pub last_install_path: Option<PathBuf>,
+    pub last_installed: Option<String>,
+    /// The release the manager last installed, and when (in seconds since the epoch).
+    pub installed_at: Option<(String, i64)>,
+    pub show_beta: bool,
+    /// Don't offer updates while this release is installed.
+    pub pinned_release: Option<String>,
+    /// Releases that are never offered as updates (known bad ones, usually).
+    pub ignored_releases: Vec<String>,
+    /// The user's own notes about releases ("worked great for our 4-player run"), by tag.
+    pub release_notes: BTreeMap<String, String>,
+    /// Release to install once the game exits (see [crate::queue]).
+    pub pending_install: Option<String>,
+    /// Where downloaded release zips go. `None` means next to the exe, like it always has been.
+    pub cache_dir: Option<PathBuf>,
+    /// Proxy url for everything we fetch (eg, `http://proxy.example.com:8080`).
+    pub proxy: Option<String>,
+    /// Look for a new version of the manager at startup.
+    pub check_for_updates: bool,
+    /// Which of the manager's own releases count as new versions.
+    pub manager_channel: ManagerChannel,
+    pub after_launch: AfterLaunch,
+    /// Put the co-op password on the clipboard when Launch is clicked, for pasting to the rest of the group.
+    pub copy_password_on_launch: bool,
+    /// Offer the eye button that shows the co-op password. Off for streaming, so it can't be shown by accident.
+    pub allow_password_reveal: bool,
+    /// Offer to use a co-op password that gets copied while the window's in front (see [crate::clipwatch]).
+    pub watch_clipboard: bool,
+    /// Offer to install a newer release (if there is one) when Launch is clicked.
+    pub update_before_launch: bool,
+    /// How often the GUI re-fetches the release list while it's open.
+    pub auto_refresh: AutoRefresh,
+    pub backup_schedule: BackupSchedule,
+    /// When the saves were last backed up, in seconds since the epoch.
+    pub last_backup: Option<i64>,
+    /// Personal access token (with gist access) for uploading settings to share.
+    pub github_token: Option<Secret<String>>,
+    /// The gist settings were last uploaded to, so uploading again updates it instead of making another.
+    pub settings_gist: Option<String>,
+    /// Link to the settings last uploaded or imported.
+    pub settings_url: Option<String>,
+    /// UI language code (see [crate::i18n::LANGUAGES]). `None` follows the OS.
+    pub language: Option<String>,
+    pub theme: Theme,
+    /// Show dates as "2 weeks ago" instead of "May 3, 2024".
+    pub relative_dates: bool,
+    /// Solid backgrounds and full-strength text instead of see-through frames over the artwork.
+    pub high_contrast: bool,
+    /// Let companion tools (stream decks, overlays, scripts) drive the GUI over a local socket (see [crate::ipc]).
+    pub control_server: bool,
+    /// Whatever a newer manager saved that this one doesn't know about. It's written back out as is, so going back to
+    /// an older version for a bit doesn't throw away the newer one's settings.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            config_version: CONFIG_VERSION,
+            window: None,
+            settings_view: SettingsView::default(),
+            selected_release: None,
+            last_install_path: None,
+            last_installed: None,
+            installed_at: None,
+            show_beta: false,
+            pinned_release: None,
+            ignored_releases: vec![],
+            release_notes: BTreeMap::new(),
+            pending_install: None,
+            cache_dir: None,
+            proxy: None,
+            check_for_updates: true,
+            manager_channel: ManagerChannel::Stable,
+            after_launch: AfterLaunch::StayOpen,
+            copy_password_on_launch: false,
+            allow_password_reveal: true,
+            watch_clipboard: false,
+            update_before_launch: false,
+            auto_refresh: AutoRefresh::Hourly,
+            backup_schedule: BackupSchedule::Never,
+            last_backup: None,
+            github_token: None,
+            settings_gist: None,
+            settings_url: None,
+            language: None,
+            theme: Theme::Dark,
+            relative_dates: false,
+            high_contrast: false,
+            control_server: false,
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+/// What the manager does with itself once the game is launched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AfterLaunch {
+    StayOpen,
+    Minimize,
+    Exit,
+}
+
+impl AfterLaunch {
+    // In the same order as the ComboBox in PreferencesWindow
+    pub const ALL: [AfterLaunch; 3] = [AfterLaunch::StayOpen, AfterLaunch::Minimize, AfterLaunch::Exit];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AfterLaunch::StayOpen => "Stay open",
+            AfterLaunch::Minimize => "Minimize",
+            AfterLaunch::Exit     => "Exit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutoRefresh {
+    Never,
+    Every15Minutes,
+    Hourly,
+    Every6Hours,
+}
+
+impl AutoRefresh {
+    // In the same order as the ComboBox in PreferencesWindow
+    pub const ALL: [AutoRefresh; 4] = [AutoRefresh::Never, AutoRefresh::Every15Minutes, AutoRefresh::Hourly, AutoRefresh::Every6Hours];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AutoRefresh::Never          => "Never",
+            AutoRefresh::Every15Minutes => "Every 15 minutes",
+            AutoRefresh::Hourly         => "Every hour",
+            AutoRefresh::Every6Hours    => "Every 6 hours",
+        }
+    }
+
+    pub fn interval(self) -> Option<chrono::Duration> {
+        match self {
+            AutoRefresh::Never          => None,
+            AutoRefresh::Every15Minutes => Some(chrono::Duration::minutes(15)),
+            AutoRefresh::Hourly         => Some(chrono::Duration::hours(1)),
+            AutoRefresh::Every6Hours    => Some(chrono::Duration::hours(6)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ManagerChannel {
+    Stable,
+    Beta, // Pre-releases too
+}
+
+impl ManagerChannel {
+    // In the same order as the ComboBox in PreferencesWindow
+    pub const ALL: [ManagerChannel; 2] = [ManagerChannel::Stable, ManagerChannel::Beta];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ManagerChannel::Stable => "Stable releases",
+            ManagerChannel::Beta   => "Beta (pre-releases too)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    Dark,
+    Light,
+    System,
+}
+
+impl Theme {
+    // In the same order as the ComboBox in PreferencesWindow
+    pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::System];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Dark   => "Dark",
+            Theme::Light  => "Light",
+            Theme::System => "Same as the system",
+        }
+    }
+
+    pub fn color_theme(self) -> crate::ColorTheme {
+        match self {
+            Theme::Dark   => crate::ColorTheme::Dark,
+            Theme::Light  => crate::ColorTheme::Light,
+            Theme::System => crate::ColorTheme::System,
+        }
+    }
+}
+
+/// In physical pixels, like slint reports them.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+/// Where things were left in the settings window, so it opens back up the same way.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsView {
+    pub window: Option<WindowGeometry>,
+    /// Name of the file whose tab was showing.
+    pub file: Option<String>,
+    /// How far down each file was scrolled (in logical pixels), by file name.
+    pub scroll: BTreeMap<String, f32>,
+    /// Sections that were collapsed, by file name.
+    pub collapsed: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Bump this when the config changes in a way `#[serde(default)]` can't cope with, and add to [MIGRATIONS].
+pub const CONFIG_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` turns a version `n` config into a version `n+1` one. They work on the raw json, since the old
+/// format won't deserialize into the current [Config].
+const MIGRATIONS: [fn(&mut Value); CONFIG_VERSION as usize] = [
+    // 0 -> 1: Nothing changed, it just didn't say what version it was
+    |_| {},
+];
+
+pub fn path() -> PathBuf {
+    paths::data_dir().join("config.json")
+}
+
+// Set when the config file couldn't be read and got moved out of the way. See [recovery_notice].
+static RECOVERED: OnceLock<String> = OnceLock::new();
+
+fn config() -> &'static Mutex<Config> {
+    static CONFIG: OnceLock<Mutex<Config>> = OnceLock::new();
+    CONFIG.get_or_init(|| Mutex::new(Config::load().unwrap_or_else(|e| {
+        tracing::warn!("Couldn't load config, using defaults: {}", e);
+        let _ = RECOVERED.set(match set_aside() {
+            Ok(Some(aside)) => format!("Your preferences couldn't be read ({}), so they've been reset. The old file was saved as {}.", e, aside.display()),
+            Ok(None) => format!("Your preferences couldn't be read ({}), so they've been reset.", e),
+            Err(e2) => format!("Your preferences couldn't be read ({}), so they've been reset. The old file couldn't be moved aside either: {}", e, e2),
+        });
+        Config::default()
+    })))
+}
+
+// Rename a config that won't load, so that saving the defaults doesn't wipe it out
+fn set_aside() -> Result<Option<PathBuf>, ErscomError> {
+    let path = path();
+    if !path.exists() { return Ok(None) }
+    let aside = path.with_file_name(format!("config.json.bad-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::rename(&path, &aside).map_err(ErscomError::io(&path))?;
+    tracing::info!("Moved unreadable config to {}", aside.display());
+    Ok(Some(aside))
+}
+
+/// If the config had to be reset because it couldn't be read, what to tell people about it. Only returns it once.
+pub fn recovery_notice() -> Option<String> {
+    config(); // Make sure it's been loaded
+    static TOLD: AtomicBool = AtomicBool::new(false);
+    if TOLD.swap(true, Ordering::Relaxed) { return None }
+    RECOVERED.get().cloned()
+}
+
+/// A copy of the current config.
+pub fn get() -> Config {
+    config().lock().unwrap().clone()
+}
+
+/// Change the config and write it back out.
+pub fn update(f: impl FnOnce(&mut Config)) -> Result<(), ErscomError> {
+    let mut config = config().lock().unwrap();
+    f(&mut config);
+    config.save()
+}
+
+impl Config {
+    fn load() -> Result<Config, ErscomError> {
+        let path = path();
+        let json: Value = match File::open(&path) {
+            Ok(file) => serde_json::from_reader(file).map_err(ErscomError::json(&path))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => Err(ErscomError::io(&path)(e))?,
+        };
+        Config::from_json(json, &path)
+    }
+
+    /// A config of any version (`path` is where it came from, for errors), brought up to date.
+    pub fn from_json(mut json: Value, path: &Path) -> Result<Config, ErscomError> {
+        let version = json.get("config_version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        if version > CONFIG_VERSION {
+            // From a newer manager. What it added ends up in `extra` and the rest should still be good.
+            tracing::warn!("Config is version {}, newer than the {} this version knows about", version, CONFIG_VERSION);
+        }
+        for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            tracing::info!("Migrating config from version {} to {}", from, from + 1);
+            migrate(&mut json);
+        }
+        let mut config: Config = serde_json::from_value(json).map_err(ErscomError::json(path))?;
+        // Saying it's older than it is would get the newer manager to migrate it again
+        config.config_version = version.max(CONFIG_VERSION);
+        Ok(config)
+    }
+
+    fn save(&self) -> Result<(), ErscomError> {
+        let path = path();
+        let dir = paths::data_dir();
+        std::fs::create_dir_all(&dir).map_err(ErscomError::io(&dir))?;
+        tracing::debug!("Writing {}", path.display());
+        // Written alongside and then renamed over, so a crash halfway through can't leave half a config
+        let temp = path.with_extension("json.new");
+        serde_json::to_writer_pretty(File::create(&temp).map_err(ErscomError::io(&temp))?, self).map_err(ErscomError::json(&temp))?;
+        std::fs::rename(&temp, &path).map_err(ErscomError::io(&path))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_config_keeps_what_it_added() {
+        let newer = serde_json::json!({ "config_version": CONFIG_VERSION + 1, "show_beta": true, "from_the_future": { "a": 1 } });
+        let config = Config::from_json(newer, Path::new("config.json")).unwrap();
+        assert!(config.show_beta);
+        let saved = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["config_version"], CONFIG_VERSION + 1);
+        assert_eq!(saved["from_the_future"], serde_json::json!({ "a": 1 }));
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{notifier::{Level, Notice, UiNotifier}, paths, report};
+
+/// Hidden command line flag that makes us show the fatal dialog for a panic that happened in another process.
+pub const SHOW_PANIC_FLAG: &str = "--show-panic";
+
+const EXIT_PANIC: i32 = 101; // Same as an uncaught panic normally exits with
+
+/// Make panics log a backtrace and show the fatal dialog instead of silently vanishing (there's no console in
+/// release builds).
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let location = info.location().map(|l| format!(" at {}:{}", l.file(), l.line())).unwrap_or_default();
+        let message = info.payload().downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or("Unknown panic".to_string());
+        tracing::error!("Panic{}: {}\n{}", location, message, std::backtrace::Backtrace::force_capture());
+
+        let showing_panic = std::env::args().any(|a| a == SHOW_PANIC_FLAG); // Don't recurse forever
+        if report::cli_mode() || showing_panic {
+            eprintln!("Internal error{}: {}\nDetails were written to the log in {}", location, message, paths::log_dir().display());
+        } else {
+            // We may be deep inside a slint callback so the event loop isn't in any shape to show a dialog. Get a
+            // fresh copy of ourselves to do it.
+            if let Err(e) = std::env::current_exe().and_then(|exe| std::process::Command::new(exe).arg(SHOW_PANIC_FLAG).arg(&message).spawn()) {
+                tracing::error!("Couldn't start a process to show the panic: {}", e);
+            }
+        }
+        std::process::exit(EXIT_PANIC);
+    }));
+}
+
+/// The other half of [install_panic_hook()]: show the fatal dialog and wait for the user to dismiss it.
+pub fn show_panic(message: &str) -> Result<(), slint::PlatformError> {
+    UiNotifier::post(Notice { level: Level::Fatal, hint: None,
+                              message: format!("Something went very wrong inside the manager: {}\n\n\
+                                                Details were written to the log in {}", message, paths::log_dir().display()) });
+    slint::run_event_loop()
+}
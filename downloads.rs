@@ -0,0 +1,200 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Downloading releases without installing them, for people who like to have a few versions on hand. Picked ones go on
+// a queue and a few of them download at a time.
+
+use std::{cell::RefCell, collections::HashSet, rc::Rc, sync::{Arc, Mutex}, time::Duration};
+
+use slint::ComponentHandle;
+use tracing::{info, warn};
+
+use crate::{app, error::ErscomError, manage::Release, report::UIError, source::Progress, status, DownloadChoice, DownloadRow, DownloadsWindow};
+
+// Any more than this and they just slow each other down
+const AT_ONCE: usize = 3;
+
+enum JobState {
+    Queued,
+    Downloading,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+struct Job {
+    release: Release,
+    progress: Arc<Progress>,
+    state: JobState,
+}
+
+impl Job {
+    fn finished(&self) -> bool {
+        matches!(self.state, JobState::Done | JobState::Failed(_) | JobState::Cancelled)
+    }
+}
+
+// Everything that's been queued this run, oldest first. The downloads update it from their own threads.
+static JOBS: Mutex<Vec<Job>> = Mutex::new(vec![]);
+
+/// Download `releases` in the background, unless they're already on their way.
+pub fn enqueue(releases: Vec<Release>) {
+    {
+        let mut jobs = JOBS.lock().unwrap();
+        for release in releases {
+            if jobs.iter().any(|j| j.release.tag == release.tag && !j.finished()) { continue }
+            jobs.retain(|j| j.release.tag != release.tag); // An old failed or cancelled try
+            info!("Queueing a download of {}", release.tag);
+            jobs.push(Job { release, progress: Arc::new(Progress::default()), state: JobState::Queued });
+        }
+    }
+    start_more();
+}
+
+// Start queued downloads until AT_ONCE of them are going.
+fn start_more() {
+    let mut jobs = JOBS.lock().unwrap();
+    while jobs.iter().filter(|j| matches!(j.state, JobState::Downloading)).count() < AT_ONCE {
+        let Some(job) = jobs.iter_mut().find(|j| matches!(j.state, JobState::Queued)) else { break };
+        job.state = JobState::Downloading;
+        let (release, progress) = (job.release.clone(), job.progress.clone());
+        tokio::task::spawn_blocking(move || {
+            let downloaded = release.download_with(&progress);
+            finish(&release.tag, downloaded.map(|_| ()));
+        });
+    }
+}
+
+fn finish(tag: &str, downloaded: Result<(), ErscomError>) {
+    {
+        let mut jobs = JOBS.lock().unwrap();
+        let Some(job) = jobs.iter_mut().find(|j| j.release.tag == tag && !j.finished()) else { return };
+        job.state = match downloaded {
+            Ok(()) => { info!("Downloaded {}", tag); JobState::Done },
+            Err(ErscomError::Cancelled) => { info!("Cancelled the download of {}", tag); JobState::Cancelled },
+            Err(e) => { warn!("Downloading {} failed: {}", tag, e); JobState::Failed(e.to_string()) },
+        };
+    }
+    start_more();
+}
+
+/// Stop downloading `tag` (or don't start, if it hasn't yet).
+pub fn cancel(tag: &str) {
+    let mut jobs = JOBS.lock().unwrap();
+    let Some(job) = jobs.iter_mut().find(|j| j.release.tag == tag && !j.finished()) else { return };
+    match job.state {
+        JobState::Queued => job.state = JobState::Cancelled,
+        _ => job.progress.cancel(),
+    }
+}
+
+fn rows() -> Vec<DownloadRow> {
+    JOBS.lock().unwrap().iter().map(|job| {
+        let (status, active) = match job.state {
+            JobState::Queued => ("Waiting...".to_string(), true),
+            JobState::Downloading => (match job.progress.fraction() {
+                Some(fraction) => format!("{:.0}%", fraction * 100.0),
+                None => status::size(job.progress.done()),
+            }, true),
+            JobState::Done => ("Downloaded".to_string(), false),
+            JobState::Failed(ref e) => (format!("Failed: {}", e), false),
+            JobState::Cancelled => ("Cancelled".to_string(), false),
+        };
+        let progress = match job.state {
+            JobState::Downloading => job.progress.fraction().unwrap_or(0.0),
+            JobState::Done => 1.0,
+            _ => 0.0,
+        };
+        DownloadRow { tag: job.release.tag.clone().into(), status: status.into(), progress, active }
+    }).collect()
+}
+
+/// The window for picking releases from `releases` to download, and watching them come in.
+pub fn show_downloads_window(releases: Vec<Release>) {
+    let win = DownloadsWindow::new().try_error()?;
+    win.set_theme(crate::config::get().theme.color_theme());
+    win.set_high_contrast(crate::config::get().high_contrast);
+    let selected: Rc<RefCell<HashSet<String>>> = Rc::new(RefCell::new(HashSet::new()));
+
+    let show_choices = {
+        let weak_win = win.as_weak();
+        let (releases, selected) = (releases.clone(), selected.clone());
+        move || {
+            let Some(win) = weak_win.upgrade() else { return };
+            win.set_choices(Rc::new(slint::VecModel::from(releases.iter().map(|r| DownloadChoice {
+                tag: r.tag.clone().into(),
+                downloaded: r.downloaded(),
+                checked: selected.borrow().contains(&r.tag),
+            }).collect::<Vec<_>>())).into());
+        }
+    };
+    show_choices();
+
+    let update = {
+        let weak_win = win.as_weak();
+        let show_choices = show_choices.clone();
+        let finished = Rc::new(RefCell::new(0));
+        move || {
+            let Some(win) = weak_win.upgrade() else { return };
+            let rows = rows();
+            let done = rows.iter().filter(|r| !r.active).count();
+            win.set_downloads(Rc::new(slint::VecModel::from(rows)).into());
+            if done != *finished.borrow() {
+                *finished.borrow_mut() = done;
+                show_choices();
+                // Get "[ Downloaded ]" showing up in the main window's list too
+                if let Some(main_win) = crate::main_window() { app::view(&main_win) }
+            }
+        }
+    };
+    update();
+
+    let timer = slint::Timer::default();
+    timer.start(slint::TimerMode::Repeated, Duration::from_millis(250), update.clone());
+
+    win.on_toggle_choice({
+        let (selected, show_choices) = (selected.clone(), show_choices.clone());
+        move |tag, checked| {
+            match checked {
+                true => selected.borrow_mut().insert(tag.to_string()),
+                false => selected.borrow_mut().remove(tag.as_str()),
+            };
+            show_choices();
+        }
+    });
+    win.on_download_selected({
+        let update = update.clone();
+        move || {
+            let picked: Vec<Release> = releases.iter().filter(|r| selected.borrow().contains(&r.tag) && !r.downloaded()).cloned().collect();
+            selected.borrow_mut().clear();
+            enqueue(picked);
+            show_choices();
+            update();
+        }
+    });
+    win.on_cancel(move |tag| {
+        cancel(&tag);
+        update();
+    });
+    win.on_close({
+        let weak_win = win.as_weak();
+        move || {
+            // The downloads keep going, they just aren't being watched
+            timer.stop();
+            weak_win.unwrap().hide().try_log("closing downloads window")?;
+        }
+    });
+    win.show().try_log("showing downloads window")?;
+}
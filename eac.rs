@@ -0,0 +1,107 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// EasyAntiCheat files that have been tampered with. The mod doesn't need any of this (its launcher skips EAC on its
+// own), but guides around the internet tell people to rename or replace `start_protected_game.exe`, and then the
+// unmodded game won't start online.
+
+use std::path::PathBuf;
+
+use tracing::{info, warn};
+
+use crate::{error::ErscomError, manage::EldenRingDir};
+
+const EAC_LAUNCHER: &str = "start_protected_game.exe";
+const EAC_DIR: &str = "EasyAntiCheat";
+// What people tend to rename the original to
+const BACKUP_NAMES: &[&str] = &["start_protected_game.exe.bak", "start_protected_game.bak", "start_protected_game_original.exe",
+                                "start_protected_game.exe.original", "start_protected_game.exe.old", "start_protected_game_backup.exe"];
+// Left behind by anti-cheat togglers and "offline mode" guides
+const EXTRA_FILES: &[&str] = &["toggle_anti_cheat.exe", "steam_appid.txt"];
+const STEAM_VALIDATE_URL: &str = "steam://validate/1245620";
+
+pub struct Change {
+    pub description: String,
+    fix: Fix,
+}
+
+enum Fix {
+    Rename { from: PathBuf, to: PathBuf },
+    Remove(PathBuf),
+    Verify,
+}
+
+/// Everything that's different from a stock install.
+pub fn changes(installdir: &EldenRingDir) -> Vec<Change> {
+    let dir = installdir.path();
+    // No game here at all isn't our problem to report
+    if !dir.join("eldenring.exe").is_file() { return vec![] }
+    let mut changes = vec![];
+    let launcher = dir.join(EAC_LAUNCHER);
+    let backup = BACKUP_NAMES.iter().map(|name| dir.join(name)).find(|p| p.is_file());
+    let same_size = |a: &PathBuf, b: &PathBuf| matches!((std::fs::metadata(a), std::fs::metadata(b)), (Ok(a), Ok(b)) if a.len() == b.len());
+    match (launcher.is_file(), backup) {
+        (false, Some(backup)) =>
+            changes.push(Change { description: format!("{} was renamed to {}", EAC_LAUNCHER, backup.file_name().unwrap().to_string_lossy()),
+                                  fix: Fix::Rename { from: backup, to: launcher } }),
+        (false, None) =>
+            changes.push(Change { description: format!("{} is missing", EAC_LAUNCHER), fix: Fix::Verify }),
+        // The usual trick is to copy eldenring.exe over it
+        (true, backup) if same_size(&launcher, &dir.join("eldenring.exe")) =>
+            changes.push(Change { description: format!("{} was replaced with a copy of eldenring.exe", EAC_LAUNCHER),
+                                  fix: match backup { Some(backup) => Fix::Rename { from: backup, to: launcher }, None => Fix::Verify } }),
+        _ => {},
+    }
+    if !dir.join(EAC_DIR).is_dir() {
+        changes.push(Change { description: format!("The {} folder is missing", EAC_DIR), fix: Fix::Verify });
+    }
+    for extra in EXTRA_FILES.iter().map(|name| dir.join(name)).filter(|p| p.is_file()) {
+        changes.push(Change { description: format!("{} isn't part of the game", extra.file_name().unwrap().to_string_lossy()),
+                              fix: Fix::Remove(extra) });
+    }
+    for change in changes.iter() { warn!("EAC: {}", change.description) }
+    changes
+}
+
+/// Put back what we can. Returns true if Steam still needs to verify the game files to get the rest (that gets
+/// started too).
+pub fn restore(installdir: &EldenRingDir) -> Result<bool, ErscomError> {
+    let mut verify = false;
+    for change in changes(installdir) {
+        match change.fix {
+            Fix::Rename { from, to } => {
+                // Don't throw away whatever is in the way, just in case
+                if to.exists() {
+                    let aside = to.with_extension("exe.erscom-removed");
+                    std::fs::rename(&to, &aside).map_err(ErscomError::io(&to))?;
+                }
+                info!("Renaming {} back to {}", from.display(), to.display());
+                std::fs::rename(&from, &to).map_err(ErscomError::io(&from))?;
+            },
+            Fix::Remove(path) => {
+                let aside = path.with_file_name(format!("{}.erscom-removed", path.file_name().unwrap().to_string_lossy()));
+                info!("Moving {} out of the way", path.display());
+                std::fs::rename(&path, &aside).map_err(ErscomError::io(&path))?;
+            },
+            Fix::Verify => verify = true,
+        }
+        crate::history::record(format!("Restored EAC: {}", change.description));
+    }
+    if verify {
+        info!("Asking Steam to verify the game files");
+        webbrowser::open(STEAM_VALIDATE_URL).map_err(|e| format!("Couldn't ask Steam to verify the game files: {}", e))?;
+    }
+    Ok(verify)
+}
@@ -0,0 +1,146 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// When the game lives somewhere only administrators can write (Program Files, usually) the install is handed off to
+// a copy of ourselves running as administrator. It's just the `install` command from cli.rs, working from the cache so
+// it doesn't have to go to github again.
+//
+// Its window is hidden, so there's nobody to ask when a file can't be written (the game or an antivirus holding on to
+// it, usually). It retries a few times instead, and if that doesn't work the whole install is undone.
+
+use crate::error::ErscomError;
+use crate::manage::{EldenRingDir, Release};
+
+/// Install `release` into `installdir` from an elevated copy of ourselves. With `upgrade` it only replaces the files
+/// that changed from what's installed (when it can, see [Release::can_upgrade_from()]), otherwise it uninstalls whatever's
+/// there first. This brings up the UAC prompt and waits for it to finish.
+#[cfg(target_os = "windows")]
+pub fn install(installdir: &EldenRingDir, release: &Release, upgrade: bool) -> Result<(), ErscomError> {
+    use crate::manage;
+
+    release.download()?; // So the elevated copy can work offline
+    let cache_dir = manage::cache_dir()?;
+    let mut args = vec![
+        "--offline".to_string(),
+        "--cache-dir".to_string(), cache_dir.to_string_lossy().into_owned(),
+        "--game-dir".to_string(), installdir.path().to_string_lossy().into_owned(),
+        "--on-file-error".to_string(), "retry".to_string(),
+        "install".to_string(), release.tag.clone(),
+    ];
+    if crate::paths::portable() { args.insert(0, "--portable".to_string()) } // So its log ends up with ours
+    if upgrade { args.insert(0, "--upgrade".to_string()) }
+    tracing::info!("{} {} as administrator (files that can't be written are retried, then the install is undone)",
+                   if upgrade { "Upgrading to" } else { "Installing" }, release.tag);
+    match run_as_admin(&args)? {
+        crate::report::EXIT_OK => Ok(()),
+        code => Err(ErscomError::Other(format!("Installing {} as administrator failed (exit code {}). The log has the details.", release.tag, code))),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn run_as_admin(args: &[String]) -> Result<i32, ErscomError> {
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+
+    #[repr(C)]
+    struct ShellExecuteInfoW {
+        size: u32,
+        mask: u32,
+        hwnd: *mut c_void,
+        verb: *const u16,
+        file: *const u16,
+        parameters: *const u16,
+        directory: *const u16,
+        show: i32,
+        inst_app: *mut c_void,
+        id_list: *mut c_void,
+        class: *const u16,
+        hkey_class: *mut c_void,
+        hot_key: u32,
+        icon_or_monitor: *mut c_void,
+        process: *mut c_void,
+    }
+    #[link(name = "shell32")]
+    extern "system" { fn ShellExecuteExW(info: *mut ShellExecuteInfoW) -> i32; }
+    extern "system" {
+        fn WaitForSingleObject(handle: *mut c_void, milliseconds: u32) -> u32;
+        fn GetExitCodeProcess(process: *mut c_void, exit_code: *mut u32) -> i32;
+        fn CloseHandle(handle: *mut c_void) -> i32;
+    }
+    const SEE_MASK_NOCLOSEPROCESS: u32 = 0x40;
+    const SW_HIDE: i32 = 0;
+    const INFINITE: u32 = 0xFFFFFFFF;
+    const ERROR_CANCELLED: i32 = 1223;
+
+    let wide = |s: &std::ffi::OsStr| s.encode_wide().chain(std::iter::once(0)).collect::<Vec<u16>>();
+    let exe = std::env::current_exe().map_err(|e| format!("Couldn't find my .exe: {}", e))?;
+    let exe_w = wide(exe.as_os_str());
+    let verb_w = wide("runas".as_ref());
+    let params_w = wide(args.iter().map(|a| quote(a)).collect::<Vec<_>>().join(" ").as_ref());
+
+    let mut info = ShellExecuteInfoW {
+        size: std::mem::size_of::<ShellExecuteInfoW>() as u32,
+        mask: SEE_MASK_NOCLOSEPROCESS,
+        hwnd: std::ptr::null_mut(),
+        verb: verb_w.as_ptr(),
+        file: exe_w.as_ptr(),
+        parameters: params_w.as_ptr(),
+        directory: std::ptr::null(),
+        show: SW_HIDE,
+        inst_app: std::ptr::null_mut(),
+        id_list: std::ptr::null_mut(),
+        class: std::ptr::null(),
+        hkey_class: std::ptr::null_mut(),
+        hot_key: 0,
+        icon_or_monitor: std::ptr::null_mut(),
+        process: std::ptr::null_mut(),
+    };
+    if unsafe { ShellExecuteExW(&mut info) } == 0 {
+        let e = std::io::Error::last_os_error();
+        if e.raw_os_error() == Some(ERROR_CANCELLED) { Err(ErscomError::Other(format!("Administrator access was turned down, so nothing was installed.")))? }
+        Err(ErscomError::Other(format!("Couldn't run as administrator: {}", e)))?
+    }
+    let mut code = 0u32;
+    unsafe {
+        WaitForSingleObject(info.process, INFINITE);
+        GetExitCodeProcess(info.process, &mut code);
+        CloseHandle(info.process);
+    }
+    Ok(code as i32)
+}
+
+// Quote so CommandLineToArgvW() (what the Rust runtime uses to split the command line) gives back `arg` unchanged.
+#[cfg(target_os = "windows")]
+fn quote(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => { quoted.push_str(&"\\".repeat(backslashes * 2 + 1)); backslashes = 0 },
+            _ => { quoted.push_str(&"\\".repeat(backslashes)); backslashes = 0 },
+        }
+        if c != '\\' { quoted.push(c) }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2)); // They'd escape the closing quote otherwise
+    quoted.push('"');
+    quoted
+}
+
+// No UAC to ask, they'll have to sort out the permissions themselves
+#[cfg(not(target_os = "windows"))]
+pub fn install(installdir: &EldenRingDir, _release: &Release, _upgrade: bool) -> Result<(), ErscomError> {
+    Err(ErscomError::NotWritable { path: installdir.path().to_path_buf() })
+}
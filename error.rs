@@ -0,0 +1,147 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ErscomError {
+    #[error("Network error: {0}")]
+    Network(#[from] reqwest::Error),
+
+    #[error("Github returned {status}: {message}")]
+    GitHubApi { status: u16, message: String },
+
+    #[error("Couldn't read zip file {}: {source}", path.display())]
+    Zip { path: PathBuf, source: zip::result::ZipError },
+
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+
+    #[error("Couldn't parse {}: {message}", path.display())]
+    IniParse { path: PathBuf, message: String },
+
+    #[error("Couldn't parse {}: {source}", path.display())]
+    Json { path: PathBuf, source: serde_json::Error },
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{}", in_use(path, by))]
+    GameRunning { path: PathBuf, by: Vec<String> }, // `by` is who has it open, if we could tell
+
+    #[error("Can't change files in {}", path.display())]
+    NotWritable { path: PathBuf },
+
+    #[error("The download of {tag} doesn't match its published checksum (expected {expected}, got {actual})")]
+    ChecksumMismatch { tag: String, expected: String, actual: String },
+
+    #[error("Cancelled")]
+    Cancelled,
+
+    #[error("{0}")]
+    Other(String),
+
+    /// Something that already got shown to the user (see [crate::breaker]), so don't report it again.
+    #[error("Error already reported")]
+    Reported,
+}
+
+impl ErscomError {
+    /// For `.map_err()`: wrap an io error with the path it happened on.
+    pub fn io(path: &Path) -> impl FnOnce(std::io::Error) -> ErscomError + '_ {
+        move |source| {
+            match source.raw_os_error() {
+                // ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION: Windows' way of saying someone has the file open
+                #[cfg(target_os = "windows")]
+                Some(32) | Some(33) => ErscomError::GameRunning { path: path.to_path_buf(), by: crate::lock::holders(path) },
+                // ERROR_ACCESS_DENIED is also what you get for a DLL that's loaded in something
+                #[cfg(target_os = "windows")]
+                Some(5) => match crate::lock::holders(path) {
+                    by if !by.is_empty() => ErscomError::GameRunning { path: path.to_path_buf(), by },
+                    _ => ErscomError::Io { path: path.to_path_buf(), source },
+                },
+                _ => ErscomError::Io { path: path.to_path_buf(), source },
+            }
+        }
+    }
+
+    /// For `.map_err()`: wrap a zip error with the path of the zip file.
+    pub fn zip(path: &Path) -> impl FnOnce(zip::result::ZipError) -> ErscomError + '_ {
+        move |source| ErscomError::Zip { path: path.to_path_buf(), source }
+    }
+
+    /// For `.map_err()`: wrap a json error with the path of the file.
+    pub fn json(path: &Path) -> impl FnOnce(serde_json::Error) -> ErscomError + '_ {
+        move |source| ErscomError::Json { path: path.to_path_buf(), source }
+    }
+
+    /// Did we fail because we couldn't talk to the server at all (as opposed to the server saying no)?
+    pub fn is_network(&self) -> bool {
+        match self {
+            ErscomError::Network(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+            _ => false,
+        }
+    }
+
+    /// Something the user might actually be able to do about the error.
+    pub fn hint(&self) -> Option<&'static str> {
+        use std::io::ErrorKind;
+        match self {
+            ErscomError::Network(_) => Some("Check your internet connection and try again."),
+            ErscomError::GitHubApi { status: 401, .. } => Some("Check the GitHub token in Preferences."),
+            ErscomError::GitHubApi { status: 403 | 429, .. } => Some("Github is limiting how often we can ask for releases. Wait a while and then hit Refresh."),
+            ErscomError::GitHubApi { status, .. } if *status >= 500 => Some("Github seems to be having problems. Try again later."),
+            ErscomError::Zip { .. } => Some("The download may be corrupt. Delete it from the \"release cache\" folder and try again."),
+            ErscomError::Io { source, .. } if source.kind() == ErrorKind::PermissionDenied => Some("Try running the manager as administrator."),
+            ErscomError::IniParse { .. } => Some("The settings file may be damaged. Reinstalling the mod will replace it."),
+            ErscomError::Json { .. } => Some("The file may be damaged. Deleting it should fix this."),
+            ErscomError::GameRunning { by, .. } if !by.is_empty() => Some("Close it and hit Retry."),
+            ErscomError::GameRunning { .. } => Some("Close Elden Ring and try again."),
+            ErscomError::NotWritable { .. } => Some("Check that you're allowed to change files there, or run the manager as administrator."),
+            ErscomError::ChecksumMismatch { .. } => Some("It wasn't installed. It may have been changed since it was released, or damaged on the way. Try again later, and report it if it keeps happening."),
+            _ => None,
+        }
+    }
+}
+
+fn in_use(path: &Path, by: &[String]) -> String {
+    match by {
+        [] => format!("{} is in use. Is Elden Ring still running?", path.display()),
+        by => format!("{} is in use by {}", path.file_name().unwrap_or(path.as_os_str()).to_string_lossy(), by.join(", ")),
+    }
+}
+
+impl From<crate::breaker::Empty> for ErscomError {
+    fn from(_: crate::breaker::Empty) -> ErscomError {
+        ErscomError::Reported
+    }
+}
+
+impl From<String> for ErscomError {
+    fn from(message: String) -> ErscomError {
+        ErscomError::Other(message)
+    }
+}
+
+impl From<&str> for ErscomError {
+    fn from(message: &str) -> ErscomError {
+        ErscomError::Other(message.to_string())
+    }
+}
+
+/// The hint for an arbitrary error, if it's one of ours.
+pub fn hint_for(error: &(dyn std::error::Error + 'static)) -> Option<&'static str> {
+    error.downcast_ref::<ErscomError>().and_then(|e| e.hint())
+}
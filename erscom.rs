@@ -25,27 +25,139 @@ use std::rc::Rc;
 mod manage;
 mod ini;
 mod breaker;
+mod report;
+mod cli;
+mod source;
+mod error;
+mod issue;
+mod paths;
+mod logging;
+mod logview;
+mod crash;
+mod troubleshoot;
+mod config;
+mod backup;
+mod prefs;
+mod i18n;
+mod settings_schema;
+mod status;
+mod notifier;
+mod instance;
+mod elevate;
+mod share;
+mod known_good;
+mod history;
+mod shortcut;
+mod steam;
+mod prereqs;
+mod eac;
+mod changelog;
+mod session;
+mod app;
+mod about;
+mod support;
+mod secret;
+mod checksums;
+mod zipcache;
+mod downloads;
+mod transfer;
+mod moddocs;
+mod gamecheck;
+mod verify;
+mod queue;
+mod ipc;
+mod clipwatch;
+#[cfg(target_os = "windows")]
+mod lock;
+
+use report::UIError;
+use secret::Secret;
+use tracing::{debug, info, warn};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => std::process::exit(cli::usage_error(&e)),
+    };
+    args.apply(); // Before logging, which needs to know where the logs go
+    logging::init(args.verbose, args.command.is_some());
+    crash::install_panic_hook();
+
+    if let Some(ref message) = args.show_panic {
+        crash::show_panic(message)?;
+        return Ok(());
+    }
+
+    if args.quick_launch {
+        std::process::exit(cli::quick_launch(&args));
+    }
+
+    if let Some(command) = args.command.clone() {
+        std::process::exit(cli::run(&args, command));
+    }
+
+    let instance = match instance::claim(&std::env::args().skip(1).collect::<Vec<_>>()) {
+        Ok(Some(instance)) => Some(instance),
+        Ok(None) => { info!("Already running, handed off to the other copy"); return Ok(()) },
+        Err(e) => { warn!("Couldn't check for another copy of the manager, running anyway: {}", e); None },
+    };
+
     let win = MainWindow::new()?;
+    MAIN_WINDOW.with(|w| *w.borrow_mut() = Some(win.as_weak()));
+    if let Some(ref instance) = instance {
+        instance.listen(|_args| {
+            slint::invoke_from_event_loop(raise_main_window).try_log("raising main window")?;
+        }).try_log("listening for other copies of the manager");
+    }
+    win.set_theme(config::get().theme.color_theme());
+    win.set_high_contrast(config::get().high_contrast);
+    win.set_allow_password_reveal(config::get().allow_password_reveal);
+    i18n::apply(config::get().language.as_deref());
+    restore_geometry(win.window(), config::get().window);
+    if let Some(notice) = config::recovery_notice() { report::warn(notice.into()) }
+
+    win.window().on_close_requested({
+        let weak_win = win.as_weak();
+        move || {
+            let win = weak_win.unwrap();
+            win.invoke_save_password();
+            save_geometry(&win);
+            slint::CloseRequestResponse::HideWindow
+        }
+    });
 
     win.on_exit(move || {
-        println!("Exiting");
+        info!("Exiting");
         slint::quit_event_loop().try_log("quitting event loop");
     });
 
-    let manager = Rc::new(RefCell::new(manage::EldenRingManager::new()));
+    let manager = Rc::new(RefCell::new(args.manager()?));
+    app::init(&manager);
+    manager.borrow_mut().load_snapshot();
     if let Some(ref p) = manager.borrow().dir {
         win.set_install_path(p.display().into());
+        if !eac::changes(p).is_empty() {
+            toast("Elden Ring's anti-cheat files have been changed. See Troubleshoot for details.");
+        }
+    }
+    issue::set_context(manager.borrow().dir.as_ref().map(|d| d.display()), None);
+
+    wire_release_callbacks(&win, &manager);
+    if manager.borrow().releases.is_empty() {
+        get_releases(&win, &manager.clone());
+        app::check_manager_upgrade(); // There's no refresh for it to go along with
+    } else {
+        app::view(&win);
+        app::detect_current_release(&manager); // Installs can happen while we're not running
+        app::send(app::Msg::RefreshStarted { quiet: false });
     }
-
-    get_releases(&win, &manager.clone());
 
     win.on_new_password({
         let manager = manager.clone();
         move |password| {
-            println!("New password: {}", password);
+            let password = Secret::new(password.to_string());
+            debug!("Saving new password {}", password);
             if manager.borrow().found_dir() {
                 manager.borrow().set_password(&password).try_error()?;
             }
@@ -54,704 +166,1270 @@ async fn main() -> Result<(), Box<dyn Error>> {
     });
 
     win.on_launch({
+        let manager_ref = manager.clone();
+        let weak_win = win.as_weak();
+        move || {
+            let win = weak_win.unwrap();
+            if config::get().update_before_launch {
+                return check_before_launch(&win, &manager_ref);
+            }
+            launch_game(&win, &manager_ref);
+        }
+    });
+
+    show_known_good(&win);
+
+    ipc::serve({
+        let (weak_win, manager) = (win.as_weak(), manager.clone());
+        move |command| control(&weak_win, &manager, command)
+    });
+
+    clipwatch::watch(&win, &manager);
+    win.on_use_clipboard_password({
+        let (weak_win, manager) = (win.as_weak(), manager.clone());
+        move || clipwatch::accept(&weak_win.unwrap(), &manager.borrow())
+    });
+    win.on_dismiss_clipboard_password({
+        let weak_win = win.as_weak();
+        move || clipwatch::dismiss(&weak_win.unwrap())
+    });
+
+    show_queued_install();
+    if let Some(dir) = manager.borrow().dir.clone() {
+        // It could have been queued last time, before quitting
+        let manager_ref = manager.clone();
+        queue::watch(&dir, move |tag| install_queued(&manager_ref, tag));
+    }
+    win.on_cancel_queued_install(|| {
+        queue::cancel();
+        show_queued_install();
+    });
+
+    win.on_troubleshoot({
         let manager = manager.clone();
         move || {
-            let manager = manager.borrow();
-            launch(manager.launcher_path().try_error()?).try_error()?;
+            let Some(dir) = manager.borrow().dir.clone() else { return };
+            troubleshoot::show_troubleshooter(dir, manager.borrow().current.clone(), 0);
         }
     });
 
-    win.on_refresh({
+    win.on_session_check({
+        let manager = manager.clone();
+        move || session::show_session_check(manager.clone())
+    });
+
+    win.on_version_selected(|tag| {
+        config::update(|c| c.selected_release = Some(tag.to_string())).try_log("saving selected release")?;
+    });
+
+    win.on_toggle_pin({
         let weak_win = win.as_weak();
+        let manager = manager.clone();
         move || {
-            let win = weak_win.unwrap();
-            println!("Refreshing");
-            get_releases(&win, &manager.clone());
+            let Some(current) = manager.borrow().current.as_ref().map(|r| r.tag.clone()) else { return };
+            let pin = config::get().pinned_release.as_ref() != Some(&current);
+            config::update(|c| c.pinned_release = pin.then(|| current.clone())).try_error()?;
+            history::record(if pin { format!("Pinned {}", current) } else { format!("Unpinned {}", current) });
+            show_releases(&weak_win.unwrap(), &manager);
+        }
+    });
+    win.on_toggle_ignore({
+        let weak_win = win.as_weak();
+        let manager = manager.clone();
+        move |tag| {
+            let tag = tag.to_string();
+            let ignore = !config::get().ignored_releases.contains(&tag);
+            config::update(|c| if ignore { c.ignored_releases.push(tag.clone()) } else { c.ignored_releases.retain(|t| *t != tag) }).try_error()?;
+            history::record(if ignore { format!("Ignored {}", tag) } else { format!("Stopped ignoring {}", tag) });
+            show_releases(&weak_win.unwrap(), &manager);
         }
     });
 
+    win.on_set_note({
+        let weak_win = win.as_weak();
+        let manager = manager.clone();
+        move |tag, note| {
+            let (tag, note) = (tag.to_string(), note.trim().to_string());
+            config::update(|c| if note.is_empty() { c.release_notes.remove(&tag); } else { c.release_notes.insert(tag.clone(), note.clone()); }).try_error()?;
+            show_releases(&weak_win.unwrap(), &manager);
+        }
+    });
+
+    win.set_show_beta(config::get().show_beta);
+    win.on_show_beta_changed({
+        let weak_win = win.as_weak();
+        let manager = manager.clone();
+        move |show| {
+            config::update(|c| c.show_beta = show).try_log("saving beta setting")?;
+            show_releases(&weak_win.unwrap(), &manager);
+        }
+    });
+
+    win.on_open_install_folder({
+        let manager = manager.clone();
+        move || {
+            let Some(ref dir) = manager.borrow().dir else { return };
+            open_path(dir.path()).try_error()?;
+        }
+    });
+
+    // Keep the "updated N min ago" from going stale
+    let status_timer = slint::Timer::default();
+    status_timer.start(slint::TimerMode::Repeated, std::time::Duration::from_secs(30), {
+        let weak_win = win.as_weak();
+        let manager = manager.clone();
+        move || {
+            let Some(win) = weak_win.upgrade() else { return };
+            let Ok(manager) = manager.try_borrow() else { return }; // Busy fetching, catch it next time
+            win.set_status(status::text(&manager).into());
+        }
+    });
+
+    // Check for new releases every so often, and when the window comes back to the front after a while
+    let refresh_timer = slint::Timer::default();
+    refresh_timer.start(slint::TimerMode::Repeated, std::time::Duration::from_secs(2), {
+        let weak_win = win.as_weak();
+        let manager = manager.clone();
+        let was_active = std::cell::Cell::new(true);
+        let last_try = std::cell::Cell::new(chrono::Local::now());
+        move || {
+            let Some(win) = weak_win.upgrade() else { return };
+            let Some(interval) = config::get().auto_refresh.interval() else { return };
+            let active = window_active(&win);
+            let focused = active && !was_active.replace(active);
+            let max_age = if focused { interval.min(FOCUS_REFRESH_AGE) } else { interval };
+            if chrono::Local::now() - last_try.get() < max_age { return }
+            let due = match manager.try_borrow() {
+                Ok(manager) => manager.refresh_due(max_age),
+                Err(_) => false, // Already fetching
+            };
+            if !due { return }
+            last_try.set(chrono::Local::now());
+            app::send(app::Msg::RefreshStarted { quiet: true });
+        }
+    });
+
+    win.on_refresh(|| app::send(app::Msg::RefreshStarted { quiet: false }));
+    win.on_dismiss_refresh_error(|| app::send(app::Msg::RefreshErrorDismissed));
+
     win.on_open_url(|url| {
         let _ = webbrowser::open(&url);
     });
 
+    win.on_view_logs(logview::show_log_window);
+    win.on_create_support_bundle({
+        let manager = manager.clone();
+        move || {
+            let path = support::create_bundle(&manager.borrow()).try_error()?;
+            toast(&format!("Saved {}. Attach it to your bug report or post.", path.file_name().unwrap_or_default().to_string_lossy()));
+            if let Some(dir) = path.parent() { open_path(dir).try_warn()?; }
+        }
+    });
+    win.on_view_history(history::show_history_window);
+    win.on_about(about::show_about_window);
+    win.on_open_downloads(|| downloads::show_downloads_window(SHOWN_RELEASES.with(|shown| shown.borrow().clone())));
+    win.on_open_preferences({
+        let (weak_win, manager) = (win.as_weak(), manager.clone());
+        move || prefs::show_preferences(weak_win.clone(), manager.clone())
+    });
+
     win.set_copyright(regex::Regex::new(r"^Copyright (.*) <.*>$").unwrap()
         .captures(env!("COPYRIGHT")/* Set by build.rs */)
         .expect("copyright didn't match")
         .get(1).unwrap().as_str().into());
 
     if let Some(v) = option_env!("VERSION") { win.set_my_version(v.into()); }
-
-    if let Some(v) = manage::self_upgrade_version().unwrap_or(None) { win.set_my_upgrade_version(v.into()) }
+    win.set_beta_channel(config::get().manager_channel == config::ManagerChannel::Beta);
 
     win.run()?;
     Ok(())
 }
 
-fn error(error: Box<dyn Error>) {
-    let dialog = ErrorDialog::new().unwrap();
-    dialog.set_error(format!("{}", error).into());
+/// Ask before doing something drastic. `ok` is the button label for going ahead.
+pub fn confirm(message: &str, warning: &str, ok: &str, on_ok: impl Fn() + 'static) {
+    ask(message, warning, ok, "Cancel", on_ok, || {});
+}
+
+/// Like [confirm()], but for when saying no does something too.
+pub fn ask(message: &str, warning: &str, ok: &str, cancel: &str, on_ok: impl Fn() + 'static, on_cancel: impl Fn() + 'static) {
+    let dialog = ConfirmDialog::new().try_error()?;
+    dialog.set_theme(config::get().theme.color_theme());
+    dialog.set_high_contrast(config::get().high_contrast);
+    dialog.set_message(message.into());
+    dialog.set_warning(warning.into());
+    dialog.set_ok_text(ok.into());
+    dialog.set_cancel_text(cancel.into());
     dialog.on_ok_clicked({
         let dialog = dialog.as_weak();
         move || {
-            dialog.unwrap().hide().try_log("hiding dialog")?;
+            dialog.unwrap().hide().try_log("hiding confirm dialog")?;
+            on_ok();
+        }
+    });
+    dialog.on_cancel_clicked({
+        let dialog = dialog.as_weak();
+        move || {
+            dialog.unwrap().hide().try_log("hiding confirm dialog")?;
+            on_cancel();
         }
     });
-    dialog.show().try_log(&format!("showing error dialog for {}", error))?;
+    dialog.show().try_log("showing confirm dialog")?;
 }
 
-fn fatal(error: Box<dyn Error>) {
-    let dialog = FatalDialog::new().unwrap();
-    dialog.set_error(format!("{}", error).into());
-    dialog.on_abort_clicked(move || {
-            slint::quit_event_loop().try_log("quitting event loop");
-    });
-    dialog.show().try_log(&format!("showing fatal dialog for {}", error))?;
+thread_local! {
+    // So things like toast() can find the main window without it being passed all the way down
+    static MAIN_WINDOW: RefCell<Option<slint::Weak<MainWindow>>> = const { RefCell::new(None) };
+    static TOAST_TIMER: slint::Timer = slint::Timer::default();
+}
+
+fn main_window() -> Option<MainWindow> {
+    MAIN_WINDOW.with(|w| w.borrow().as_ref().and_then(|w| w.upgrade()))
+}
+
+// When someone starts us again they probably couldn't find the window
+fn raise_main_window() {
+    let Some(win) = main_window() else { return };
+    win.window().set_minimized(false);
+    win.show().try_log("raising main window")?;
+}
+
+const TOAST_FOR: std::time::Duration = std::time::Duration::from_secs(6);
+
+/// Briefly show `message` at the bottom of the main window. If there's no main window (yet) it's just dropped--the
+/// caller is expected to have logged it.
+pub fn toast(message: &str) {
+    let Some(win) = main_window() else { return };
+    win.set_toast(message.into());
+    TOAST_TIMER.with(|timer| timer.start(slint::TimerMode::SingleShot, TOAST_FOR, {
+        let weak_win = win.as_weak();
+        move || {
+            if let Some(win) = weak_win.upgrade() { win.set_toast("".into()) }
+        }
+    }));
+}
+
+fn show_known_good(win: &MainWindow) {
+    let known_good = known_good::load().try_log("loading last working setup")?;
+    win.set_known_good(known_good.map(|k| k.describe()).unwrap_or_default().into());
+}
+
+fn restore_geometry(window: &slint::Window, geometry: Option<config::WindowGeometry>) {
+    let Some(geometry) = geometry else { return };
+    debug!("Restoring window geometry {:?}", geometry);
+    window.set_size(slint::PhysicalSize::new(geometry.width, geometry.height));
+    window.set_position(slint::PhysicalPosition::new(geometry.x, geometry.y));
+    window.set_maximized(geometry.maximized);
+}
+
+// Where `window` is now. `old` is what was saved last time.
+fn window_geometry(window: &slint::Window, old: Option<config::WindowGeometry>) -> config::WindowGeometry {
+    let (position, size) = (window.position(), window.size());
+    let geometry = config::WindowGeometry { x: position.x, y: position.y, width: size.width, height: size.height, maximized: window.is_maximized() };
+    match old {
+        // Keep the old un-maximized geometry so un-maximizing next time goes somewhere sensible
+        Some(old) if geometry.maximized => config::WindowGeometry { maximized: true, ..old },
+        _ => geometry,
+    }
+}
+
+fn save_geometry(win: &MainWindow) {
+    let geometry = window_geometry(win.window(), config::get().window);
+    config::update(|c| c.window = Some(geometry)).try_log("saving window geometry")?;
+}
+
+// Coming back to the window only refreshes if the list is at least this old.
+const FOCUS_REFRESH_AGE: chrono::Duration = chrono::Duration::minutes(5);
+
+// Slint (as of 1.9) has no public way to find out when the window gains focus, so peek at its internals. That's why
+// Cargo.toml pins slint to an exact version: check this still builds before bumping it.
+pub fn window_active(win: &MainWindow) -> bool {
+    slint::private_unstable_api::re_exports::WindowInner::from_pub(win.window()).active()
 }
 
 fn get_releases(win: &MainWindow, manager_ref: &Rc<RefCell<manage::EldenRingManager>>) {
-    let mut manager = manager_ref.borrow_mut();
-    manager.fetch_releases().try_fatal()?;
+    // show_releases() does this too, but it doesn't get called if fetching fails (and the rate limit may have changed)
+    let _status = breaker::finally(|| win.set_status(status::text(&manager_ref.borrow()).into()));
+    manager_ref.borrow_mut().fetch_releases().try_fatal()?;
     //println!("Releases:\n{:?}", releases);
+    app::view(win);
+    app::detect_current_release(manager_ref);
+}
 
+// Fill the version dropdown from the already fetched releases.
+fn show_releases(win: &MainWindow, manager_ref: &Rc<RefCell<manage::EldenRingManager>>) {
+    let manager = manager_ref.borrow();
+    win.set_offline(manager.offline);
+    win.set_status(status::text(&manager).into());
     win.set_current_version("".into());
-    let current_release_tag = manager.detect_current_release().as_ref().map(|r| r.tag.clone());
+    let current_release_tag = manager.current.as_ref().map(|r| r.tag.clone());
+    issue::set_context(manager.dir.as_ref().map(|d| d.display()), current_release_tag.clone());
     if let Some(ref tag) = current_release_tag {
         win.set_current_version(tag.clone().into());
     }
-
-    win.set_available_versions(Rc::new(slint::VecModel::<slint::SharedString>::from(manager.releases.iter()
-                                                                                    .map(|r| format!("{}  --  {}  {}",
-                                                                                                     r.tag, r.date,
+    win.set_install_age(status::install_age(&manager).into());
+
+    // Betas are hidden unless asked for, but never hide the one that's installed
+    let show_beta = config::get().show_beta;
+    let releases: Vec<manage::Release> = manager.releases.iter()
+        .filter(|r| show_beta || !r.prerelease || current_release_tag.as_ref() == Some(&r.tag))
+        .cloned()
+        .collect();
+    SHOWN_RELEASES.with(|shown| *shown.borrow_mut() = releases.clone());
+    win.invoke_search_changelogs(win.get_changelog_query()); // The matches point into the list
+
+    let config = config::get();
+    win.set_available_versions(Rc::new(slint::VecModel::<slint::SharedString>::from(releases.iter()
+                                                                                    .map(|r| format!("{}{}{}{}{}  --  {}{}  {}",
+                                                                                                     if changelog::breaking_changes(&r.changelog).is_empty() { "" } else { "⚠ " },
+                                                                                                     r.tag, if r.prerelease { " (beta)" } else { "" },
+                                                                                                     if config.ignored_releases.contains(&r.tag) { " (ignored)" } else { "" },
+                                                                                                     if config.release_notes.contains_key(&r.tag) { " 📝" } else { "" },
+                                                                                                     status::release_date(&r.date),
+                                                                                                     r.downloads.map(|d| format!("  ({})", status::downloads(d))).unwrap_or_default(),
                                                                                                      match (r.downloaded(), current_release_tag.as_ref()) {
+                                                                                                         (_, Some(cur_tag)) if cur_tag == &r.tag && config.pinned_release.as_ref() == Some(cur_tag) => "[ Installed, Pinned ]",
                                                                                                          (_, Some(cur_tag)) if cur_tag == &r.tag => "[ Installed ]",
                                                                                                          (true, _) => "[ Downloaded ]",
                                                                                                          _ => ""
                                                                                                      }).into())
                                                                                     .collect::<Vec<slint::SharedString>>())).into());
+    win.set_ignored_versions(Rc::new(slint::VecModel::from(releases.iter().map(|r| config.ignored_releases.contains(&r.tag)).collect::<Vec<bool>>())).into());
+    win.set_version_notes(Rc::new(slint::VecModel::from(releases.iter().map(|r| config.release_notes.get(&r.tag).cloned().unwrap_or_default().into()).collect::<Vec<slint::SharedString>>())).into());
+    win.set_pinned(current_release_tag.is_some() && config.pinned_release == current_release_tag);
 
     match manager.get_password() {
-        Ok(ref password) => { win.set_password(password.into()) },
-        Err(e) => { println!("Couldn't get password: {:?}", e) },
+        Ok(ref password) => { win.set_password(password.expose().into()) },
+        // No password is expected before the mod is installed
+        Err(e) if current_release_tag.is_some() => { report::warn(format!("Couldn't read the co-op password: {}", e).into()) },
+        Err(e) => { warn!("Couldn't get password: {}", e) },
+    }
+
+    if let Some(index) = config::get().selected_release.and_then(|tag| releases.iter().position(|r| r.tag == tag)) {
+        win.invoke_select_version(index as i32);
     }
+}
+
+// The releases in the version dropdown, in the same order (betas can be filtered out, so it isn't just
+// `manager.releases`). show_releases() keeps it up to date, and the callbacks that get a dropdown index look in it.
+thread_local! {
+    static SHOWN_RELEASES: RefCell<Vec<manage::Release>> = const { RefCell::new(vec![]) };
+}
+
+fn shown_release(index: i32) -> Option<manage::Release> {
+    let index = usize::try_from(index).ok()?;
+    SHOWN_RELEASES.with(|shown| shown.borrow().get(index).cloned())
+}
+
+/// Install the release called `tag`, asking first the way picking it from the list would. False if it isn't in the
+/// list.
+pub fn confirm_install_tag(tag: &str) -> bool {
+    let Some(win) = main_window() else { return false };
+    let Some(index) = SHOWN_RELEASES.with(|shown| shown.borrow().iter().position(|r| r.tag == tag)) else { return false };
+    win.invoke_confirm_install(index as i32);
+    true
+}
+
+// Past this the search isn't narrowing anything down
+const MAX_CHANGELOG_MATCHES: usize = 100;
+
+// The callbacks that deal with the release list. They only get registered once and look at the current state when
+// they're called, so a refresh just has to update SHOWN_RELEASES and the manager.
+fn wire_release_callbacks(win: &MainWindow, manager_ref: &Rc<RefCell<manage::EldenRingManager>>) {
+    win.on_version_at_index(|version_index| {
+        shown_release(version_index).map(|version| version.tag.into()).unwrap_or_default()
+    });
+
+    win.on_date_at_index(|version_index| {
+        shown_release(version_index).map(|version| status::release_date(&version.date).into()).unwrap_or_default()
+    });
+
+    win.on_page_url_at_index(|version_index| {
+        shown_release(version_index).and_then(|version| version.page_url).unwrap_or_default().into()
+    });
+
+    win.on_changelog_at_index(|version_index, search| {
+        let Some(version) = shown_release(version_index) else { return "".into() };
+        match version.changelog.as_str() {
+            "" => format!("No release notes available 🙁"),
+            s => changelog::highlight(s, &search),
+        }.into()
+    });
+
+    win.on_search_changelogs({
+        let weak_win = win.as_weak();
+        move |query| {
+            let matches: Vec<ChangelogMatch> = SHOWN_RELEASES.with(|shown| shown.borrow().iter().enumerate()
+                .flat_map(|(index, release)| changelog::search(&release.changelog, &query).into_iter().map(move |m| ChangelogMatch {
+                    index: index as i32, tag: release.tag.clone().into(), line: m.line.into(), position: m.position,
+                }))
+                .take(MAX_CHANGELOG_MATCHES)
+                .collect());
+            weak_win.unwrap().set_changelog_matches(Rc::new(slint::VecModel::from(matches)).into());
+        }
+    });
+
+    win.on_breaking_changes_at_index(|version_index| {
+        let Some(version) = shown_release(version_index) else { return "".into() };
+        changelog::breaking_changes(&version.changelog).iter().map(|line| format!("⚠ {}", line)).collect::<Vec<_>>().join("\n").into()
+    });
+
+    win.on_summary_at_index(|version_index| {
+        let Some(version) = shown_release(version_index) else { return "".into() };
+        // Only downloaded zips can be looked in
+        if !version.downloaded() { return "".into() }
+        match version.contents() {
+            Ok(contents) => format!("{} files, {} installed", contents.file_count(), status::size(contents.size())).into(),
+            Err(e) => { warn!("Couldn't read {}'s contents: {}", version.tag, e); "".into() },
+        }
+    });
 
-    win.on_version_at_index({
-        let releases = manager.releases.clone();
+    win.on_install({
+        let manager_ref = manager_ref.clone();
+        let weak_win = win.as_weak();
         move |version_index| {
-            if version_index < 0 { return "".into(); }
-            let version = &releases[version_index as usize];
-            version.tag.clone().into()
+            let Some(installdir) = manager_ref.borrow().dir.clone() else { return };
+            let Some(version) = shown_release(version_index) else { return };
+            let retry = { let weak_win = weak_win.clone(); move || if let Some(win) = weak_win.upgrade() { win.invoke_install_version(version_index) } };
+            let weak_win = weak_win.clone();
+            install_release(&manager_ref, &installdir, &version, retry,
+                            move || if let Some(win) = weak_win.upgrade() { win.invoke_installed() })
         }
     });
 
-    win.on_changelog_at_index({
-        let releases = manager.releases.clone();
+    win.on_repair_install({
+        let manager_ref = manager_ref.clone();
+        let weak_win = win.as_weak();
         move |version_index| {
-            if version_index < 0 { return "".into(); }
-            let version = &releases[version_index as usize];
-            match version.changelog.as_str() {
-                "" => format!("No release notes available 🙁"),
-                s => s.to_owned(),
-            }.into()
+            let Some(installdir) = manager_ref.borrow().dir.clone() else { return };
+            let Some(version) = shown_release(version_index) else { return };
+            if app::busy() { return }
+            let mixed: Vec<_> = manager_ref.borrow().releases.iter().filter(|r| app::mixed_releases().contains(&r.tag)).cloned().collect();
+            history::record(format!("Repairing an install with files from {} by reinstalling {}",
+                                    mixed.iter().map(|r| r.tag.as_str()).collect::<Vec<_>>().join(", "), version.tag));
+            app::update(app::Msg::Started("Removing mixed up files...".to_string()));
+            let (manager_ref, weak_win) = (manager_ref.clone(), weak_win.clone());
+            slint::spawn_local(async move {
+                let uninstalled = tokio::task::spawn_blocking({
+                    let installdir = installdir.clone();
+                    move || for release in mixed {
+                        // Whatever doesn't come out gets written over by the reinstall, but they should know it's there
+                        if let Err(e) = release.uninstall(&installdir) {
+                            warn!("Got error removing {}'s files: {}", release.tag, e);
+                            history::record(format!("Couldn't remove {}'s files: {}", release.tag, e));
+                            report::warn(format!("Couldn't remove all of {}'s files: {}", release.tag, e).into());
+                        }
+                    }
+                }).await;
+                app::update(app::Msg::Finished);
+                if let Err(e) = uninstalled { return report::error(format!("Removing the mixed up files didn't finish: {}", e).into()) }
+                let retry = { let weak_win = weak_win.clone(); move || if let Some(win) = weak_win.upgrade() { win.invoke_repair_install(version_index) } };
+                install_release(&manager_ref, &installdir, &version, retry,
+                                move || if let Some(win) = weak_win.upgrade() { win.invoke_installed() });
+            }).try_error()?;
         }
     });
 
-    if let Some(installdir) = manager.dir.clone() {
-        win.on_install({
-            let manager_ref = manager_ref.clone();
-            move |version_index| {
-                let manager = manager_ref.borrow();
-                let version = &manager.releases[version_index as usize];
-                if let Some(ref current) = manager.current {
-                    println!("Uninstalling {}", current.tag);
-                    if let Err(e) = current.uninstall(&installdir) {
-                        println!("Got error uninstalling {}: {}", current.tag, e);
-                        // What do do about errors??
+    win.on_revert_to_known_good({
+        let manager_ref = manager_ref.clone();
+        let weak_win = win.as_weak();
+        move || {
+            let Some(installdir) = manager_ref.borrow().dir.clone() else { return };
+            let Some(known_good) = known_good::load().try_error()? else { return };
+            let release = manager_ref.borrow().releases.iter().find(|r| r.tag == known_good.tag).cloned()
+                .ok_or(format!("{} isn't available any more, so it can't be reinstalled", known_good.tag)).try_error()?;
+            let reinstall = manager_ref.borrow().current.as_ref().map(|r| &r.tag) != Some(&known_good.tag);
+            let message = match reinstall {
+                true => format!("Go back to {}, with the settings it had then?", known_good.describe()),
+                false => format!("Put back the settings from when {} was working?", known_good.describe()),
+            };
+            confirm(&message, "Your current settings will be replaced.", "Revert", {
+                let (manager_ref, weak_win) = (manager_ref.clone(), weak_win.clone());
+                move || {
+                    let retry = { let weak_win = weak_win.clone(); move || if let Some(win) = weak_win.upgrade() { win.invoke_revert_to_known_good() } };
+                    let restore = {
+                        let (known_good, installdir, weak_win) = (known_good.clone(), installdir.clone(), weak_win.clone());
+                        move || {
+                            known_good.restore_settings(&installdir).try_error()?;
+                            info!("Reverted to {}", known_good.tag);
+                            history::record(format!("Reverted to the last working setup, {}", known_good.describe()));
+                            if let Some(win) = weak_win.upgrade() { win.invoke_refresh() }
+                        }
+                    };
+                    match reinstall {
+                        true => install_release(&manager_ref, &installdir, &release, retry, restore),
+                        false => restore(),
                     }
                 }
-                println!("Installing {}", version.tag);
-                version.install(&installdir).try_error()?;
-                true
+            });
+        }
+    });
+
+    win.on_confirm_install({
+        let main_win_weak = win.as_weak();
+        let manager_ref = manager_ref.clone();
+        move |version_index| {
+            let Some(version) = shown_release(version_index) else { return };
+            let Some(current) = manager_ref.borrow().current.clone() else { return main_win_weak.unwrap().invoke_install_version(version_index) };
+            let reinstall = current.tag == version.tag;
+            let downgrade = manage::version_key(&version.tag) < manage::version_key(&current.tag);
+            if !reinstall && !downgrade {
+                return main_win_weak.unwrap().invoke_install_version(version_index);
             }
-        });
-    }
+            let (message, warning, ok) = if reinstall {
+                (format!("Reinstall {}? Your settings will be preserved.", version.tag), String::new(), "Reinstall")
+            } else {
+                (format!("Uninstall {}, install {}? Your settings will be preserved.", current.tag, version.tag),
+                 format!("{} is older than {}. Everyone you play with has to be on the same version of the mod.", version.tag, current.tag),
+                 "Downgrade")
+            };
+            confirm(&message, &warning, ok, {
+                let main_win_weak = main_win_weak.clone();
+                move || main_win_weak.unwrap().invoke_install_version(version_index)
+            });
+        }
+    });
 
     win.on_open_settings({
         let manager_ref = manager_ref.clone();
         let main_win_weak = win.as_weak();
         move || {
             let manager = manager_ref.borrow();
-            let ini = manager.read_settings().try_error()?;
+            // The main settings file has to be there. Anything else the release came with is a bonus.
+            let mut paths = manager.ini_paths().try_error()?.into_iter();
+            let mut files = vec![(paths.next().ok_or("No settings file").try_error()?, manager.read_settings().try_error()?)];
+            for path in paths {
+                match manager.read_ini(&path) {
+                    Ok(ini) => files.push((path, ini)),
+                    Err(e) => warn!("Couldn't read {}: {}", path.display(), e),
+                }
+            }
             let win = SettingsWindow::new().try_error()?;
-            let mut settings_count = 0;
-            // A giant map to convert the rust structure into the slint structure (which has a similar shape but different types)
-            let model = slint::ModelRc::from(Rc::new(slint::VecModel::from(
-                ini.sections().map(|s| Section {
-                    name: s.name().into(),
-                    settings: {
-                        let mut settings = vec![];
-                        let mut help = String::new();
-                        for entry in s.entries() {
-                            match entry {
-                                ini::Entry::Blank => { help.truncate(0) },
-                                ini::Entry::Comment(line) => {
-                                    if help.len() > 0 { help.push_str("\n") }
-                                    help.push_str(line.trim_start_matches(&[' ', ';'][..]).trim_end());
-                                },
-                                ini::Entry::KV { key, value } => {
-                                    settings_count += 1;
-                                    settings.push(Setting {
-                                        kind: {
-                                            // There's no real good way to do this as there aren't really enough solid hints in the ini comments to get this exactly right.
-                                            if key.contains("password") { SettingKind::Password }
-                                            else if help.contains("%") { SettingKind::Number }
-                                            else if s.name().to_lowercase() == "save" ||
-                                                s.name().to_lowercase() == "language" { SettingKind::String }
-                                            else if help.contains("2 =") { SettingKind::Number } // Maybe try to parse this and make a menuselect out of it?
-                                            else if help.contains("1 =") { SettingKind::Boolean } // Maybe try to parse this and make a menuselect out of it?
-                                            else if s.name().to_lowercase() == "gameplay" { SettingKind::Boolean }
-                                            else { SettingKind::String }
-                                        },
-                                        help: help.into(),
-                                        name: key.clone().into(),
-                                        value: value.clone().into(),
-                                    });
-                                    help = String::new();
-                                },
-                            }
-                        }
-                        slint::ModelRc::from(Rc::new(slint::VecModel::from(settings)))
-                    },
-                }).collect::<Vec<Section>>()
-            )));
-            win.set_settings(model);
-            win.set_settings_count(settings_count);
-            let ini_rc = Rc::new(RefCell::new(ini));
+            win.set_theme(config::get().theme.color_theme());
+            win.set_high_contrast(config::get().high_contrast);
+            win.set_allow_password_reveal(config::get().allow_password_reveal);
+            let mod_version = manager.current.as_ref().map(|r| r.tag.clone());
+            let names: Vec<String> = files.iter().map(|(path, _)| ini_file_name(path)).collect();
+            win.set_files(slint::ModelRc::from(Rc::new(slint::VecModel::from(files.iter().map(|(path, ini)| {
+                ini_file_model(path, ini, mod_version.as_deref())
+            }).collect::<Vec<IniFile>>()))));
+            // Back where they were last time
+            let view = config::get().settings_view;
+            restore_geometry(win.window(), view.window);
+            if let Some(index) = view.file.as_ref().and_then(|file| names.iter().position(|n| n == file)) {
+                win.set_current_file(index as i32);
+                win.set_scroll_y(view.scroll.get(&names[index]).copied().unwrap_or_default());
+            }
+            win.on_section_toggled(|file, section, collapsed| {
+                config::update(|c| {
+                    let sections = c.settings_view.collapsed.entry(file.to_string()).or_default();
+                    if collapsed { sections.insert(section.to_string()); } else { sections.remove(section.as_str()); }
+                    if sections.is_empty() { c.settings_view.collapsed.remove(file.as_str()); }
+                }).try_log("saving collapsed sections")?;
+            });
+            win.on_remember_scroll(|file, y| {
+                config::update(|c| { c.settings_view.scroll.insert(file.to_string(), y); }).try_log("saving settings scroll position")?;
+            });
+            win.on_remembered_scroll(|file| config::get().settings_view.scroll.get(file.as_str()).copied().unwrap_or_default());
+            win.on_show_docs({
+                let weak_win = win.as_weak();
+                move |section, key| {
+                    let weak_win = weak_win.clone();
+                    let win = weak_win.unwrap();
+                    win.set_docs_key(key.clone());
+                    win.set_docs(slint::SharedString::new());
+                    win.set_docs_loading(true);
+                    slint::spawn_local(async move {
+                        let docs = tokio::task::spawn_blocking({
+                            let (section, key) = (section.to_string(), key.to_string());
+                            move || moddocs::section_for(&section, &key)
+                        }).await.unwrap_or_else(|e| { warn!("Looking up the docs didn't finish: {}", e); None });
+                        let Some(win) = weak_win.upgrade() else { return };
+                        if win.get_docs_key() != key { return } // They've moved on to another one
+                        win.set_docs_loading(false);
+                        win.set_docs(docs.unwrap_or_default().into());
+                    }).try_log("looking up docs")?;
+                }
+            });
+            win.on_open_folder({
+                let manager_ref = manager_ref.clone();
+                move || {
+                    let Some(ref dir) = manager_ref.borrow().dir else { return };
+                    open_path(&dir.mod_dir()).try_error()?;
+                }
+            });
+            let originals = Rc::new(RefCell::new(files.clone())); // What's in the files, to tell if anything's changed
+            let files_rc = Rc::new(RefCell::new(files));
+            win.on_share({
+                let files_rc = files_rc.clone();
+                let weak_win = win.as_weak();
+                move |password_only| {
+                    let qr = share::qr_code(&share::text(&files_rc.borrow()[0].1, password_only)).try_error()?;
+                    weak_win.unwrap().set_share_qr(qr);
+                }
+            });
+            win.set_share_url(config::get().settings_url.unwrap_or_default().into());
+            win.on_upload_gist({
+                let files_rc = files_rc.clone();
+                let weak_win = win.as_weak();
+                move || {
+                    let config = config::get();
+                    let token = config.github_token.ok_or("Uploading to a gist needs a GitHub token (with gist access). Add one in Preferences.").try_error()?;
+                    let text = files_rc.borrow()[0].1.export();
+                    let weak_win = weak_win.clone();
+                    slint::spawn_local(async move {
+                        let uploaded = tokio::task::spawn_blocking(move || share::upload(&text, token.expose(), config.settings_gist.as_deref())).await
+                            .map_err(|e| format!("Uploading didn't finish: {}", e)).try_error()?;
+                        let (gist, url) = uploaded.try_error()?;
+                        info!("Uploaded settings to {}", url);
+                        config::update(|c| { c.settings_gist = Some(gist); c.settings_url = Some(url.clone()) }).try_log("saving gist")?;
+                        let Some(win) = weak_win.upgrade() else { return };
+                        win.set_share_url(url.into());
+                    }).try_log("uploading settings")?;
+                }
+            });
+            let undo = Rc::new(RefCell::new(SettingsUndo::default()));
+            win.on_import_from_url({
+                let (files_rc, originals, undo) = (files_rc.clone(), originals.clone(), undo.clone());
+                let mod_version = mod_version.clone();
+                let weak_win = win.as_weak();
+                move |url| {
+                    let (files_rc, originals, undo) = (files_rc.clone(), originals.clone(), undo.clone());
+                    let (mod_version, weak_win) = (mod_version.clone(), weak_win.clone());
+                    slint::spawn_local(async move {
+                        let downloaded = tokio::task::spawn_blocking({ let url = url.to_string(); move || share::download(&url) }).await
+                            .map_err(|e| format!("Downloading didn't finish: {}", e)).try_error()?;
+                        let imported = downloaded.try_error()?;
+                        let Some(win) = weak_win.upgrade() else { return };
+                        let mut files = files_rc.borrow_mut();
+                        // Not saved until they save it, so it can be undone like anything else
+                        undo.borrow_mut().record(None, &files);
+                        files[0].1.merge(&imported);
+                        info!("Imported settings from {}", url);
+                        config::update(|c| c.settings_url = Some(url.to_string())).try_log("saving settings url")?;
+                        show_settings_files(&win, &files, &originals.borrow(), mod_version.as_deref());
+                    }).try_log("importing settings")?;
+                }
+            });
+            win.on_undo({
+                let (files_rc, originals, undo) = (files_rc.clone(), originals.clone(), undo.clone());
+                let mod_version = mod_version.clone();
+                let weak_win = win.as_weak();
+                move || {
+                    let mut files = files_rc.borrow_mut();
+                    let Some(before) = undo.borrow_mut().undo(&files) else { return };
+                    *files = before;
+                    show_settings_files(&weak_win.unwrap(), &files, &originals.borrow(), mod_version.as_deref());
+                }
+            });
+            win.on_redo({
+                let (files_rc, originals, undo) = (files_rc.clone(), originals.clone(), undo.clone());
+                let mod_version = mod_version.clone();
+                let weak_win = win.as_weak();
+                move || {
+                    let mut files = files_rc.borrow_mut();
+                    let Some(after) = undo.borrow_mut().redo(&files) else { return };
+                    *files = after;
+                    show_settings_files(&weak_win.unwrap(), &files, &originals.borrow(), mod_version.as_deref());
+                }
+            });
             win.on_set({
-                let ini_rc = ini_rc.clone();
-                move |section, key, new_value| {
-                    let mut ini = ini_rc.borrow_mut();
+                let (files_rc, originals, undo) = (files_rc.clone(), originals.clone(), undo.clone());
+                let weak_win = win.as_weak();
+                move |file, section, key, new_value| {
+                    let mut files = files_rc.borrow_mut();
+                    if files.get(file as usize).and_then(|(_, ini)| ini.get(section.as_str(), key.as_str())) == Some(new_value.as_str()) { return }
+                    undo.borrow_mut().record(Some((file, section.to_string(), key.to_string())), &files);
+                    let Some((_, ref mut ini)) = files.get_mut(file as usize) else { return };
                     ini.set(section.as_str(), key.as_str(), new_value.as_str());
+                    weak_win.unwrap().set_dirty(settings_dirty(&files, &originals.borrow()));
                 }
             });
             win.on_save({
-                let ini_rc = ini_rc.clone();
+                let files_rc = files_rc.clone();
+                let originals = originals.clone();
+                let weak_win = win.as_weak();
                 let manager_ref = manager_ref.clone();
                 let main_win_weak = main_win_weak.clone();
                 move || {
-                    let ini = ini_rc.borrow();
+                    let win = weak_win.unwrap();
                     let manager = manager_ref.borrow();
-                    manager.write_settings(&ini).try_error()?;
+                    // Don't write over changes made in Notepad (or wherever) without asking
+                    let conflicts = reload_external_changes(&win, &manager, &mut files_rc.borrow_mut(), &mut originals.borrow_mut());
+                    if !conflicts.is_empty() { return false }
+                    for (path, ini) in files_rc.borrow().iter() {
+                        let old = manager.read_ini(path).ok(); // Just for the history
+                        manager.write_ini(path, ini).try_error()?;
+                        if let Some(old) = old {
+                            for (section, key, from, to) in old.changes(ini) {
+                                history::record_setting(section, key, from, to);
+                            }
+                        }
+                    }
+                    *originals.borrow_mut() = files_rc.borrow().clone();
+                    win.set_dirty(false);
 
                     if let Some(main_win) = main_win_weak.upgrade() {
-                        main_win.set_password(manager.get_password().try_log("re-reading password after saving settings")?.into());
+                        main_win.set_password(manager.get_password().try_log("re-reading password after saving settings")?.expose().into());
                     }
+                    true
                 }});
+            // Something else writing to the files while this is open
+            let watcher = manager.dir.as_ref().map(|dir| watch_ini_files(&win, files_rc.borrow().iter().map(|(path, _)| dir.path().join(path)).collect()));
+            win.on_file_changed({
+                let (files_rc, originals, manager_ref) = (files_rc.clone(), originals.clone(), manager_ref.clone());
+                let weak_win = win.as_weak();
+                move || {
+                    let win = weak_win.unwrap();
+                    reload_external_changes(&win, &manager_ref.borrow(), &mut files_rc.borrow_mut(), &mut originals.borrow_mut());
+                }
+            });
+            win.on_resolve_external_change({
+                let (files_rc, originals, manager_ref) = (files_rc.clone(), originals.clone(), manager_ref.clone());
+                let weak_win = win.as_weak();
+                move |keep_mine| {
+                    let win = weak_win.unwrap();
+                    let manager = manager_ref.borrow();
+                    let mut files = files_rc.borrow_mut();
+                    let mut originals = originals.borrow_mut();
+                    let mod_version = manager.current.as_ref().map(|r| r.tag.clone());
+                    for (index, ((path, ini), (_, original))) in files.iter_mut().zip(originals.iter_mut()).enumerate() {
+                        let Ok(disk) = manager.read_ini(path) else { continue };
+                        if disk.same_settings(original) { continue }
+                        info!("{} was changed by something else, {}", path.display(), if keep_mine { "keeping ours" } else { "reloading" });
+                        if !keep_mine {
+                            *ini = disk.clone();
+                            slint::Model::set_row_data(&win.get_files(), index, ini_file_model(path, ini, mod_version.as_deref()));
+                        }
+                        *original = disk; // Either way, it's been seen
+                    }
+                    win.set_external_change("".into());
+                    win.set_dirty(settings_dirty(&files, &originals));
+                }
+            });
             win.on_close({
                 let weak_win = win.as_weak();
+                let watcher = RefCell::new(watcher);
                 move || {
+                    watcher.take(); // Stops watching
                     let win = weak_win.unwrap();
+                    let file = slint::Model::row_data(&win.get_files(), win.get_current_file() as usize).map(|f| f.name.to_string());
+                    config::update(|c| {
+                        let view = &mut c.settings_view;
+                        if let Some(ref file) = file { view.scroll.insert(file.clone(), win.get_scroll_y()); }
+                        view.file = file;
+                        view.window = Some(window_geometry(win.window(), view.window));
+                    }).try_log("saving settings window layout");
                     win.hide().try_log("closing settings window")?;
                 }
             });
 
+            win.window().on_close_requested({
+                let weak_win = win.as_weak();
+                move || {
+                    weak_win.unwrap().invoke_close_unless_dirty();
+                    slint::CloseRequestResponse::KeepWindowShown // close() hides it, if it's ok to
+                }
+            });
+
             win.show().try_log("showing settings dialog")?;
         }
     });
 }
 
-fn launch(exe: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Launching {:?}", &exe);
-    if !exe.is_file() {
-        Err(format!("Couldn't find {:?} to launch", exe))?;
-    }
-    let mut child = std::process::Command::new(exe.clone())
-        .current_dir(&exe.parent().ok_or(format!("Couldn't find parent directory for {}", &exe.display()))?)
-        .spawn().map_err(|e| format!("Launching {:?} failed: {}", &exe, e))?;
-    std::thread::spawn(move || {
-        let _ = child.wait(); // we really don't care if it failed
-    });
-    Ok(())
+fn ini_file_name(path: &std::path::Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
 }
 
-use crate::breaker::Breaker;
-
-/// Convenience functions added to Result to display dialogs for errors or log them to stdout (eating the error
-/// so you can use `?` in a function that returns `()`)
-trait UIError<T> {
-    fn try_log(self, context: &str) -> Breaker<T>;
-    fn try_error(self) -> Breaker<T>;
-    fn try_fatal(self) -> Breaker<T>;
+fn ini_file_model(path: &std::path::Path, ini: &ini::Ini, mod_version: Option<&str>) -> IniFile {
+    let name = ini_file_name(path);
+    let (sections, settings_count) = settings_model(ini, mod_version, &name);
+    let problems = ini.diagnostics().iter().map(|d| match d.text.to_lowercase().contains("password") {
+        true => format!("Line {}: {}", d.line, d.reason), // Could be showing on a stream
+        false => format!("Line {}: {}\n    {}", d.line, d.reason, d.text.trim()),
+    }).collect::<Vec<_>>().join("\n");
+    IniFile { name: name.into(), sections, settings_count, problems: problems.into() }
 }
 
-impl<T,E> UIError<T> for Result<T, E>
-where E: std::fmt::Display,
-      E: Into<Box<dyn Error>> {
-    fn try_log(self, context: &str) -> Breaker<T> {
-        match self {
-            Ok(t) => Breaker::cont(t),
-            Err(e) => { println!("Error while {context}: {e}"); Breaker::brk() },
-        }
+// Tell the settings window when one of `paths` changes. Editors tend to replace files rather than write to them, so
+// it's their directories that get watched. Watching stops when the watcher is dropped.
+fn watch_ini_files(win: &SettingsWindow, paths: Vec<PathBuf>) -> Option<notify::RecommendedWatcher> {
+    use notify::Watcher;
+    let weak_win = win.as_weak();
+    let names: Vec<_> = paths.iter().filter_map(|p| p.file_name().map(|n| n.to_os_string())).collect();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else { return };
+        if !(event.kind.is_modify() || event.kind.is_create()) { return }
+        if !event.paths.iter().any(|p| p.file_name().map(|n| names.iter().any(|name| name == n)).unwrap_or(false)) { return }
+        weak_win.upgrade_in_event_loop(|win| win.invoke_file_changed()).try_log("passing on settings file change");
+    }).try_log("watching settings files")?;
+    let mut dirs: Vec<_> = paths.iter().filter_map(|p| p.parent()).collect();
+    dirs.sort();
+    dirs.dedup();
+    for dir in dirs {
+        watcher.watch(dir, notify::RecursiveMode::NonRecursive).try_log(&format!("watching {}", dir.display()))?;
     }
+    Some(watcher)
+}
 
-    fn try_error(self) -> Breaker<T> {
-        match self {
-            Ok(t) => Breaker::cont(t),
-            Err(e) => { error(e.into()); Breaker::brk() },
-        }
+// Pick up changes something else made to the settings files since they were read. Files without edits in the window
+// just get reloaded. Files with them are conflicts: they're returned, and the window asks what to do about them.
+fn reload_external_changes(win: &SettingsWindow, manager: &manage::EldenRingManager, files: &mut [(PathBuf, ini::Ini)],
+                           originals: &mut [(PathBuf, ini::Ini)]) -> Vec<usize> {
+    let mod_version = manager.current.as_ref().map(|r| r.tag.clone());
+    let mut conflicts = vec![];
+    for (index, ((path, ini), (_, original))) in files.iter_mut().zip(originals.iter_mut()).enumerate() {
+        let Ok(disk) = manager.read_ini(path) else { continue };
+        if disk.same_settings(original) { continue } // Including our own saves
+        if !ini.same_settings(original) { conflicts.push(index); continue }
+        info!("{} was changed by something else, reloading", path.display());
+        *ini = disk.clone();
+        *original = disk;
+        slint::Model::set_row_data(&win.get_files(), index, ini_file_model(path, ini, mod_version.as_deref()));
     }
+    if !conflicts.is_empty() {
+        warn!("Settings files changed while being edited: {:?}", conflicts.iter().map(|&i| &files[i].0).collect::<Vec<_>>());
+        win.set_external_change(conflicts.iter().map(|&i| ini_file_name(&files[i].0)).collect::<Vec<_>>().join(", ").into());
+    }
+    conflicts
+}
 
-    fn try_fatal(self) -> Breaker<T> {
-        match self {
-            Ok(t) => Breaker::cont(t),
-            Err(e) => { fatal(e.into()); Breaker::brk() },
-        }
+// Changing something back isn't a change
+fn settings_dirty(files: &[(PathBuf, ini::Ini)], originals: &[(PathBuf, ini::Ini)]) -> bool {
+    files.iter().zip(originals).any(|((_, ini), (_, original))| !original.same_settings(ini))
+}
+
+// After everything in the settings window changed at once (an undo, an import)
+fn show_settings_files(win: &SettingsWindow, files: &[(PathBuf, ini::Ini)], originals: &[(PathBuf, ini::Ini)], mod_version: Option<&str>) {
+    for (index, (path, ini)) in files.iter().enumerate() {
+        slint::Model::set_row_data(&win.get_files(), index, ini_file_model(path, ini, mod_version));
     }
+    win.set_dirty(settings_dirty(files, originals));
 }
 
-slint::slint! {
-    import { Button, ComboBox, LineEdit, ListView, ScrollView, Switch, StandardButton } from "std-widgets.slint";
-    component LightText inherits Text {
-        color: white;
+// Every file in the settings window, and where it came from
+type IniFiles = Vec<(PathBuf, ini::Ini)>;
+
+// (file index, section, key)
+type SettingKey = (i32, String, String);
+
+// The settings window's undo and redo. Each step is a copy of all the files from before (or after) it.
+#[derive(Default)]
+struct SettingsUndo {
+    undo: Vec<(Option<SettingKey>, IniFiles)>, // What got changed, and what things were like before
+    redo: Vec<IniFiles>,
+}
+
+impl SettingsUndo {
+    // `changing` is None for things that change more than one setting (imports)
+    fn record(&mut self, changing: Option<SettingKey>, before: &[(PathBuf, ini::Ini)]) {
+        self.redo.clear();
+        // Typing into a box changes it on every key, but it's one change as far as undo goes
+        if changing.is_some() && self.undo.last().map(|(last, _)| last) == Some(&changing) { return }
+        self.undo.push((changing, before.to_vec()));
     }
 
-    component Frame inherits Rectangle {
-        background: #000000aa;
-        border-color: #000000;
-        border-width: 1px;
-        border-radius: 5px;
+    fn undo(&mut self, current: &[(PathBuf, ini::Ini)]) -> Option<IniFiles> {
+        let (_, before) = self.undo.pop()?;
+        self.redo.push(current.to_vec());
+        Some(before)
     }
 
-    component PasswordEdit {
-        callback new-password(string) -> bool;
-        in-out property text <=> pass.text;
-        property<bool> show-password: false;
-
-        Rectangle {
-            pass := LineEdit {
-                width: 100%;
-                input-type: root.show-password ? InputType.text : InputType.password;
-                edited => {
-                    root.new-password(pass.text)
-                }
-                accepted => {
-                    root.new-password(pass.text)
-                }
-            }
-            Rectangle {
-                width: image.width;
-                x: pass.width - image.width - 5px;
-
-                image := Image {
-                    colorize: white;
-                    source: root.show-password ? @image-url("assets/eye-slash-fill.svg") : @image-url("assets/eye-fill.svg");
-                    image-fit: cover;
-                    //width: self.height;
-                }
-                TouchArea {
-                    clicked => {
-                        root.show-password = !root.show-password;
+    fn redo(&mut self, current: &[(PathBuf, ini::Ini)]) -> Option<IniFiles> {
+        let after = self.redo.pop()?;
+        self.undo.push((None, current.to_vec()));
+        Some(after)
+    }
+}
+
+// The slint version of `ini` (from the file named `file`), and how many settings it has
+fn settings_model(ini: &ini::Ini, mod_version: Option<&str>, file: &str) -> (slint::ModelRc<Section>, i32) {
+    let mut settings_count = 0;
+    let collapsed = config::get().settings_view.collapsed;
+    // A giant map to convert the rust structure into the slint structure (which has a similar shape but different types)
+    let model = slint::ModelRc::from(Rc::new(slint::VecModel::from(
+        ini.sections().map(|s| Section {
+            name: s.name().into(),
+            collapsed: collapsed.get(file).is_some_and(|sections| sections.contains(s.name())),
+            settings: {
+                let mut settings = vec![];
+                let mut help = String::new();
+                for entry in s.entries() {
+                    match entry {
+                        ini::Entry::Blank(_) => { help.truncate(0) },
+                        ini::Entry::Comment(line) => {
+                            if help.len() > 0 { help.push_str("\n") }
+                            help.push_str(line.trim_start_matches(&[' ', ';'][..]).trim_end());
+                        },
+                        ini::Entry::KV { key, value, .. } => {
+                            settings_count += 1;
+                            let kind = settings_schema::kind(mod_version, s.name(), key, &help, value);
+                            let choices = match kind {
+                                settings_schema::Kind::Choice(ref choices) => choices.clone(),
+                                _ => vec![],
+                            };
+                            let strings = |f: fn(&(String, String)) -> &String| -> slint::ModelRc<slint::SharedString> {
+                                Rc::new(slint::VecModel::from(choices.iter().map(|c| f(c).into()).collect::<Vec<slint::SharedString>>())).into()
+                            };
+                            settings.push(Setting {
+                                kind: match kind {
+                                    settings_schema::Kind::Boolean   => SettingKind::Boolean,
+                                    settings_schema::Kind::Number    => SettingKind::Number,
+                                    settings_schema::Kind::String    => SettingKind::String,
+                                    settings_schema::Kind::Password  => SettingKind::Password,
+                                    settings_schema::Kind::Choice(_) => SettingKind::Choice,
+                                    settings_schema::Kind::Percent { .. } => SettingKind::Percent,
+                                },
+                                range: match kind {
+                                    settings_schema::Kind::Percent { min, max, step } => Range { minimum: min as f32, maximum: max as f32, step: step as f32 },
+                                    _ => Range::default(),
+                                },
+                                choices: strings(|(_, label)| label),
+                                choice_values: strings(|(value, _)| value),
+                                choice_index: choices.iter().position(|(v, _)| v.eq_ignore_ascii_case(value)).map(|i| i as i32).unwrap_or(-1),
+                                help: help.into(),
+                                name: key.clone().into(),
+                                value: value.clone().into(),
+                            });
+                            help = String::new();
+                        },
                     }
                 }
+                slint::ModelRc::from(Rc::new(slint::VecModel::from(settings)))
+            },
+        }).collect::<Vec<Section>>()
+    )));
+    (model, settings_count)
+}
+
+// Launch and then whatever's supposed to happen after (see on_launch)
+fn launch_game(win: &MainWindow, manager_ref: &Rc<RefCell<manage::EldenRingManager>>) {
+    let manager = manager_ref.borrow();
+    let launcher = manager.launcher_path().try_error()?;
+    backup::backup_if_due().try_warn(); // Not worth stopping them from playing over
+    launch(launcher).try_error()?;
+    if config::get().copy_password_on_launch {
+        copy_password(&manager);
+    }
+    if let Some(ref dir) = manager.dir {
+        troubleshoot::watch_after_launch(dir.clone(), manager.current.clone(), {
+            let manager_ref = manager_ref.clone();
+            let weak_win = win.as_weak();
+            move || {
+                known_good::save(&manager_ref.borrow()).try_log("saving last working setup")?;
+                if let Some(win) = weak_win.upgrade() { show_known_good(&win) }
             }
-        }
+        });
+    }
+    match config::get().after_launch {
+        config::AfterLaunch::StayOpen => {},
+        config::AfterLaunch::Minimize => win.window().set_minimized(true),
+        config::AfterLaunch::Exit => {
+            save_geometry(win);
+            slint::quit_event_loop().try_log("quitting event loop after launch");
+        },
     }
+}
 
-    ////////// Main Window //////////
-
-    export component MainWindow inherits Window {
-        callback install(int) -> bool;
-        pure callback version-at-index(int) -> string;
-        pure callback changelog-at-index(int) -> string;
-        callback launch;
-        callback exit;
-        callback refresh;
-        callback new-password(string) -> bool;
-        callback open-url(string);
-        callback open-settings;
-        in property<string> install-path;
-        in property<string> current-version;
-        in property<[string]> available-versions;
-        in property<string> copyright: "[[ failed-to-detect-copyright ]]";
-        in property<string> my-version: "0.0.0-local";
-        in property<string> my-upgrade-version: "";
-        property<bool> show-password: false;
-        in-out property password <=> pass.text;
-        property<length> em: 16px;
-
-        title: "Elden Ring Seamless Co-op Manager  v" + my-version;
-        icon: @image-url("assets/eldenringlogo.jpg");
-        default-font-size: 1*em;
-        max-width: 10000px;
-
-        Rectangle {
-            width: Math.max(parent.height,parent.width);
-            height: Math.max(parent.height,parent.width);
-            y: 0;
-            x: 0;
-            Image {
-                source: @image-url("assets/eldenring.jpg");
-                image-fit: cover;
-                width: parent.height;
-                height: parent.height;
-            }
+// Offer a newer release than what's installed, if there is one, or else just launch. The release list gets fetched
+// first if it's been a while (in the background, since github can take its time). Errors there just get logged, it's
+// not worth stopping anyone from playing over.
+fn check_before_launch(win: &MainWindow, manager_ref: &Rc<RefCell<manage::EldenRingManager>>) {
+    if !manager_ref.borrow().refresh_due(FOCUS_REFRESH_AGE) || app::busy() { return offer_or_launch(win, manager_ref) }
+    app::update(app::Msg::Started("Checking for updates...".to_string()));
+    let mut fetcher = manager_ref.borrow().clone();
+    let (weak_win, manager_ref) = (win.as_weak(), manager_ref.clone());
+    slint::spawn_local(async move {
+        let fetched = tokio::task::spawn_blocking(move || fetcher.fetch_releases().map(|()| fetcher)).await;
+        app::update(app::Msg::Finished);
+        match fetched {
+            Ok(Ok(fetcher)) => app::update(app::Msg::ReleasesLoaded(Box::new(fetcher))),
+            Ok(Err(e)) => warn!("Couldn't check for a new version before launching: {}", e),
+            Err(e) => warn!("Checking for a new version before launching didn't finish: {}", e),
         }
-        VerticalLayout {
-            padding-top: 180px;
-            padding-bottom: 30px;
-            padding-left: 30px;
-            padding-right: 30px;
-            spacing: 30px;
-
-            Frame {
-                vertical-stretch: 0;
-                GridLayout {
-                    padding: 50px;
-                    spacing: 10px;
-                    Row {
-                        LightText {
-                            text: "Elden Ring:";
-                        }
-                        LightText {
-                            colspan: 2;
-                            wrap: word-wrap;
-                            text: root.install-path == "" ? "<Not Found>" : root.install-path;
-                        }
-                    }
-                    Row {
-                        LightText {
-                            vertical-alignment: center;
-                            text: "Current Mod Version:";
-                        }
-                        LightText {
-                            vertical-alignment: center;
-                            text: root.current-version == "" ? "<Unknown>" : root.current-version;
-                        }
-                        Button {
-                            horizontal-stretch: 0;
-                            text: "Launch";
-                            height: 4*em;
-                            width: 10*em;
-                            primary: root.current-version == root.version-at-index(cb.current-index);
-                            clicked => {
-                                root.launch()
-                            }
+        let Some(win) = weak_win.upgrade() else { return };
+        offer_or_launch(&win, &manager_ref);
+    }).try_error()?;
+}
 
-                            enabled: root.install-path != "" && cb.current-index != -1;
-                        }
-                    }
-                    Row {
-                        LightText {
-                            text: "New Mod Version:";
-                        }
-                        cb := ComboBox {
-                            model: root.available-versions;
-                            selected => {
-                                changelog-scroll.viewport-y = 0;
-                            }
-                        }
-                        Button {
-                            text: root.current-version == root.version-at-index(cb.current-index) ? "Reinstall" : "Install";
-                            enabled: root.install-path != "" && cb.current-index != -1;
-                            primary: root.current-version != root.version-at-index(cb.current-index);
-                            clicked => {
-                                if (!root.install(cb.current-index)) { return; }
-                                if (!root.new-password(pass.text)) { return; }
-                                root.refresh();
-                                cb.current-value = cb.model[cb.current-index];
-                            }
-                            min-width: 1.5in;
-                        }
-                    }
-                    Row {
-                        LightText {
-                            text: "Password:";
-                        }
-                        pass := PasswordEdit {
-                            new-password(new) => { root.new-password(new) }
-                        }
-                        Button {
-                            text: "More Settings...";
-                            enabled: root.install-path != "" && cb.current-index != -1;
-                            clicked => {
-                                root.open-settings();
-                            }
-                        }
-                    }
-                }
-            }
-            Frame {
-                VerticalLayout {
-                    spacing: 10px;
-                    padding: 50px;
-                    LightText {
-                        font-size: 24px;
-                        font-weight: 750;
-                        text: root.version-at-index(cb.current-index) + " Release Notes";
-                    }
-                    changelog-scroll := ScrollView {
-                        min-height:changelog.font-size*10;
-                        viewport-height: changelog.height;
-
-                        changelog := LightText {
-                            font-size: 16px;
-                            vertical-stretch: 1;
-                            x: 5px;
-                            width: parent.width - 25px;
-                            wrap: word-wrap;
-                            text: root.changelog-at-index(cb.current-index);
-                        }
-                    }
-                }
-            }
-        }
-        HorizontalLayout {
-            y: parent.height - self.height;
-            height: 12px;
-            alignment: end;
-
-            Rectangle {
-                background: black;
-                HorizontalLayout {
-                    padding-left: 3px;
-                    spacing: 3px;
-                    alignment: start;
-                    Text {
-                        font-size: 10px;
-                        color: white;
-                        text: copyright;
-                    }
-                    octocat := Image {
-                        colorize: white;
-                        source: @image-url("assets/github.svg");
-                        height: 9px;
-                        width: 9px;
-                    }
-                }
-                TouchArea {
-                    clicked => {
-                        root.open-url("https://github.com/caldwell/erscom");
-                    }
-                }
-            }
-            Rectangle { // spacer
-                background: black;
-                width: 30px;
-            }
-        }
-        if root.my-upgrade-version != "" : Rectangle {
-            y: 0;
-            height: 20px;
-            background: black;
-            HorizontalLayout {
-                alignment: center;
-                HorizontalLayout {
-                    alignment: start;
-                    spacing: 5px;
-                    Image {
-                        colorize: white;
-                        source: @image-url("assets/cloud-arrow-down-fill.svg");
-                        width: 20px;
-                        height: 20px;
-                    }
-                    Text {
-                        text: "Download New Manager Version "+root.my-upgrade-version;
-                        color: white;
-                        font-size: 18px;
-                        font-weight: 700;
+fn offer_or_launch(win: &MainWindow, manager_ref: &Rc<RefCell<manage::EldenRingManager>>) {
+    let newer = manager_ref.borrow().update_available(config::get().show_beta).cloned();
+    match newer {
+        Some(newer) => offer_update_before_launch(win, manager_ref, newer),
+        None => launch_game(win, manager_ref),
+    }
+}
+
+fn offer_update_before_launch(win: &MainWindow, manager_ref: &Rc<RefCell<manage::EldenRingManager>>, newer: manage::Release) {
+    let Some(installdir) = manager_ref.borrow().dir.clone() else { return launch_game(win, manager_ref) };
+    let current = manager_ref.borrow().current.as_ref().map(|r| r.tag.clone()).unwrap_or_default();
+    let weak_win = win.as_weak();
+    ask(&format!("Update to {} before launching?", newer.tag),
+        &format!("{} is installed now. Everyone you play with has to be on the same version of the mod.", current),
+        "Update and Launch", "Launch Without Updating",
+        {
+            let (weak_win, manager_ref) = (weak_win.clone(), manager_ref.clone());
+            move || {
+                let password = Secret::new(weak_win.unwrap().get_password().to_string());
+                let retry = { let weak_win = weak_win.clone(); move || if let Some(win) = weak_win.upgrade() { win.invoke_launch() } };
+                install_release(&manager_ref, &installdir, &newer, retry, {
+                    let (weak_win, manager_ref) = (weak_win.clone(), manager_ref.clone());
+                    move || {
+                        manager_ref.borrow().set_password(&password).try_error()?; // Like install-version() does
+                        launch_game(&weak_win.unwrap(), &manager_ref);
                     }
-                }
+                });
             }
-            TouchArea {
-                clicked => {
-                    root.open-url("https://github.com/caldwell/erscom/releases/latest");
-                }
-            }
-        }
+        },
+        {
+            let manager_ref = manager_ref.clone();
+            move || launch_game(&weak_win.unwrap(), &manager_ref)
+        });
+}
+
+// Uninstall whatever's there and install `version` in its place, in the background. `then` runs once it's installed.
+/// `retry` is for the error dialog's Retry button, when something has the mod's files open.
+fn install_release(manager_ref: &Rc<RefCell<manage::EldenRingManager>>, installdir: &manage::EldenRingDir, version: &manage::Release,
+                   retry: impl Fn() + 'static, then: impl Fn() + 'static) {
+    if app::busy() {
+        info!("Not installing {}, something else is going on", version.tag);
+        return toast(&format!("Can't install {} yet, wait for the manager to finish what it's doing first", version.tag));
+    }
+    if queue::game_running(installdir) {
+        let manager_ref = manager_ref.clone();
+        queue::enqueue(installdir, &version.tag, move |tag| install_queued(&manager_ref, tag));
+        show_queued_install();
+        return toast(&format!("Elden Ring is running. {} will be installed when it exits.", version.tag));
+    }
+    let (installdir, version, retry, then) = (installdir.clone(), version.clone(), Rc::new(retry), Rc::new(then));
+    if app::current_detected() {
+        let current = manager_ref.borrow().current.clone();
+        return install_over(current, installdir, version, retry, then);
     }
+    // The background check hasn't finished yet and we need to know what to uninstall. It reads every installed file,
+    // so it can't be done here on the event loop.
+    let (releases, previous) = { let manager = manager_ref.borrow(); (manager.releases.clone(), manager.current.clone()) };
+    app::update(app::Msg::Started("Checking what's installed...".to_string()));
+    slint::spawn_local(async move {
+        let found = manage::find_installed(releases, installdir.clone()).await;
+        app::update(app::Msg::Finished);
+        install_over(found.or(previous), installdir, version, retry, then);
+    }).try_error()?;
+}
 
-    component ErrorGuts inherits Rectangle {
-        in property<string> error;
+// The rest of install_release(), once we know what's installed.
+fn install_over(current: Option<manage::Release>, installdir: manage::EldenRingDir, version: manage::Release, retry: Rc<dyn Fn()>, then: Rc<dyn Fn()>) {
+    let Some(current) = current.filter(|_| installdir.writable()) else { return finish_install(installdir, version, Over::Nothing, retry, then) };
+    if version.can_upgrade_from(&current) { return finish_install(installdir, version, Over::Upgrading(current), retry, then) }
+    app::update(app::Msg::Started(format!("Uninstalling {}...", current.tag)));
+    slint::spawn_local(async move {
+        let uninstalled = tokio::task::spawn_blocking({
+            let (installdir, current) = (installdir.clone(), current.clone());
+            move || { info!("Uninstalling {}", current.tag); current.uninstall(&installdir) }
+        }).await;
+        app::update(app::Msg::Finished);
+        let e = match uninstalled {
+            Ok(Ok(())) => return finish_install(installdir, version, Over::Nothing, retry, then),
+            Ok(Err(e)) => e,
+            Err(e) => return warn!("Uninstalling {} didn't finish: {}", current.tag, e),
+        };
+        // Going ahead anyway could leave a mix of the two versions, so it's up to them
+        warn!("Got error uninstalling {}: {}", current.tag, e);
+        history::record(format!("Couldn't uninstall {}: {}", current.tag, e));
+        ask(&format!("Couldn't uninstall {}: {}", current.tag, e),
+            &format!("Installing {} over the top of it could leave files from both versions.", version.tag),
+            "Force Reinstall Over the Top", "Abort",
+            move || finish_install(installdir.clone(), version.clone(), Over::Forced(current.clone()), retry.clone(), then.clone()),
+            || info!("Not installing, the uninstall failed"));
+    }).try_error()?;
+}
 
-        image := Image {
-            source: @image-url("assets/youdied.png");
-            image-fit: contain;
-            width: parent.width;
-            height: parent.height;
-        }
+// The install [queue] was waiting for the game to exit to do.
+fn install_queued(manager_ref: &Rc<RefCell<manage::EldenRingManager>>, tag: String) {
+    show_queued_install();
+    let Some(installdir) = manager_ref.borrow().dir.clone() else { return };
+    let Some(version) = manager_ref.borrow().releases.iter().find(|r| r.tag == tag).cloned() else {
+        warn!("{} was queued to install but isn't in the release list", tag);
+        return toast(&format!("Couldn't install {}: it isn't in the list of releases any more", tag));
+    };
+    let retry = { let (manager_ref, tag) = (manager_ref.clone(), tag.clone()); move || install_queued(&manager_ref, tag.clone()) };
+    install_release(manager_ref, &installdir, &version, retry, move || toast(&format!("Elden Ring exited, so {} is installed now", tag)));
+}
 
-        Frame {
-            GridLayout {
-                padding: 50px;
-                spacing: 10px;
-                Row {
-                    LightText {
-                        text: "I'm terribly sorry but an error occurred!";
-                        font-size: 36px;
-                        font-weight: 900;
-                    }
-                }
-                Row {
-                    LightText {
-                        text: root.error;
-                        wrap: word-wrap;
-                        max-width: 720px;
-                    }
-                }
-            }
-        }
-    }
+// Something sent over [ipc] by a companion tool.
+fn control(weak_win: &slint::Weak<MainWindow>, manager_ref: &Rc<RefCell<manage::EldenRingManager>>, command: ipc::Command) -> Result<serde_json::Value, String> {
+    let win = weak_win.upgrade().ok_or("The manager is exiting")?;
+    // Fetching the release list holds on to it, and that's not worth waiting on
+    let manager = manager_ref.try_borrow().map_err(|_| "The manager is busy, try again in a moment")?;
+    let current = manager.current.as_ref().map(|r| r.tag.clone());
+    Ok(match command {
+        ipc::Command::List => serde_json::Value::Array(manager.releases.iter().map(|r| serde_json::json!({
+            "tag": r.tag,
+            "date": r.date,
+            "prerelease": r.prerelease,
+            "downloaded": r.downloaded(),
+            "installed": current.as_ref() == Some(&r.tag),
+        })).collect()),
+        ipc::Command::Status => serde_json::json!({
+            "installed": current,
+            "install_path": manager.dir.as_ref().map(|d| d.display()),
+            "game_running": manager.dir.as_ref().is_some_and(queue::game_running),
+            "busy": app::busy(),
+            "queued_install": queue::pending(),
+            "status": status::text(&manager),
+        }),
+        ipc::Command::Install { tag } => {
+            if app::busy() { Err("The manager is busy, try again once it's done")? }
+            let installdir = manager.dir.clone().ok_or("Couldn't find the Elden Ring directory")?;
+            let version = manager.releases.iter().find(|r| r.tag == tag).cloned().ok_or(format!("No mod version named {}", tag))?;
+            drop(manager);
+            info!("Installing {} for a companion tool", tag);
+            history::record(format!("Installing {} (asked for by another program)", tag));
+            let retry = { let (weak_win, manager_ref) = (weak_win.clone(), manager_ref.clone()); move || { control(&weak_win, &manager_ref, ipc::Command::Install { tag: tag.clone() }).try_error()?; } };
+            install_release(manager_ref, &installdir, &version, retry, || {});
+            serde_json::json!("started")
+        },
+        ipc::Command::Launch => {
+            drop(manager);
+            info!("Launching for a companion tool");
+            win.invoke_launch();
+            serde_json::json!("started")
+        },
+    })
+}
 
-    ////////// Error Dialogs //////////
+fn show_queued_install() {
+    let Some(win) = main_window() else { return };
+    win.set_queued_install(queue::pending().unwrap_or_default().into());
+}
 
-    export component ErrorDialog inherits Dialog {
-        in property<string> error <=> message.error;
-        callback ok-clicked;
+// What finish_install() is installing over.
+enum Over {
+    Nothing, // It got uninstalled (or there wasn't anything)
+    Forced(manage::Release), // Couldn't be uninstalled
+    Upgrading(manage::Release), // Still installed, only the files that changed get replaced
+}
 
-        background: black;
-        title: "Error!";
-        message := ErrorGuts {
+// The installing half of install_release().
+fn finish_install(installdir: manage::EldenRingDir, version: manage::Release, over: Over, retry: Rc<dyn Fn()>, then: Rc<dyn Fn()>) {
+    app::update(app::Msg::Started(format!("Installing {}...", version.tag)));
+    slint::spawn_local(async move {
+        let installed = tokio::task::spawn_blocking({
+            let (installdir, version) = (installdir.clone(), version.clone());
+            let upgrading = match over { Over::Upgrading(ref old) => Some(old.clone()), _ => None };
+            move || {
+                if !installdir.writable() {
+                    info!("Can't write to {}", installdir);
+                    return elevate::install(&installdir, &version, upgrading.is_some()).map(|()| vec![]);
+                }
+                if let Some(old) = upgrading { return version.upgrade_from(&old, &installdir, ask_about_file) }
+                info!("Installing {}", version.tag);
+                version.install_with(&installdir, ask_about_file)
+            }
+        }).await;
+        app::update(app::Msg::Finished);
+        let skipped = match installed {
+            Ok(Ok(skipped)) => skipped,
+            Ok(Err(e @ error::ErscomError::GameRunning { .. })) => return report::error_with_retry(e.into(), move || retry()),
+            Ok(Err(e)) => return app::send(app::Msg::Error(e)),
+            Err(e) => return warn!("Installing {} didn't finish: {}", version.tag, e),
+        };
+        if !skipped.is_empty() {
+            report::warn(format!("{} is installed, except for {}", version.tag,
+                                 skipped.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ")).into());
         }
-        Button {
-            text: "Sigh... Ok";
-            dialog-button-role: action;
-            clicked => { ok_clicked() }
+        if let Over::Forced(old) = over {
+            let leftovers = old.leftovers(&installdir, &version).try_log("checking for leftover files")?;
+            if !leftovers.is_empty() {
+                history::record(format!("{} left behind {} files", old.tag, leftovers.len()));
+                report::warn(format!("{} is installed, but these files from {} are still there: {}", version.tag, old.tag,
+                                     leftovers.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ")).into());
+            }
         }
-    }
-
-    export component FatalDialog inherits Dialog {
-        in property<string> error <=> message.error;
+        app::update(app::Msg::InstallFinished(version.clone()));
+        offer_prerequisites(&installdir);
+        then();
+    }).try_error()?;
+}
 
-        background: black;
-        title: "Fatal Error!";
-        message := ErrorGuts {
-        }
-        StandardButton { kind: abort; }
+// One file out of many failing shouldn't have to throw away the whole install. This has to answer before the install
+// can go on, and slint can't do that without going back to the event loop, so it's a plain Windows message box.
+#[cfg(target_os = "windows")]
+fn ask_about_file(path: &std::path::Path, error: &error::ErscomError) -> manage::FileErrorAction {
+    use std::os::windows::ffi::OsStrExt;
+    extern "system" { fn MessageBoxW(hwnd: *mut std::ffi::c_void, text: *const u16, caption: *const u16, kind: u32) -> i32; }
+    const MB_CANCELTRYCONTINUE: u32 = 0x6;
+    const MB_ICONWARNING: u32 = 0x30;
+    const IDTRYAGAIN: i32 = 10;
+    const IDCONTINUE: i32 = 11;
+
+    let wide = |s: &str| std::ffi::OsStr::new(s).encode_wide().chain(std::iter::once(0)).collect::<Vec<u16>>();
+    let text = wide(&format!("Couldn't install {}:\n{}\n\nTry Again, Continue without this file, or Cancel (undoing the install)?",
+                             path.display(), error));
+    let caption = wide("Elden Ring Seamless Co-op Manager");
+    match unsafe { MessageBoxW(std::ptr::null_mut(), text.as_ptr(), caption.as_ptr(), MB_CANCELTRYCONTINUE | MB_ICONWARNING) } {
+        IDTRYAGAIN => manage::FileErrorAction::Retry,
+        IDCONTINUE => manage::FileErrorAction::Skip,
+        _ => manage::FileErrorAction::Abort,
     }
+}
 
-    ////////// Settings Window //////////
-
-    export enum SettingKind { boolean, string, password, number }
-
-    export struct Setting {
-        name: string,
-        kind: SettingKind,
-        value: string,
-        help: string,
-    }
+#[cfg(not(target_os = "windows"))]
+fn ask_about_file(path: &std::path::Path, error: &error::ErscomError) -> manage::FileErrorAction {
+    warn!("Couldn't install {}: {}", path.display(), error);
+    manage::FileErrorAction::Abort
+}
 
-    export struct Section {
-        name: string,
-        settings: [Setting],
-    }
+// The mod installs fine without these, but then the game just starts without co-op
+fn offer_prerequisites(installdir: &manage::EldenRingDir) {
+    let Some(&prereq) = prereqs::missing(installdir).first() else { return };
+    history::record(format!("{} isn't installed", prereq.name));
+    ask(&format!("The mod needs the {}, which isn't installed.", prereq.name),
+        "Without it the mod won't load and the game will start without co-op.",
+        "Install It", "Not Now",
+        move || install_prerequisite(prereq), || {});
+}
 
-    import { Palette } from "std-widgets.slint";
-    export component SettingsWindow inherits Window {
-        callback set(string, string, string);
-        callback save;
-        callback close;
-        in-out property<[Section]> settings: [];
-        in property<int> settings_count; // Not possible to calculate here? (no recursion, no real loops)
-
-        property<length> em: 16px;
-        property<color> faint: Palette.foreground.mix(root.background, 30%);
-        default-font-size: 1*em;
-        title: "Elden Ring Seamless Co-op Settings Editor";
-
-        init => {
-            Palette.color-scheme = ColorScheme.dark;
+pub fn install_prerequisite(prereq: &'static prereqs::Prerequisite) {
+    toast(&format!("Downloading the {}...", prereq.name));
+    slint::spawn_local(async move {
+        match tokio::task::spawn_blocking(move || prereq.install()).await {
+            Ok(Ok(())) => toast("Follow the installer's steps, then launch the game again"),
+            Ok(Err(e)) => { report::error(e.into()); },
+            Err(e) => warn!("Installing {} didn't finish: {}", prereq.name, e),
         }
+    }).try_log("starting prerequisite install");
+}
 
-        VerticalLayout {
-            padding: 1*em;
-            spacing: 10px;
-
-            frame := Frame {
-                VerticalLayout {
-                    padding: 1*em;
-                    ListView {
-                        pure function setting-height(rows: int, sections: int) -> length {
-                            return rows * (1*em /*name*/ + 5px/*spacing*/ + 0.75*em*2/*help*/ + 5px/*spacing*/ + 1px/*hline*/ + 5px*2/*padding*/) +
-                                sections * (1.1*em + 0.5*em/*padding*/) + 1*em/*fudge*/;
-                        }
-
-                        min-width: 300px + 10*em/*max(setting[min-width])*/ + (5px + 2*em)/*padding (right+left)*/ + 25px/*scrollbar*/;
-                        min-height: setting-height(5, 1);
-                        preferred-height: setting-height(settings_count, settings.length);
+// So the host can paste it straight to everyone else
+fn copy_password(manager: &manage::EldenRingManager) {
+    let password = manager.get_password().try_warn()?;
+    if password.expose().is_empty() { return toast("There's no co-op password to copy") }
+    arboard::Clipboard::new().and_then(|mut c| c.set_text(password.expose())).try_warn()?;
+    toast("Copied the co-op password to the clipboard");
+}
 
-                        for section[index] in settings: VerticalLayout {
-                            padding-bottom: 0.5*em;
-                            LightText {
-                                text: section.name;
-                                font-size: 1.1*em;
-                            }
-                            for setting[index] in section.settings: VerticalLayout {
-                                width: parent.width - 25px/*scrollbar*/;
-                                padding: 5px;
-                                padding-left: 2*em;
-                                spacing: 5px;
-                                HorizontalLayout {
-                                    LightText {
-                                        text: setting.name;
-                                        width: 300px; // hack
-                                    }
-                                    if setting.kind == SettingKind.boolean : Switch/*CheckBox*/ {
-                                        checked: setting.value == "1";
-                                        toggled => { set(section.name, setting.name, self.checked ? "1" : "0"); }
-                                    }
-                                    if setting.kind == SettingKind.number : LineEdit {
-                                        text: setting.value;
-                                        input-type: number;
-                                        min-width: 4*em;
-                                        max-width: 8*em;
-                                        edited(new) => { set(section.name, setting.name, new); }
-                                        accepted(new) => { set(section.name, setting.name, new); }
-                                    }
-                                    if setting.kind == SettingKind.string : LineEdit {
-                                        text: setting.value;
-                                        input-type: text;
-                                        min-width: 8*em;
-                                        edited(new) => { set(section.name, setting.name, new); }
-                                        accepted(new) => { set(section.name, setting.name, new); }
-                                    }
-                                    if setting.kind == SettingKind.password : PasswordEdit {
-                                        text: setting.value;
-                                        min-width: 10*em;
-                                        new-password(new) => { set(section.name, setting.name, new); true }
-                                    }
-                                }
-                                LightText {
-                                    padding-bottom: 5px;
-                                    width: 300px;
-                                    text: setting.help;
-                                    color: root.faint;
-                                    wrap: word-wrap;
-                                    font-size: 0.75*em;
-                                }
-                                if index < settings.length - 1: Rectangle {
-                                    height: 1px;
-                                    background: root.faint;
-                                }
-                            }
-                        }
-                    }}
-            }
-            buttons := HorizontalLayout {
-                vertical-stretch: 0;
-                spacing: 10*em;
-                alignment: space-between;
-                Button {
-                    text: "Save Changes";
-                    clicked => {
-                        root.save();
-                        root.close();
-                    }
-                }
-                Button {
-                    text: "Discard Changes";
-                    clicked => { root.close() }
-                }
-            }
-        }
+pub fn launch(exe: PathBuf) -> Result<(), error::ErscomError> {
+    info!("Launching {:?}", &exe);
+    if !exe.is_file() {
+        Err(error::ErscomError::NotFound(format!("Couldn't find {:?} to launch", exe)))?;
     }
+    let mut child = std::process::Command::new(exe.clone())
+        .current_dir(&exe.parent().ok_or(format!("Couldn't find parent directory for {}", &exe.display()))?)
+        .spawn().map_err(error::ErscomError::io(&exe))?;
+    std::thread::spawn(move || {
+        let _ = child.wait(); // we really don't care if it failed
+    });
+    Ok(())
+}
 
+/// Show a file or directory in Explorer (or whatever the platform's equivalent is).
+pub fn open_path(path: &std::path::Path) -> Result<(), error::ErscomError> {
+    info!("Opening {:?}", path);
+    #[cfg(target_os = "windows")]    let opener = "explorer";
+    #[cfg(target_os = "macos")]      let opener = "open";
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))] let opener = "xdg-open";
+    std::process::Command::new(opener).arg(path).spawn().map_err(error::ErscomError::io(path))?;
+    Ok(())
 }
+
+slint::include_modules!();
@@ -0,0 +1,225 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Checking the game directory for the basics the mod needs, before blaming the mod. Each check says what it found and
+// what to do about it if it failed.
+//
+// OneDrive and Windows' Controlled Folder Access both make writes fail for reasons that aren't obvious from the error
+// (OneDrive locks files while it syncs them, and Controlled Folder Access silently blocks apps it doesn't know), so
+// those get their own checks. Running as administrator doesn't get around either of them.
+
+use std::{fs::File, io::Read, path::{Path, PathBuf}};
+
+use tracing::info;
+
+use crate::{backup, ini::Ini, manage::EldenRingDir};
+
+// Mod loaders (and some other mods) go by this name, and the game loads whatever is there
+const PROXY_DLL: &str = "dinput8.dll";
+// What's in the Game directory of every install
+const GAME_FILES: &[&str] = &["regulation.bin", "Data0.bdt", "Data0.bhd"];
+
+/// Something the manager can do about a failed check, instead of just telling them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    /// Install the mod again from a copy of the manager running as administrator.
+    Elevate,
+}
+
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    /// What was found.
+    pub detail: String,
+    /// What to do about it. Empty if it passed.
+    pub fix: &'static str,
+    pub action: Option<Action>,
+}
+
+impl Check {
+    fn new(name: &'static str, passed: bool, detail: String, fix: &'static str) -> Check {
+        Check { name, passed, detail, fix: if passed { "" } else { fix }, action: None }
+    }
+
+    fn with_action(self, action: Action) -> Check {
+        Check { action: (!self.passed).then_some(action), ..self }
+    }
+}
+
+/// Every check, in the order they matter: there's no point worrying about the version of a missing exe.
+pub fn run(dir: &EldenRingDir) -> Vec<Check> {
+    let exe = dir.path().join("eldenring.exe");
+    let onedrive = onedrive(dir);
+    let controlled = controlled_folder_access(dir);
+    // Administrator access only helps when it's plain old permissions in the way
+    let interfering = !onedrive.passed || controlled.as_ref().is_some_and(|c| !c.passed);
+    let writable = Check::new("The manager can change files in the game folder", dir.writable(),
+                              if dir.writable() { "It can".to_string() } else { format!("{} can't be written to", dir) },
+                              if interfering { "See below for what's getting in the way." }
+                              else { "Reinstall the mod as administrator, or move the game out of Program Files with Steam's \"Move install folder\"." });
+    let mut checks = vec![
+        Check::new("Elden Ring is there", exe.is_file(),
+                   if exe.is_file() { format!("Found {}", exe.display()) } else { format!("There's no eldenring.exe in {}", dir) },
+                   "Pick the Game folder inside Elden Ring's install folder (the one with eldenring.exe in it)."),
+        folder_structure(dir),
+        if interfering { writable } else { writable.with_action(Action::Elevate) },
+        onedrive,
+    ];
+    checks.extend(controlled);
+    checks.extend([proxy_dll(dir), game_version(&exe)]);
+    checks.extend(settings_files(dir));
+    for check in checks.iter() {
+        info!("Game check: {}: {} ({})", check.name, if check.passed { "passed" } else { "failed" }, check.detail);
+    }
+    checks
+}
+
+fn folder_structure(dir: &EldenRingDir) -> Check {
+    let mut missing: Vec<String> = GAME_FILES.iter().filter(|f| !dir.path().join(f).is_file()).map(|f| f.to_string()).collect();
+    // Only once the mod's been installed, of course
+    if dir.mod_dir().is_dir() {
+        if !dir.mod_dir().join("ersc.dll").is_file() { missing.push(format!("SeamlessCoop{}ersc.dll", std::path::MAIN_SEPARATOR)) }
+        if !dir.path().join("ersc_launcher.exe").is_file() { missing.push("ersc_launcher.exe".to_string()) }
+    }
+    Check::new("The game's folders look right", missing.is_empty(),
+               if missing.is_empty() { "Everything's where it should be".to_string() } else { format!("Missing: {}", missing.join(", ")) },
+               "Verify the game files in Steam (Properties → Installed Files), then reinstall the mod.")
+}
+
+// The folders the game writes to: its own, and the saves
+fn game_folders(dir: &EldenRingDir) -> Vec<PathBuf> {
+    [Some(dir.path().to_path_buf()), backup::save_dir()].into_iter().flatten().collect()
+}
+
+// Case doesn't matter on Windows, and neither do slashes
+fn inside(path: &Path, dir: &Path) -> bool {
+    let parts = |p: &Path| p.components().map(|c| c.as_os_str().to_string_lossy().to_lowercase()).collect::<Vec<_>>();
+    let dir = parts(dir);
+    !dir.is_empty() && parts(path).starts_with(&dir)
+}
+
+fn onedrive_roots() -> Vec<PathBuf> {
+    ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"].iter().filter_map(std::env::var_os).map(PathBuf::from).collect()
+}
+
+fn onedrive(dir: &EldenRingDir) -> Check {
+    let roots = onedrive_roots();
+    let synced: Vec<String> = game_folders(dir).into_iter().filter(|folder| {
+        // A "OneDrive - Company" folder that the environment doesn't mention is still OneDrive
+        roots.iter().any(|root| inside(folder, root))
+            || folder.components().any(|c| c.as_os_str().to_string_lossy().to_lowercase().starts_with("onedrive"))
+    }).map(|folder| folder.display().to_string()).collect();
+    Check::new("The game and its saves aren't in OneDrive", synced.is_empty(),
+               if synced.is_empty() { "They aren't".to_string() } else { format!("In OneDrive: {}", synced.join(", ")) },
+               "OneDrive locks files while it syncs them and can leave \"online-only\" placeholders instead of real files. \
+                Move the game out of OneDrive with Steam's \"Move install folder\", or pause syncing while installing and playing.")
+}
+
+// Controlled Folder Access is off unless Windows Security (or a group policy) says otherwise. Not a thing anywhere else.
+#[cfg(target_os = "windows")]
+fn controlled_folder_access(dir: &EldenRingDir) -> Option<Check> {
+    use winreg::{enums::HKEY_LOCAL_MACHINE, RegKey};
+
+    const KEY: &str = r"Windows Defender\Windows Defender Exploit Guard\Controlled Folder Access";
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let keys: Vec<RegKey> = [format!(r"SOFTWARE\Policies\Microsoft\{}", KEY), format!(r"SOFTWARE\Microsoft\{}", KEY)].iter()
+        .filter_map(|path| hklm.open_subkey(path).ok()).collect();
+    // 1 blocks, 2 only audits, and there are a couple of disk-only modes that don't matter here
+    let enabled = keys.iter().find_map(|key| key.get_value::<u32, _>("EnableControlledFolderAccess").ok()) == Some(1);
+    if !enabled { return None }
+
+    let mut protected: Vec<PathBuf> = [dirs::document_dir(), dirs::picture_dir(), dirs::video_dir(), dirs::audio_dir(), dirs::desktop_dir()]
+        .into_iter().flatten().collect();
+    for key in keys.iter() {
+        let Ok(folders) = key.open_subkey("ProtectedFolders") else { continue };
+        protected.extend(folders.enum_values().filter_map(|v| v.ok()).map(|(name, _)| PathBuf::from(name)));
+    }
+    let blocked: Vec<String> = game_folders(dir).into_iter().filter(|folder| protected.iter().any(|p| inside(folder, p)))
+        .map(|folder| folder.display().to_string()).collect();
+    Some(Check::new("Controlled Folder Access isn't blocking the game", blocked.is_empty(),
+                    if blocked.is_empty() { "It's on, but doesn't cover the game or its saves".to_string() }
+                    else { format!("It's on and protects {}", blocked.join(", ")) },
+                    "Windows Security blocks apps it doesn't know from writing there. In Windows Security → Virus & threat protection → \
+                     Ransomware protection → \"Allow an app through Controlled folder access\", add this manager, eldenring.exe, \
+                     and ersc_launcher.exe."))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn controlled_folder_access(_dir: &EldenRingDir) -> Option<Check> {
+    None
+}
+
+fn proxy_dll(dir: &EldenRingDir) -> Check {
+    let path = dir.path().join(PROXY_DLL);
+    Check::new("No other mod loader is in the way", !path.exists(),
+               if path.exists() { format!("Found {}, which the game loads before anything else", path.display()) } else { format!("No {}", PROXY_DLL) },
+               "It's from another mod loader or an old mod. Move it out of the game folder, or load Seamless Co-op through that loader instead.")
+}
+
+// Hand-edited settings can have lines the mod ignores (or a setting twice, where only the first counts). None until the
+// mod's installed.
+fn settings_files(dir: &EldenRingDir) -> Option<Check> {
+    let files = std::fs::read_dir(dir.mod_dir()).ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("ini")));
+    let mut problems = vec![];
+    for path in files {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        match Ini::read(&path) {
+            Ok(ini) => problems.extend(ini.diagnostics().iter().map(|d| format!("{} {}", name, d))),
+            Err(e) => problems.push(format!("{}: {}", name, e)),
+        }
+    }
+    Some(Check::new("The mod's settings files make sense", problems.is_empty(),
+                    if problems.is_empty() { "Every line is one the mod understands".to_string() } else { problems.join("\n") },
+                    "\"More Settings...\" shows the lines. Saving there drops repeated settings, anything else needs fixing in a text editor."))
+}
+
+fn game_version(exe: &Path) -> Check {
+    let version = exe_version(exe);
+    let sane = version.is_some_and(|v| v[0] > 0);
+    Check::new("eldenring.exe is a real copy of the game", sane,
+               match version {
+                   Some(v) => format!("Version {}.{}.{}.{}", v[0], v[1], v[2], v[3]),
+                   None => "Couldn't find a version in it".to_string(),
+               },
+               "It may be damaged or replaced by something else. Verify the game files in Steam (Properties → Installed Files).")
+}
+
+// VS_FIXEDFILEINFO starts with this, and the file version follows a little after. It's easier to look for it than to
+// walk the exe's resources to get there.
+const FIXED_FILE_INFO_SIGNATURE: [u8; 4] = 0xfeef04bd_u32.to_le_bytes();
+
+/// The file version from an exe's version resource.
+fn exe_version(exe: &Path) -> Option<[u16; 4]> {
+    let mut file = File::open(exe).ok()?;
+    let mut buf = vec![0; 1024*1024];
+    let mut kept = 0; // From the end of the last chunk, in case the signature was split across it
+    loop {
+        let n = file.read(&mut buf[kept..]).ok()?;
+        if n == 0 { return None }
+        let len = kept + n;
+        if let Some(at) = buf[..len].windows(4).position(|w| w == FIXED_FILE_INFO_SIGNATURE) {
+            // signature, struct version, then the file version's high and low halves
+            if at + 16 <= len {
+                let word = |offset: usize| u32::from_le_bytes(buf[at + offset..at + offset + 4].try_into().unwrap());
+                let (high, low) = (word(8), word(12));
+                return Some([(high >> 16) as u16, high as u16, (low >> 16) as u16, low as u16]);
+            }
+        }
+        kept = 16.min(len);
+        buf.copy_within(len - kept..len, 0);
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// A record of everything the manager has changed (installs, uninstalls, settings), kept forever-ish. Unlike the logs,
+// which get rotated away, this is for answering "what changed since it last worked?".
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+use slint::ComponentHandle;
+
+use crate::{error::ErscomError, paths, report::UIError, HistoryWindow};
+
+const MAX_SHOWN: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    /// Seconds since the epoch.
+    pub time: i64,
+    pub what: String,
+}
+
+pub fn path() -> PathBuf {
+    paths::data_dir().join("history.jsonl")
+}
+
+/// Add `what` to the history. Failing to doesn't stop anything, it just gets logged.
+pub fn record(what: impl Into<String>) {
+    let event = Event { time: chrono::Local::now().timestamp(), what: what.into() };
+    append(&event).try_log(&format!("recording \"{}\" in the history", event.what))?;
+}
+
+/// Record a change to the mod's settings. Passwords aren't written down.
+pub fn record_setting(section: &str, key: &str, old: Option<&str>, new: &str) {
+    if key.eq_ignore_ascii_case("cooppassword") {
+        return record("Changed the co-op password");
+    }
+    match old {
+        Some(old) => record(format!("Changed [{}] {} from \"{}\" to \"{}\"", section, key, old, new)),
+        None => record(format!("Set [{}] {} to \"{}\"", section, key, new)),
+    }
+}
+
+// One json object per line, so it can be appended to without reading it all in
+fn append(event: &Event) -> Result<(), ErscomError> {
+    let path = path();
+    std::fs::create_dir_all(paths::data_dir()).map_err(ErscomError::io(&paths::data_dir()))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(&path).map_err(ErscomError::io(&path))?;
+    let mut line = serde_json::to_string(event).map_err(ErscomError::json(&path))?;
+    line.push('\n');
+    file.write_all(line.as_bytes()).map_err(ErscomError::io(&path))
+}
+
+/// Everything in the history, oldest first. Lines that don't parse are skipped.
+pub fn load() -> Result<Vec<Event>, ErscomError> {
+    let path = path();
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => Err(ErscomError::io(&path)(e))?,
+    };
+    let mut events = vec![];
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line.map_err(ErscomError::io(&path))?;
+        match serde_json::from_str(&line) {
+            Ok(event) => events.push(event),
+            Err(e) => tracing::warn!("Skipping bad line in {}: {}", path.display(), e),
+        }
+    }
+    Ok(events)
+}
+
+// Newest first, since that's what's usually wanted
+fn history_text() -> String {
+    let events = match load() {
+        Ok(events) => events,
+        Err(e) => return format!("Couldn't read the history: {}", e),
+    };
+    if events.is_empty() { return "Nothing yet".to_string() }
+    events.iter().rev().take(MAX_SHOWN).map(|e| {
+        let when = chrono::DateTime::from_timestamp(e.time, 0).map(|t| crate::status::date_time(t.with_timezone(&chrono::Local))).unwrap_or_default();
+        format!("{}  {}", when, e.what)
+    }).collect::<Vec<_>>().join("\n")
+}
+
+pub fn show_history_window() {
+    let win = HistoryWindow::new().try_error()?;
+    win.set_theme(crate::config::get().theme.color_theme());
+    win.set_high_contrast(crate::config::get().high_contrast);
+    win.set_history_text(history_text().into());
+    win.on_copy({
+        let weak_win = win.as_weak();
+        move || {
+            let text = weak_win.unwrap().get_history_text();
+            arboard::Clipboard::new().and_then(|mut c| c.set_text(text.as_str())).try_warn()?;
+        }
+    });
+    win.on_close({
+        let weak_win = win.as_weak();
+        move || {
+            weak_win.unwrap().hide().try_log("closing history window")?;
+        }
+    });
+    win.show().try_log("showing history window")?;
+}
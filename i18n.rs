@@ -0,0 +1,53 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use tracing::{info, warn};
+
+/// The languages we have translations for (in `translations/<code>/LC_MESSAGES/`), as `(code, name)`. The names are
+/// in their own language so people can find theirs. English is what's in erscom.slint so it doesn't need a catalog.
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("es", "Español"),
+];
+
+/// The OS's language, if we have a translation for it.
+pub fn detect() -> Option<&'static str> {
+    let locale = sys_locale::get_locale()?;
+    let base = locale.split(['-', '_', '@']).next().unwrap_or(&locale);
+    LANGUAGES.iter().map(|(code, _)| *code).find(|code| *code == locale || *code == base)
+}
+
+/// The language the UI is in: the one picked in the preferences, or the OS's.
+pub fn current() -> String {
+    crate::config::get().language.or(detect().map(str::to_string)).unwrap_or("en".to_string())
+}
+
+/// How dates (without times) are written in `language`, as a chrono format and the locale for the month names.
+pub fn date_format(language: &str) -> (&'static str, chrono::Locale) {
+    match language {
+        "es" => ("%-d de %B de %Y", chrono::Locale::es_ES),
+        _    => ("%B %-d, %Y", chrono::Locale::en_US),
+    }
+}
+
+/// Switch the UI to `language` (a code from [LANGUAGES]), or to the OS's language if `None`. Slint only allows this
+/// once a component exists.
+pub fn apply(language: Option<&str>) {
+    let language = language.or(detect()).unwrap_or("en");
+    info!("Using language {}", language);
+    if let Err(e) = slint::select_bundled_translation(language) {
+        warn!("Couldn't switch to language {}: {:?}", language, e);
+    }
+}
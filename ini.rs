@@ -13,111 +13,247 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::error::Error;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::error::ErscomError;
 
 #[derive(Debug, Clone)]
 pub struct Ini {
-    section: Vec<Section>
+    section: Vec<Section>,
+    newline: &'static str, // Whatever the file had, so Windows files stay Windows files
+    final_newline: bool, // Does the last line end with one? Editors don't all agree on that.
+    diagnostics: Vec<Diagnostic>, // From when it was read
 }
 
 #[derive(Debug, Clone)]
 pub struct Section {
     name: String,
+    raw: Option<String>, // The header line as it was read, if it was
     entry: Vec<Entry>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Entry {
-    KV { key: String, value: String },
+    // `raw` is the line as it was read, so writing it back out doesn't change its spacing. None once it's been set().
+    KV { key: String, value: String, raw: Option<String> },
     Comment(String), // Includes comment character itself
-    Blank,
+    Blank(String), // Whatever whitespace was on the line
+}
+
+/// A line that probably isn't doing what whoever wrote it wanted (see [Ini::diagnostics()]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize, // Counting from 1, like editors do
+    pub text: String,
+    pub reason: String,
+}
+
+// Just the line number and reason. The text could have the password in it, and this is what goes in the log.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
 }
 
 impl Ini {
-    pub fn read(file: &std::path::Path) -> Result<Ini, Box<dyn Error>> {
-        use std::io::BufRead;
-        let file = std::fs::File::open(file)?;
-        let mut ini = Ini { section: vec![Section { name: "".to_string(), entry: Vec::new()}], };
+    pub fn read(path: &std::path::Path) -> Result<Ini, ErscomError> {
+        let text = std::fs::read_to_string(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidData => ErscomError::IniParse { path: path.to_path_buf(), message: format!("{}", e) },
+            _ => ErscomError::io(path)(e),
+        })?;
+        let ini = Ini::parse(&text);
+        for diagnostic in ini.diagnostics() {
+            tracing::warn!("{}: {}", path.display(), diagnostic);
+        }
+        Ok(ini)
+    }
+
+    /// Like [Ini::read()], but from a string (an [Ini::export()] from somewhere else, say). Also there as
+    /// `text.parse::<Ini>()`.
+    pub fn parse(text: &str) -> Ini {
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        let final_newline = text.is_empty() || text.ends_with('\n');
+        let mut ini = Ini { section: vec![Section { name: "".to_string(), raw: None, entry: Vec::new()}], newline, final_newline, diagnostics: vec![] };
         let mut section = &mut ini.section[0];
+        // Where each section and setting first showed up, for pointing out the duplicates
+        let mut sections: HashMap<String, usize> = HashMap::new();
+        let mut keys: HashMap<(String, String), usize> = HashMap::new();
+        let mut diagnostics = vec![];
 
         let section_re = regex::Regex::new(r"^\s*\[([^]]+)\]\s*$").unwrap();
         let kv_re      = regex::Regex::new(r"^\s*([^=]+)\s*=\s*(.*)$").unwrap();
         let blank_re   = regex::Regex::new(r"^\s*$").unwrap();
         let comment_re = regex::Regex::new(r"^\s*(?:;.*)$").unwrap();
-        for line in std::io::BufReader::new(file).lines() {
-            let line = line?;
-            if blank_re.is_match(&line) {
-                section.entry.push(Entry::Blank);
-            } else if comment_re.is_match(&line) {
+        for (number, line) in text.lines().enumerate().map(|(i, line)| (i + 1, line)) {
+            let mut problem = |reason: String| diagnostics.push(Diagnostic { line: number, text: line.to_string(), reason });
+            if blank_re.is_match(line) {
+                section.entry.push(Entry::Blank(line.to_string()));
+            } else if comment_re.is_match(line) {
                 section.entry.push(Entry::Comment(line.to_string()));
-            } else if let Some(caps) = section_re.captures(&line) {
-                ini.section.push(Section { name: caps.get(1).unwrap().as_str().trim().to_string(),
-                                           entry: Vec::new() });
+            } else if let Some(caps) = section_re.captures(line) {
+                let name = caps.get(1).unwrap().as_str().trim().to_string();
+                match sections.get(&name) {
+                    Some(first) => problem(format!("[{}] already started on line {}. The settings after this go with that one.", name, first)),
+                    None => { sections.insert(name.clone(), number); },
+                }
+                ini.section.push(Section { name, raw: Some(line.to_string()), entry: Vec::new() });
                 section = ini.section.last_mut().unwrap();
-            } else if let Some(caps) = kv_re.captures(&line) {
-                section.entry.push(Entry::KV { key:   caps.get(1).unwrap().as_str().trim().to_string(),
-                                               value: caps.get(2).unwrap().as_str().trim().to_string(), });
+            } else if let Some(caps) = kv_re.captures(line) {
+                let key = caps.get(1).unwrap().as_str().trim().to_string();
+                if key.is_empty() {
+                    problem("There's no setting name before the =".to_string());
+                } else if let Some(first) = keys.get(&(section.name.clone(), key.clone())) {
+                    problem(format!("{} is already set on line {}, and only that one counts", key, first));
+                } else {
+                    keys.insert((section.name.clone(), key.clone()), number);
+                }
+                section.entry.push(Entry::KV { key,
+                                               value: caps.get(2).unwrap().as_str().trim().to_string(),
+                                               raw:   Some(line.to_string()), });
             } else {
-                section.entry.push(Entry::Comment(line));
+                // Kept so it's written back out, but nothing reads it
+                problem("Isn't a setting, a [section] or a ;comment, so it's ignored".to_string());
+                section.entry.push(Entry::Comment(line.to_string()));
             }
         }
-        Ok(ini)
+        ini.diagnostics = diagnostics;
+        ini
+    }
+
+    /// Write it back out, without any duplicates (see [Ini::without_duplicates()]). Everything else comes out just
+    /// like it went in.
+    pub fn write(&self, path: &std::path::Path) -> Result<(), ErscomError> {
+        tracing::info!("Writing {}", path.display());
+        std::fs::write(path, self.without_duplicates().to_string()).map_err(ErscomError::io(path))
     }
 
-    pub fn write(&self, file: &std::path::Path) -> Result<(), Box<dyn Error>> {
-        use std::io::Write;
-        let mut file = std::fs::File::create(file)?;
+    /// The same file with each section only once (where it first appeared, with the settings from any repeats moved
+    /// up into it) and each key only once per section. The first of a key is the one that's kept, since that's the
+    /// one [Ini::get()] (and the mod) sees, so no setting's value changes.
+    pub fn without_duplicates(&self) -> Ini {
+        let mut sections: Vec<Section> = vec![];
         for s in &self.section {
-            if s.name != "" {
-                file.write_fmt(format_args!("[{}]\n", s.name))?;
-            }
-            for e in &s.entry {
-                match e {
-                    Entry::KV { key: k, value: v } => { file.write_fmt(format_args!("{} = {}\n", k, v))?; }
-                    Entry::Comment(line)           => { file.write_fmt(format_args!("{}\n", line))?; }
-                    Entry::Blank                   => { file.write_fmt(format_args!("\n"))?; }
-                }
+            match sections.iter_mut().find(|t| t.name == s.name) {
+                Some(first) => first.entry.extend(s.entry.iter().cloned()),
+                None => sections.push(s.clone()),
             }
         }
-        Ok(())
+        for s in sections.iter_mut() {
+            let mut keys = HashSet::new();
+            s.entry.retain(|e| match e {
+                Entry::KV { key, .. } => keys.insert(key.clone()),
+                _ => true,
+            });
+        }
+        Ini { section: sections, ..self.clone() }
     }
 
+    /// The value of `key` in `section`. If it's in there more than once, the first one wins (like it does for the mod).
     pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.get_all(section, key).into_iter().next()
+    }
+
+    /// Every value `key` has in `section` (including from other sections with the same name), in file order.
+    pub fn get_all(&self, section: &str, key: &str) -> Vec<&str> {
+        self.section.iter().filter(|s| s.name == section)
+            .flat_map(|s| s.entry.iter())
+            .filter_map(|e| match e { Entry::KV { key: k, value: v, .. } if key == k => Some(v.as_str()), _ => None })
+            .collect()
+    }
+
+    /// Change `key` in `section` (or add it). Any duplicates of it get removed, so the value can't be contradicted.
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        let value = value.trim();
+        let mut found = false;
+        for s in self.section.iter_mut().filter(|s| s.name == section) {
+            s.entry.retain_mut(|e| match e {
+                Entry::KV { key: k, value: v, raw } if key == k => {
+                    if found { return false }
+                    found = true;
+                    if v != value { *v = value.to_string(); *raw = None } // Otherwise leave the line alone
+                    true
+                },
+                _ => true,
+            });
+        }
+        if found { return }
+        let new = Entry::KV { key:   key.trim().to_string(),
+                              value: value.to_string(),
+                              raw:   None, };
+        match self.section.iter_mut().find(|s| s.name == section) {
+            // No existing entry, append it to section
+            Some(s) => s.entry.push(new),
+            // No existing section, append it to file and add entry
+            None => self.section.push(Section { name: section.trim().to_string(),
+                                                raw: None,
+                                                entry: vec![new] }),
+        }
+    }
+
+    /// Lines that couldn't be made sense of, or that repeat a section or setting, as the file was when it was read.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    // Each setting once (the one that counts), grouped by section, in file order.
+    fn settings(&self) -> Vec<(&str, Vec<(&str, &str)>)> {
+        let mut settings: Vec<(&str, Vec<(&str, &str)>)> = vec![];
         for s in &self.section {
-            if s.name == section {
-                for e in &s.entry {
-                    match e {
-                        Entry::KV { key: k, value: v } if key == k => { return Some(&v) }
-                        _ => {}
-                    }
-                }
+            let index = match settings.iter().position(|(name, _)| *name == s.name) {
+                Some(index) => index,
+                None => { settings.push((s.name.as_str(), vec![])); settings.len() - 1 },
+            };
+            for e in &s.entry {
+                let Entry::KV { key, value, .. } = e else { continue };
+                let kvs = &mut settings[index].1;
+                if !kvs.iter().any(|(k, _)| k == key) { kvs.push((key.as_str(), value.as_str())) }
             }
         }
-        None
+        settings
     }
 
-    pub fn set(&mut self, section: &str, key: &str, value: &str) {
-        let new = Entry::KV { key:   key.trim().to_string(),
-                              value: value.trim().to_string(), };
-        for s in &mut self.section {
-            if s.name == section {
-                for e in &mut s.entry {
-                    match e {
-                        Entry::KV { key: k, value: _ } if key == k => {
-                            *e = new;
-                            return;
-                        }
-                        _ => {}
-                    }
+    /// Just the settings (no comments or blank lines), in ini format. Compact enough to pass around.
+    pub fn export(&self) -> String {
+        let mut out = String::new();
+        for (section, kvs) in self.settings() {
+            if kvs.is_empty() { continue }
+            if !section.is_empty() {
+                out.push_str(&format!("[{}]\n", section));
+            }
+            for (k, v) in kvs {
+                out.push_str(&format!("{} = {}\n", k, v));
+            }
+        }
+        out
+    }
+
+    /// Take every setting from `other`, leaving the ones it doesn't mention (and all our comments) alone.
+    pub fn merge(&mut self, other: &Ini) {
+        for (section, kvs) in other.settings() {
+            for (key, value) in kvs {
+                self.set(section, key, value);
+            }
+        }
+    }
+
+    /// The settings that are different in `newer`, as (section, key, old value, new value).
+    pub fn changes<'a>(&'a self, newer: &'a Ini) -> Vec<(&'a str, &'a str, Option<&'a str>, &'a str)> {
+        let mut changes = vec![];
+        for (section, kvs) in newer.settings() {
+            for (key, value) in kvs {
+                let old = self.get(section, key);
+                if old != Some(value) {
+                    changes.push((section, key, old, value));
                 }
-                // No existing entry, append it to section
-                s.entry.push(new);
-                return;
             }
         }
-        // No existing section, append it to file and add entry
-        self.section.push(Section { name: section.trim().to_string(),
-                                    entry: vec![new] });
+        changes
+    }
+
+    /// The same settings with the same values? Comments and blank lines don't count.
+    pub fn same_settings(&self, other: &Ini) -> bool {
+        self.changes(other).is_empty() && other.changes(self).is_empty()
     }
 
     pub fn sections(&self) -> impl Iterator<Item=&Section> {
@@ -125,6 +261,43 @@ impl Ini {
     }
 }
 
+impl std::str::FromStr for Ini {
+    type Err = std::convert::Infallible;
+    fn from_str(text: &str) -> Result<Ini, Self::Err> {
+        Ok(Ini::parse(text))
+    }
+}
+
+/// The whole file. Untouched lines come out just like they went in and where they were, duplicates and all, so
+/// `text.parse::<Ini>()?.to_string()` gives back `text`. [Ini::write()] gets rid of the duplicates first.
+impl fmt::Display for Ini {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Each line is written with the newline that goes between it and the one before, so the last one can go without
+        let mut nl = "";
+        let mut line = |f: &mut fmt::Formatter<'_>, args: fmt::Arguments| -> fmt::Result {
+            write!(f, "{}{}", nl, args)?;
+            nl = self.newline;
+            Ok(())
+        };
+        for s in &self.section {
+            match s.raw {
+                Some(ref raw) => line(f, format_args!("{}", raw))?,
+                None if !s.name.is_empty() => line(f, format_args!("[{}]", s.name))?,
+                None => {}
+            }
+            for e in &s.entry {
+                match e {
+                    Entry::KV { raw: Some(raw), .. }          => line(f, format_args!("{}", raw))?,
+                    Entry::KV { key: k, value: v, raw: None } => line(f, format_args!("{} = {}", k, v))?,
+                    Entry::Comment(raw) | Entry::Blank(raw)   => line(f, format_args!("{}", raw))?,
+                }
+            }
+        }
+        if self.final_newline && !nl.is_empty() { write!(f, "{}", nl)? }
+        Ok(())
+    }
+}
+
 impl Section {
     pub fn entries(&self) -> impl Iterator<Item=&Entry> {
         self.entry.iter()
@@ -133,3 +306,68 @@ impl Section {
         self.name.as_str()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untouched_files_round_trip() {
+        for text in ["[GAMEPLAY]\nallow_invaders = 1\n   \n\t\n; comment\ndeath_debuffs=0",
+                     "[PASSWORD]\r\ncooppassword = abc\r\n  \r\n",
+                     "",
+                     "\n\n",
+                     "no_section = 1"] {
+            assert_eq!(Ini::parse(text).to_string(), text);
+        }
+    }
+
+    #[test]
+    fn duplicates_round_trip_until_set() {
+        let text = "[GAMEPLAY]\na = 1\na = 2\n[PASSWORD]\ncooppassword = x\n[GAMEPLAY]\nb = 3\na = 4\n";
+        let mut ini: Ini = text.parse().unwrap();
+        assert_eq!(ini.to_string(), text);
+        assert_eq!(ini.get("GAMEPLAY", "a"), Some("1"));
+        ini.set("GAMEPLAY", "a", "5");
+        assert_eq!(ini.to_string(), "[GAMEPLAY]\na = 5\n[PASSWORD]\ncooppassword = x\n[GAMEPLAY]\nb = 3\n");
+    }
+
+    #[test]
+    fn get_all_sees_every_copy() {
+        let ini = Ini::parse("[GAMEPLAY]\na = 1\na = 2\n[PASSWORD]\na = x\n[GAMEPLAY]\na = 4\n");
+        assert_eq!(ini.get_all("GAMEPLAY", "a"), vec!["1", "2", "4"]);
+        assert_eq!(ini.get_all("PASSWORD", "a"), vec!["x"]);
+        assert!(ini.get_all("GAMEPLAY", "b").is_empty());
+    }
+
+    #[test]
+    fn writing_collapses_duplicates() {
+        let ini = Ini::parse("; top\n[GAMEPLAY]\na = 1\n  a=2\n[PASSWORD]\ncooppassword = x\n[GAMEPLAY]\nb = 3\na = 4\n");
+        let written = ini.without_duplicates();
+        assert_eq!(written.to_string(), "; top\n[GAMEPLAY]\na = 1\nb = 3\n[PASSWORD]\ncooppassword = x\n");
+        assert!(ini.same_settings(&written));
+    }
+
+    #[test]
+    fn diagnostics_point_at_the_lines() {
+        let ini = Ini::parse("[GAMEPLAY]\na = 1\nnot a setting\n = 2\na = 3\n[GAMEPLAY]\n");
+        let found: Vec<(usize, &str)> = ini.diagnostics().iter().map(|d| (d.line, d.text.as_str())).collect();
+        assert_eq!(found, vec![(3, "not a setting"), (4, " = 2"), (5, "a = 3"), (6, "[GAMEPLAY]")]);
+        let reasons: Vec<&str> = ini.diagnostics().iter().map(|d| d.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["Isn't a setting, a [section] or a ;comment, so it's ignored",
+                                 "There's no setting name before the =",
+                                 "a is already set on line 2, and only that one counts",
+                                 "[GAMEPLAY] already started on line 1. The settings after this go with that one."]);
+        assert_eq!(ini.diagnostics()[2].to_string(), "line 5: a is already set on line 2, and only that one counts");
+        assert!(Ini::parse("[GAMEPLAY]\na = 1\n").diagnostics().is_empty());
+    }
+
+    #[test]
+    fn set_only_changes_its_line() {
+        let mut ini = Ini::parse("[GAMEPLAY]\n  allow_invaders=1  \n \nskip_splash_screens = 0");
+        ini.set("GAMEPLAY", "skip_splash_screens", "1");
+        ini.set("GAMEPLAY", "allow_invaders", "1");
+        ini.set("PASSWORD", "cooppassword", "abc");
+        assert_eq!(ini.to_string(), "[GAMEPLAY]\n  allow_invaders=1  \n \nskip_splash_screens = 1\n[PASSWORD]\ncooppassword = abc");
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Only one copy of the GUI gets to run at a time, otherwise they trip over each other in the cache and the mod's ini.
+// A second copy hands its command line to the first (which comes to the front) and exits.
+//
+// On Windows a named mutex says whether we're first, and the first copy listens on a localhost port (written to
+// `instance-port` in the data dir) for the others. Everywhere else a unix socket in the data dir does both jobs.
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use tracing::{debug, info, warn};
+
+use crate::error::ErscomError;
+use crate::paths;
+
+/// Proof that we're the only copy running. Keep it around until exiting.
+pub struct Instance {
+    #[cfg(target_os = "windows")]
+    _mutex: windows::Mutex,
+    #[cfg(target_os = "windows")]
+    listener: std::net::TcpListener,
+    #[cfg(not(target_os = "windows"))]
+    listener: std::os::unix::net::UnixListener,
+}
+
+/// Become the one running copy, or, if there's already one, pass it `args` and return `None`.
+pub fn claim(args: &[String]) -> Result<Option<Instance>, ErscomError> {
+    let dir = paths::data_dir();
+    std::fs::create_dir_all(&dir).map_err(ErscomError::io(&dir))?;
+    imp::claim(args)
+}
+
+impl Instance {
+    /// Call `on_args` (from another thread) with the command line of each copy that gets started after us.
+    pub fn listen(&self, on_args: impl Fn(Vec<String>) + Send + 'static) -> Result<(), ErscomError> {
+        let listener = self.listener.try_clone().map_err(ErscomError::io(&socket_path()))?;
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream.map_err(|e| e.to_string()).and_then(|s| receive(s).map_err(|e| e.to_string())) {
+                    Ok(args) => { info!("Another copy was started with {:?}", args); on_args(args) },
+                    Err(e) => warn!("Bad message from another copy: {}", e),
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+fn socket_path() -> PathBuf {
+    #[cfg(target_os = "windows")] { paths::data_dir().join("instance-port") }
+    #[cfg(not(target_os = "windows"))] { paths::data_dir().join("instance.sock") }
+}
+
+fn send(mut stream: impl Write, args: &[String]) -> Result<(), ErscomError> {
+    let path = socket_path();
+    serde_json::to_writer(&mut stream, args).map_err(ErscomError::json(&path))?;
+    stream.flush().map_err(ErscomError::io(&path))?;
+    Ok(())
+}
+
+fn receive(stream: impl Read) -> Result<Vec<String>, ErscomError> {
+    serde_json::from_reader(stream).map_err(ErscomError::json(&socket_path()))
+}
+
+#[cfg(not(target_os = "windows"))]
+mod imp {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    use super::*;
+
+    pub fn claim(args: &[String]) -> Result<Option<Instance>, ErscomError> {
+        let path = socket_path();
+        match UnixListener::bind(&path) {
+            Ok(listener) => return Ok(Some(Instance { listener })),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {},
+            Err(e) => Err(ErscomError::io(&path)(e))?,
+        }
+        match UnixStream::connect(&path) {
+            Ok(stream) => {
+                debug!("Handing {:?} to the copy that's already running", args);
+                send(stream, args)?;
+                Ok(None)
+            },
+            Err(_) => {
+                // Nobody's listening, it's left over from a crash
+                debug!("Removing stale {}", path.display());
+                std::fs::remove_file(&path).map_err(ErscomError::io(&path))?;
+                Ok(Some(Instance { listener: UnixListener::bind(&path).map_err(ErscomError::io(&path))? }))
+            },
+        }
+    }
+
+    impl Drop for Instance {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(socket_path());
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod imp {
+    use std::net::{Ipv4Addr, TcpListener, TcpStream};
+
+    use super::*;
+
+    pub fn claim(args: &[String]) -> Result<Option<Instance>, ErscomError> {
+        let path = socket_path();
+        match windows::Mutex::create("Local\\erscom-single-instance") {
+            Some(mutex) => {
+                let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).map_err(ErscomError::io(&path))?;
+                let port = listener.local_addr().map_err(ErscomError::io(&path))?.port();
+                std::fs::write(&path, port.to_string()).map_err(ErscomError::io(&path))?;
+                Ok(Some(Instance { _mutex: mutex, listener }))
+            },
+            None => {
+                // The first copy might not have gotten around to writing its port yet
+                for _ in 0..20 {
+                    if let Some(stream) = std::fs::read_to_string(&path).ok()
+                        .and_then(|port| port.trim().parse::<u16>().ok())
+                        .and_then(|port| TcpStream::connect((Ipv4Addr::LOCALHOST, port)).ok()) {
+                        debug!("Handing {:?} to the copy that's already running", args);
+                        send(stream, args)?;
+                        return Ok(None);
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                }
+                Err(ErscomError::Other(format!("Another copy of the manager is running but isn't answering")))
+            },
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+
+    extern "system" {
+        fn CreateMutexW(attributes: *const c_void, initial_owner: i32, name: *const u16) -> *mut c_void;
+        fn GetLastError() -> u32;
+        fn CloseHandle(handle: *mut c_void) -> i32;
+    }
+    const ERROR_ALREADY_EXISTS: u32 = 183;
+
+    pub struct Mutex(*mut c_void);
+
+    impl Mutex {
+        /// `None` if somebody else already has it.
+        pub fn create(name: &str) -> Option<Mutex> {
+            let name: Vec<u16> = std::ffi::OsStr::new(name).encode_wide().chain(std::iter::once(0)).collect();
+            let handle = unsafe { CreateMutexW(std::ptr::null(), 0, name.as_ptr()) };
+            let exists = unsafe { GetLastError() } == ERROR_ALREADY_EXISTS;
+            if handle.is_null() { return None }
+            if exists { unsafe { CloseHandle(handle); } return None }
+            Some(Mutex(handle))
+        }
+    }
+
+    impl Drop for Mutex {
+        fn drop(&mut self) {
+            unsafe { CloseHandle(self.0); }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// A way for other programs (stream decks, overlays, scripts) to drive the GUI while it's running. It's off unless
+// turned on in Preferences.
+//
+// It's set up like [crate::instance]: on Windows it listens on a localhost port (written to `control-port` in the
+// data dir), everywhere else on a unix socket (`control.sock`). Each line sent is a JSON request and gets one line of
+// JSON back:
+//
+//     {"token": "...", "command": "list"}                     → {"ok": true, "result": [...]}
+//     {"token": "...", "command": "install", "tag": "v1.8.0"} → {"ok": false, "error": "..."}
+//
+// The commands are `list`, `status`, `install` and `launch`. Anything on the machine can connect, so every request
+// needs the token from `control-token` in the data dir, which only the user can read.
+
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::{config, error::ErscomError, paths};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Command {
+    /// The releases, newest first, and which one's installed.
+    List,
+    /// What's installed, whether the game's running, and what the manager's doing.
+    Status,
+    /// Install a release, just like picking it in the window (so it waits if the game's running).
+    Install { tag: String },
+    /// Same as the Launch button.
+    Launch,
+}
+
+#[derive(Deserialize)]
+struct Request {
+    token: String,
+    #[serde(flatten)]
+    command: Command,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Reply {
+    Ok { ok: bool, result: serde_json::Value },
+    Err { ok: bool, error: String },
+}
+
+impl From<Result<serde_json::Value, String>> for Reply {
+    fn from(result: Result<serde_json::Value, String>) -> Reply {
+        match result {
+            Ok(result) => Reply::Ok { ok: true, result },
+            Err(error) => Reply::Err { ok: false, error },
+        }
+    }
+}
+
+type Handler = Rc<dyn Fn(Command) -> Result<serde_json::Value, String>>;
+
+thread_local! {
+    // Only ever touched on the GUI thread, since that's where everything the commands need lives
+    static HANDLER: RefCell<Option<Handler>> = const { RefCell::new(None) };
+}
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+// Each connection gets a thread, and they come in before we know if they have the token. A companion tool only needs
+// one or two, so anything past this is turned away.
+const MAX_CONNECTIONS: usize = 8;
+static CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+// ...and they don't get to keep one by going quiet or by never finishing a line.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+const MAX_LINE: u64 = 64 * 1024;
+
+// Read once when we start listening. Requests are checked against this, not the file.
+static TOKEN: OnceLock<String> = OnceLock::new();
+
+/// Run each command that comes in with `handler` (on the GUI thread, so call this from there). Starts listening
+/// right away if it's turned on in the config, otherwise not until [start()] is called.
+pub fn serve(handler: impl Fn(Command) -> Result<serde_json::Value, String> + 'static) {
+    HANDLER.with(|h| *h.borrow_mut() = Some(Rc::new(handler)));
+    if config::get().control_server {
+        start().unwrap_or_else(|e| warn!("Couldn't start the control server: {}", e));
+    }
+}
+
+/// Start listening, if we aren't already. There's no stopping it once it's going, but it turns away everything once
+/// it's turned off in the config.
+pub fn start() -> Result<(), ErscomError> {
+    if STARTED.swap(true, Ordering::SeqCst) { return Ok(()) }
+    let started = imp::listen().and_then(|listener| { let token = token()?; TOKEN.get_or_init(|| token); Ok(listener) });
+    let listener = match started {
+        Ok(listener) => listener,
+        Err(e) => { STARTED.store(false, Ordering::SeqCst); return Err(e) },
+    };
+    info!("Listening for companion tools on {}", socket_path().display());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if CONNECTIONS.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                        CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+                        warn!("Turned away a companion tool, there are already {} connected", MAX_CONNECTIONS);
+                        continue
+                    }
+                    std::thread::spawn(move || {
+                        connection(stream);
+                        CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+                    });
+                },
+                Err(e) => warn!("Bad connection from a companion tool: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Where companion tools look for the token.
+pub fn token_path() -> PathBuf {
+    paths::data_dir().join("control-token")
+}
+
+/// What companion tools have to send with every request. It's kept between runs so they don't have to be set up again
+/// every time the manager starts.
+pub fn token() -> Result<String, ErscomError> {
+    let path = token_path();
+    if let Ok(token) = std::fs::read_to_string(&path) {
+        if !token.trim().is_empty() { return Ok(token.trim().to_string()) }
+    }
+    let mut random = [0u8; 32];
+    getrandom::getrandom(&mut random).map_err(|e| ErscomError::Other(format!("Couldn't make a control token: {}", e)))?;
+    let token: String = random.iter().map(|b| format!("{:02x}", b)).collect();
+    imp::create_private(&path)?.write_all(token.as_bytes()).map_err(ErscomError::io(&path))?;
+    info!("Made a new control token in {}", path.display());
+    Ok(token)
+}
+
+fn socket_path() -> PathBuf {
+    #[cfg(target_os = "windows")] { paths::data_dir().join("control-port") }
+    #[cfg(not(target_os = "windows"))] { paths::data_dir().join("control.sock") }
+}
+
+fn connection(stream: imp::Stream) {
+    let mut writer = match stream.set_read_timeout(Some(IDLE_TIMEOUT)).and_then(|_| stream.try_clone()) {
+        Ok(writer) => writer,
+        Err(e) => return warn!("Couldn't talk to a companion tool: {}", e),
+    };
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        match reader.by_ref().take(MAX_LINE).read_line(&mut line) {
+            Ok(0) => return,
+            Ok(_) if !line.ends_with('\n') && line.len() as u64 >= MAX_LINE =>
+                return warn!("Hung up on a companion tool that sent a line over {} bytes", MAX_LINE),
+            Ok(_) => {},
+            Err(e) => return debug!("Companion tool went away: {}", e),
+        }
+        if line.trim().is_empty() { continue }
+        let reply = Reply::from(request(&line));
+        let sent = serde_json::to_writer(&mut writer, &reply).map_err(|e| e.to_string())
+            .and_then(|_| writer.write_all(b"\n").and_then(|_| writer.flush()).map_err(|e| e.to_string()));
+        if let Err(e) = sent { return debug!("Companion tool went away: {}", e) }
+    }
+}
+
+fn request(line: &str) -> Result<serde_json::Value, String> {
+    if !config::get().control_server { Err("Control from other programs is turned off in the manager's preferences")? }
+    let request: Request = serde_json::from_str(line).map_err(|e| format!("Bad request: {}", e))?;
+    let token = TOKEN.get().ok_or("The control server isn't running")?;
+    if !same_token(request.token.as_bytes(), token.as_bytes()) {
+        warn!("Turned away a companion tool with the wrong token");
+        Err("Wrong token")?
+    }
+    debug!("Companion tool sent {:?}", request.command);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slint::invoke_from_event_loop(move || {
+        let reply = HANDLER.with(|h| h.borrow().clone())
+            .map(|handler| handler(request.command))
+            .unwrap_or_else(|| Err("The manager isn't ready yet".to_string()));
+        let _ = tx.send(reply);
+    }).map_err(|e| e.to_string())?;
+    rx.recv().map_err(|_| "The manager is exiting".to_string())?
+}
+
+/// Compares every byte no matter where the first difference is, so how long it takes doesn't give the token away.
+fn same_token(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(not(target_os = "windows"))]
+mod imp {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    use std::os::unix::net::UnixListener;
+    use std::path::Path;
+
+    use super::*;
+
+    pub type Stream = std::os::unix::net::UnixStream;
+
+    pub fn listen() -> Result<UnixListener, ErscomError> {
+        let path = socket_path();
+        // [crate::instance] makes sure we're the only copy, so anything here is left over from a crash
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(ErscomError::io(&path))?;
+        }
+        let listener = UnixListener::bind(&path).map_err(ErscomError::io(&path))?;
+        private(&path)?;
+        Ok(listener)
+    }
+
+    /// Only the user gets to read it.
+    pub fn private(path: &Path) -> Result<(), ErscomError> {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).map_err(ErscomError::io(path))
+    }
+
+    /// Create (or empty out) `path` so that only the user can ever read it.
+    pub fn create_private(path: &Path) -> Result<std::fs::File, ErscomError> {
+        let file = std::fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path).map_err(ErscomError::io(path))?;
+        private(path)?; // mode() only counts if it didn't already exist
+        Ok(file)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod imp {
+    use std::net::{Ipv4Addr, TcpListener};
+    use std::path::Path;
+
+    use super::*;
+
+    pub type Stream = std::net::TcpStream;
+
+    pub fn listen() -> Result<TcpListener, ErscomError> {
+        let path = socket_path();
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).map_err(ErscomError::io(&path))?;
+        let port = listener.local_addr().map_err(ErscomError::io(&path))?.port();
+        std::fs::write(&path, port.to_string()).map_err(ErscomError::io(&path))?;
+        Ok(listener)
+    }
+
+    /// Create (or empty out) `path`. It takes on the data dir's permissions, which (under `%LOCALAPPDATA%`) only let
+    /// the user read it. In portable mode it's only as private as wherever the exe is.
+    pub fn create_private(path: &Path) -> Result<std::fs::File, ErscomError> {
+        std::fs::File::create(path).map_err(ErscomError::io(path))
+    }
+}
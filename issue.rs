@@ -0,0 +1,75 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::Mutex;
+
+const NEW_ISSUE_URL: &str = "https://github.com/caldwell/erscom/issues/new";
+
+// Keep the url under the length browsers (and github) are happy with
+const MAX_ERROR_LEN: usize = 4000;
+
+// What we know about the install, for bug reports. Updated whenever the UI refreshes.
+#[derive(Debug, Clone, Default)]
+struct Context {
+    install_path: Option<String>,
+    mod_version: Option<String>,
+}
+
+static CONTEXT: Mutex<Context> = Mutex::new(Context { install_path: None, mod_version: None });
+
+pub fn set_context(install_path: Option<String>, mod_version: Option<String>) {
+    *CONTEXT.lock().unwrap() = Context { install_path, mod_version };
+}
+
+/// The issue body exactly as it will be sent, so it can be shown to the user first.
+pub fn body(error: &str) -> String {
+    let context = CONTEXT.lock().unwrap().clone();
+    let error = match error.char_indices().nth(MAX_ERROR_LEN) {
+        Some((i, _)) => format!("{}…", &error[..i]),
+        None => error.to_string(),
+    };
+    format!("### Error\n```\n{}\n```\n\n\
+             ### Details\n\
+             - Manager version: {}\n\
+             - OS: {}\n\
+             - Install path: {}\n\
+             - Installed mod version: {}\n\n\
+             ### What were you doing when this happened?\n\n",
+            error,
+            option_env!("VERSION").unwrap_or("0.0.0-local"),
+            os_description(),
+            context.install_path.as_deref().unwrap_or("<Not Found>"),
+            context.mod_version.as_deref().unwrap_or("<Unknown>"))
+}
+
+pub fn url(error: &str) -> String {
+    let title = format!("Error: {}", error.lines().next().unwrap_or("").chars().take(100).collect::<String>());
+    reqwest::Url::parse_with_params(NEW_ISSUE_URL, &[("title", title), ("body", body(error))])
+        .map(|u| u.to_string())
+        .unwrap_or(NEW_ISSUE_URL.to_string())
+}
+
+#[cfg(target_os = "windows")]
+pub fn os_description() -> String {
+    let hklm = winreg::RegKey::predef(winreg::enums::HKEY_LOCAL_MACHINE);
+    hklm.open_subkey(r"SOFTWARE\Microsoft\Windows NT\CurrentVersion")
+        .and_then(|key| Ok(format!("{} (build {})", key.get_value::<String,_>("ProductName")?, key.get_value::<String,_>("CurrentBuildNumber")?)))
+        .unwrap_or(format!("Windows {}", std::env::consts::ARCH))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn os_description() -> String {
+    format!("{} {}", std::env::consts::OS, std::env::consts::ARCH)
+}
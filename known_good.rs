@@ -0,0 +1,84 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// The last setup that worked: once a launch gets through the troubleshooter's watch without any problems turning up,
+// the mod version and its settings file are remembered so they can be put back when a later update or settings change
+// breaks things.
+
+use std::fs::File;
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+use tracing::info;
+
+use crate::error::ErscomError;
+use crate::manage::{EldenRingDir, EldenRingManager};
+use crate::paths;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownGood {
+    pub tag: String,
+    /// The settings file, relative to the game dir (it's moved around between versions of the mod).
+    pub ini_path: PathBuf,
+    /// The settings file as it was, comments and all.
+    pub settings: String,
+    /// When it was saved, in seconds since the epoch.
+    pub saved_at: i64,
+}
+
+pub fn path() -> PathBuf {
+    paths::data_dir().join("last-known-good.json")
+}
+
+/// The last setup that worked, if there's been one.
+pub fn load() -> Result<Option<KnownGood>, ErscomError> {
+    let path = path();
+    match File::open(&path) {
+        Ok(file) => Ok(Some(serde_json::from_reader(file).map_err(ErscomError::json(&path))?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(ErscomError::io(&path)(e)),
+    }
+}
+
+/// Remember what's installed right now as working.
+pub fn save(manager: &EldenRingManager) -> Result<(), ErscomError> {
+    let (dir, current) = manager.ok()?;
+    let ini_path = current.path_for("ini")?;
+    let full_ini_path = dir.path().join(&ini_path);
+    let settings = std::fs::read_to_string(&full_ini_path).map_err(ErscomError::io(&full_ini_path))?;
+    let known_good = KnownGood { tag: current.tag.clone(), ini_path, settings, saved_at: chrono::Local::now().timestamp() };
+    let path = path();
+    std::fs::create_dir_all(paths::data_dir()).map_err(ErscomError::io(&paths::data_dir()))?;
+    serde_json::to_writer_pretty(File::create(&path).map_err(ErscomError::io(&path))?, &known_good).map_err(ErscomError::json(&path))?;
+    info!("Remembering {} as the last working setup", known_good.tag);
+    Ok(())
+}
+
+impl KnownGood {
+    /// Put the settings file back. The right version has to be installed first.
+    pub fn restore_settings(&self, installdir: &EldenRingDir) -> Result<(), ErscomError> {
+        let path = installdir.path().join(&self.ini_path);
+        info!("Restoring {}", path.display());
+        std::fs::write(&path, &self.settings).map_err(ErscomError::io(&path))
+    }
+
+    /// "1.7.3 (working on October 10, 2026)"
+    pub fn describe(&self) -> String {
+        match chrono::DateTime::from_timestamp(self.saved_at, 0) {
+            Some(t) => format!("{} (working on {})", self.tag, crate::status::date(t.with_timezone(&chrono::Local))),
+            None => self.tag.clone(),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Who has a file open. Windows won't let us replace a file that something else is using, and all it says about it
+// is "Access is denied". The Restart Manager (what installers use for "these programs need to close") knows who.
+
+use std::ffi::c_void;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RmUniqueProcess {
+    process_id: u32,
+    start_time: [u32; 2], // FILETIME
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RmProcessInfo {
+    process: RmUniqueProcess,
+    app_name: [u16; 256],
+    service_short_name: [u16; 64],
+    app_type: i32,
+    app_status: u32,
+    ts_session_id: u32,
+    restartable: i32,
+}
+
+#[link(name = "rstrtmgr")]
+extern "system" {
+    fn RmStartSession(session: *mut u32, flags: u32, session_key: *mut u16) -> u32;
+    fn RmRegisterResources(session: u32, n_files: u32, files: *const *const u16, n_apps: u32, apps: *const RmUniqueProcess,
+                           n_services: u32, services: *const *const u16) -> u32;
+    fn RmGetList(session: u32, needed: *mut u32, count: *mut u32, info: *mut RmProcessInfo, reboot_reasons: *mut u32) -> u32;
+    fn RmEndSession(session: u32) -> u32;
+}
+extern "system" {
+    fn OpenProcess(access: u32, inherit: i32, process_id: u32) -> *mut c_void;
+    fn QueryFullProcessImageNameW(process: *mut c_void, flags: u32, name: *mut u16, size: *mut u32) -> i32;
+    fn CloseHandle(handle: *mut c_void) -> i32;
+}
+const ERROR_SUCCESS: u32 = 0;
+const ERROR_MORE_DATA: u32 = 234;
+const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+
+/// The processes that have `path` open, like "eldenring.exe (PID 1234)". Empty if nobody does or we can't tell.
+pub fn holders(path: &Path) -> Vec<String> {
+    let wide = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect::<Vec<u16>>();
+    let mut session = 0u32;
+    let mut key = [0u16; 33]; // CCH_RM_SESSION_KEY+1
+    if unsafe { RmStartSession(&mut session, 0, key.as_mut_ptr()) } != ERROR_SUCCESS { return vec![] }
+    let processes = (|| {
+        let files = [wide.as_ptr()];
+        if unsafe { RmRegisterResources(session, 1, files.as_ptr(), 0, std::ptr::null(), 0, std::ptr::null()) } != ERROR_SUCCESS { return vec![] }
+        let mut info: Vec<RmProcessInfo> = vec![];
+        // It can change between asking how many and getting them
+        for _ in 0..3 {
+            let (mut needed, mut count, mut reasons) = (0u32, info.len() as u32, 0u32);
+            match unsafe { RmGetList(session, &mut needed, &mut count, info.as_mut_ptr(), &mut reasons) } {
+                ERROR_SUCCESS => { info.truncate(count as usize); return info }
+                ERROR_MORE_DATA => info = vec![unsafe { std::mem::zeroed() }; needed as usize],
+                _ => return vec![],
+            }
+        }
+        vec![]
+    })();
+    unsafe { RmEndSession(session) };
+    processes.iter().map(|p| {
+        let name = exe_name(p.process.process_id).unwrap_or_else(|| {
+            let len = p.app_name.iter().position(|&c| c == 0).unwrap_or(p.app_name.len());
+            String::from_utf16_lossy(&p.app_name[..len])
+        });
+        format!("{} (PID {})", name, p.process.process_id)
+    }).collect()
+}
+
+fn exe_name(process_id: u32) -> Option<String> {
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process_id) };
+    if process.is_null() { return None }
+    let mut buf = [0u16; 1024];
+    let mut size = buf.len() as u32;
+    let ok = unsafe { QueryFullProcessImageNameW(process, 0, buf.as_mut_ptr(), &mut size) };
+    unsafe { CloseHandle(process) };
+    if ok == 0 { return None }
+    let full = String::from_utf16_lossy(&buf[..size as usize]);
+    Path::new(&full).file_name().map(|n| n.to_string_lossy().into_owned())
+}
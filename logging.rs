@@ -0,0 +1,52 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use crate::paths;
+
+/// Overrides the log filter, using `tracing_subscriber::EnvFilter` syntax (eg, `ERSCOM_LOG=trace`).
+pub const LOG_ENV: &str = "ERSCOM_LOG";
+
+pub const LOG_FILE_PREFIX: &str = "erscom";
+pub const LOG_FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7; // One per day
+
+/// Set up logging to a daily rotating file in the log dir plus stderr. In CLI mode stderr doesn't get any (so it
+/// doesn't drown out the actual output) unless `verbose` is set.
+pub fn init(verbose: bool, cli: bool) {
+    let default_level = if verbose { "debug" } else { "info" };
+    let file_filter = || EnvFilter::try_from_env(LOG_ENV)
+        .unwrap_or_else(|_| EnvFilter::new(format!("warn,elden_ring_seamless_co_op_manager={}", default_level)));
+    let console_filter = if cli && !verbose && std::env::var(LOG_ENV).is_err() { EnvFilter::new("off") } else { file_filter() };
+
+    let _ = std::fs::create_dir_all(paths::log_dir()); // The appender's old log pruning complains if it's missing
+    let file_layer = tracing_appender::rolling::Builder::new()
+        .rotation(tracing_appender::rolling::Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(paths::log_dir())
+        .map_err(|e| eprintln!("Couldn't open log file in {}: {}", paths::log_dir().display(), e))
+        .ok()
+        .map(|appender| fmt::layer().with_ansi(false).with_writer(appender).with_filter(file_filter()));
+
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(fmt::layer().with_writer(std::io::stderr).with_filter(console_filter))
+        .init();
+
+    tracing::info!("erscom {} starting, logging to {}", option_env!("VERSION").unwrap_or("0.0.0-local"), paths::log_dir().display());
+}
@@ -0,0 +1,99 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{cell::RefCell, path::PathBuf, rc::Rc, time::Duration};
+
+use slint::ComponentHandle;
+
+use crate::{logging, paths, report::UIError, LogWindow};
+
+const MAX_LINES: usize = 2000;
+
+// Indexes into the level filter ComboBox in LogWindow
+const LEVELS: [&str; 5] = ["TRACE", "DEBUG", "INFO", "WARN", "ERROR"];
+
+/// The newest log file. They're named by date so the biggest name is the newest.
+pub fn latest_log_file() -> Option<PathBuf> {
+    std::fs::read_dir(paths::log_dir()).ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.file_name().map(|n| n.to_string_lossy().starts_with(logging::LOG_FILE_PREFIX)).unwrap_or(false))
+        .max()
+}
+
+// Log lines look like "2024-08-18T01:02:03.456789Z  INFO target: message". Continuation lines (from messages with
+// newlines in them) don't have a level so they inherit the one before.
+fn level_of(line: &str) -> Option<usize> {
+    let word = line.split_whitespace().nth(1)?;
+    LEVELS.iter().position(|l| *l == word)
+}
+
+/// The tail end of the log, only including lines at `min_level` or above.
+fn filtered_log(min_level: usize) -> String {
+    let Some(path) = latest_log_file() else { return format!("No log file found in {}", paths::log_dir().display()) };
+    let contents = match std::fs::read(&path) {
+        Ok(contents) => String::from_utf8_lossy(&contents).into_owned(),
+        Err(e) => return format!("Couldn't read {}: {}", path.display(), e),
+    };
+    let mut level = 0;
+    let lines: Vec<&str> = contents.lines().filter(|line| {
+        level = level_of(line).unwrap_or(level);
+        level >= min_level
+    }).collect();
+    lines[lines.len().saturating_sub(MAX_LINES)..].join("\n")
+}
+
+pub fn show_log_window() {
+    let win = LogWindow::new().try_error()?;
+    win.set_theme(crate::config::get().theme.color_theme());
+    win.set_high_contrast(crate::config::get().high_contrast);
+    win.set_levels(Rc::new(slint::VecModel::from(LEVELS.iter().map(|l| slint::SharedString::from(*l)).collect::<Vec<_>>())).into());
+    win.set_level_index(LEVELS.iter().position(|l| *l == "INFO").unwrap() as i32);
+    let update = {
+        let weak_win = win.as_weak();
+        let last = Rc::new(RefCell::new(String::new()));
+        move || {
+            let Some(win) = weak_win.upgrade() else { return };
+            let text = filtered_log(win.get_level_index().max(0) as usize);
+            if *last.borrow() != text { // Don't reset the user's selection when nothing changed
+                win.set_log_text(text.clone().into());
+                *last.borrow_mut() = text;
+            }
+        }
+    };
+    update();
+
+    let timer = slint::Timer::default();
+    timer.start(slint::TimerMode::Repeated, Duration::from_secs(1), update.clone());
+
+    win.on_level_changed(update);
+    win.on_copy({
+        let weak_win = win.as_weak();
+        move || {
+            let text = weak_win.unwrap().get_log_text();
+            arboard::Clipboard::new().and_then(|mut c| c.set_text(text.as_str())).try_warn()?;
+        }
+    });
+    win.on_open_folder(|| {
+        crate::open_path(&paths::log_dir()).try_error()?;
+    });
+    win.on_close({
+        let weak_win = win.as_weak();
+        move || {
+            timer.stop();
+            weak_win.unwrap().hide().try_log("closing log window")?;
+        }
+    });
+    win.show().try_log("showing log window")?;
+}
@@ -13,11 +13,18 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::{error::Error, fs::File, path::{Path, PathBuf}};
+use std::{cell::RefCell, collections::HashSet, fs::File, path::{Path, PathBuf}, sync::{Arc, Mutex, OnceLock}};
 
-use serde::{Serialize, Deserialize};
+use tracing::{debug, info, warn};
 
+use crate::config;
+use crate::error::ErscomError;
+use crate::history;
 use crate::ini::Ini;
+use crate::paths;
+use crate::secret::Secret;
+use crate::source::{CacheSource, GithubSource, Progress, RateLimit, ReleaseSource};
+use crate::zipcache::ZipCache;
 
 #[derive(Debug, Clone)]
 pub struct Release {
@@ -25,188 +32,531 @@ pub struct Release {
     pub url: String,
     pub date: String,
     pub changelog: String,
+    pub prerelease: bool,
+    pub downloads: Option<u64>, // How many times the zip has been downloaded from github
+    pub page_url: Option<String>, // The release's web page, for the comments and linked issues
+    pub source: Arc<dyn ReleaseSource>,
+    pub contents: Arc<Mutex<Option<Arc<ReleaseContents>>>>, // See Release::contents()
 }
 
-// These are the parts of the github release api that we care about.
-// See https://docs.github.com/en/rest/releases/releases
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct GithubRelease {
-    tag_name: String,
-    published_at: String,
-    body: String,
-    assets: Vec<GithubAsset>,
+/// A release zip's table of contents.
+#[derive(Debug)]
+pub struct ReleaseContents {
+    pub entries: Vec<ZipEntry>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct GithubAsset {
-    browser_download_url: String,
+impl ReleaseContents {
+    /// How many files installing puts in the game dir (not counting directories).
+    pub fn file_count(&self) -> usize {
+        self.entries.iter().filter(|e| !e.is_dir).count()
+    }
+
+    /// Uncompressed, so how much disk it takes once installed.
+    pub fn size(&self) -> u64 {
+        self.entries.iter().map(|e| e.size).sum()
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ZipEntry {
+    pub path: PathBuf, // Relative to the install dir
+    pub is_dir: bool,
+    pub size: u64, // Uncompressed
+    pub crc32: u32,
 }
 
-fn github_releases(project: &str) -> Result<Vec<GithubRelease>, Box<dyn Error>> {
-    tokio::task::block_in_place(move || {
-        let client = reqwest::blocking::Client::new();
-        let resp = client.get(&format!("https://api.github.com/repos/{}/releases", project))
-            .header("Accept", "application/vnd.github+json")
-            .header("User-Agent", "erscom 1.0")
-            .send()?;
-        let status = resp.status();
-        if !status.is_success() {
-            Err(resp.text().unwrap_or(format!("Got status {}", status)))?;
-            unreachable!();
+// What the mod's DLL has been called, in the SeamlessCoop directory. One of them is in every release.
+const MOD_DLLS: &[&str] = &["elden_ring_seamless_coop.dll", "ersc.dll"];
+
+// For picking between several files with the same extension
+const KNOWN_FILES: &[&str] = &["ersc_launcher.exe", "ersc_settings.ini"];
+
+/// A newer version of the manager than this one, if there's one on the channel picked in the preferences. Builds that
+/// are newer than anything released (or re-tagged releases) don't count.
+pub fn self_upgrade_version() -> Result<Option<String>, ErscomError> {
+    let Some(current_version) = option_env!("VERSION") else { return Ok(None) };
+    let my_tags = GithubSource::new("caldwell/erscom").tags(config::get().manager_channel == config::ManagerChannel::Beta)?;
+    Ok(my_tags.into_iter()
+       .max_by_key(|tag| version_key(tag))
+       .filter(|newest| version_key(newest) > version_key(current_version)))
+}
+
+/// Something that sorts like a version number, the way semver does: "v1.7.10" => [1, 7, 10], and a pre-release
+/// ("v2.0.0-beta.1") comes before the release it leads up to.
+pub fn version_key(tag: &str) -> VersionKey {
+    let tag = tag.split('+').next().unwrap_or_default(); // Build metadata doesn't count
+    let (release, pre) = tag.split_once('-').unwrap_or((tag, ""));
+    VersionKey {
+        release: release.split(|c: char| !c.is_ascii_digit()).filter(|s| !s.is_empty()).map(|s| s.parse().unwrap_or(u32::MAX)).collect(),
+        pre: pre.split('.').filter(|s| !s.is_empty())
+            .map(|s| s.parse().map(PreRelease::Number).unwrap_or_else(|_| PreRelease::Text(s.to_string())))
+            .collect(),
+    }
+}
+
+/// See [version_key()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionKey {
+    release: Vec<u32>,
+    pre: Vec<PreRelease>, // Empty for a release
+}
+
+// Numbers sort before words, like semver says
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum PreRelease {
+    Number(u64),
+    Text(String),
+}
+
+impl Ord for VersionKey {
+    fn cmp(&self, other: &VersionKey) -> std::cmp::Ordering {
+        self.release.cmp(&other.release).then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+            (true, true) => std::cmp::Ordering::Equal,
+            (true, false) => std::cmp::Ordering::Greater,
+            (false, true) => std::cmp::Ordering::Less,
+            (false, false) => self.pre.cmp(&other.pre),
+        })
+    }
+}
+
+impl PartialOrd for VersionKey {
+    fn partial_cmp(&self, other: &VersionKey) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+static CACHE_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `dir` for the cache regardless of the config (for `--cache-dir`).
+pub fn override_cache_dir(dir: PathBuf) {
+    let _ = CACHE_DIR_OVERRIDE.set(dir);
+}
+
+pub fn cache_dir() -> Result<PathBuf, ErscomError> {
+    #[cfg(test)]
+    if let Some(dir) = paths::test_data_dir() { return Ok(dir.join("release cache")) }
+    if let Some(dir) = CACHE_DIR_OVERRIDE.get() { return Ok(dir.clone()) }
+    // Wherever Preferences points is likely on some other machine
+    if paths::portable() { return default_cache_dir() }
+    match crate::config::get().cache_dir {
+        Some(dir) => Ok(dir),
+        None => default_cache_dir(),
+    }
+}
+
+// Undo a failed install: put back what was there before, newest first
+fn roll_back(originals: Vec<(PathBuf, Option<Vec<u8>>)>) {
+    for (path, original) in originals.into_iter().rev() {
+        info!("Rolling back {:?}", path);
+        let rolled_back = match original {
+            Some(bytes) => std::fs::write(&path, bytes),
+            None => std::fs::remove_file(&path),
+        };
+        if let Err(e) = rolled_back { warn!("Couldn't roll back {:?}: {}", path, e) }
+    }
+}
+
+/// Where a zip entry goes, relative to the install dir. Like `ZipFile::enclosed_name()` (nothing that could escape
+/// the install dir), except that it also takes `\` as a separator. Some hand-made zips use them and the zip crate
+/// only knows about `/`.
+fn entry_path(file: &zip::read::ZipFile) -> Option<PathBuf> {
+    let name = file.name();
+    if name.contains('\0') { return None }
+    let mut path = PathBuf::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => {},
+            ".." => return None,
+            part if part.contains(':') => return None, // C:, or an NTFS stream
+            part => path.push(part),
         }
-        Ok(resp.json()?)
-    })
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
 }
 
-pub fn self_upgrade_version() -> Result<Option<String>, Box<dyn Error>> {
-    if let Some(current_version) = option_env!("VERSION") {
-        let my_releases = github_releases("caldwell/erscom")?;
-        if my_releases.first().map(|r| &r.tag_name) != Some(&current_version.to_string()) {
-            return Ok(Some(my_releases.first().unwrap().tag_name.clone()));
+fn entry_is_dir(file: &zip::read::ZipFile) -> bool {
+    file.is_dir() || file.name().ends_with('\\')
+}
+
+/// Everything in the zip at `zip_path`, without unzipping anything.
+pub fn zip_entries(zip_path: &Path) -> Result<Vec<ZipEntry>, ErscomError> {
+    let mut zip = zip::ZipArchive::new(File::open(zip_path).map_err(ErscomError::io(zip_path))?).map_err(ErscomError::zip(zip_path))?;
+    let mut entries = vec![];
+    for i in 0..zip.len() {
+        let file = zip.by_index_raw(i).map_err(ErscomError::zip(zip_path))?;
+        let Some(path) = entry_path(&file) else { continue };
+        entries.push(ZipEntry { path, is_dir: entry_is_dir(&file), size: file.size(), crc32: file.crc32() });
+    }
+    Ok(entries)
+}
+
+/// What [Release::installed()] found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallState {
+    Installed,
+    /// It's this release, but some of its other files are missing or different.
+    InstalledModified,
+    NotInstalled,
+    /// Can't tell, usually because the release's zip (and so its list of files) isn't here.
+    Unknown { reason: String },
+}
+
+impl InstallState {
+    pub fn is_installed(&self) -> bool {
+        matches!(self, InstallState::Installed | InstallState::InstalledModified)
+    }
+}
+
+impl std::fmt::Display for InstallState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallState::Installed => write!(f, "installed"),
+            InstallState::InstalledModified => write!(f, "installed, but some files are missing or changed"),
+            InstallState::NotInstalled => write!(f, "not installed"),
+            InstallState::Unknown { reason } => write!(f, "can't tell ({})", reason),
+        }
+    }
+}
+
+/// The first of `releases` that's installed in `installdir`. Each check reads files, so they all run at once.
+pub async fn find_installed(releases: Vec<Release>, installdir: EldenRingDir) -> Option<Release> {
+    let checks: Vec<_> = releases.into_iter().map(|release| {
+        let installdir = installdir.clone();
+        tokio::task::spawn_blocking(move || {
+            let state = release.installed(&installdir);
+            if state == InstallState::InstalledModified { warn!("{} is {}", release.tag, state) }
+            state.is_installed().then_some(release)
+        })
+    }).collect();
+    for check in checks {
+        match check.await {
+            Ok(Some(release)) => return Some(release),
+            Ok(None) => {},
+            Err(e) => warn!("Checking for an installed release failed: {}", e),
         }
     }
-    Ok(None)
+    None
 }
 
-pub fn get_releases() -> Result<Vec<Release>, Box<dyn Error>> {
-    Ok(github_releases("LukeYui/EldenRingSeamlessCoopRelease")?.iter().map(|release| {
-        Release {
-            tag: release.tag_name.clone(),
-            url: release.assets[0].browser_download_url.clone(),
-            date: release.published_at.clone(),
-            changelog: release.body.clone(),
+/// Which of `releases` each of the mod's files in `installdir` matches, if there's no one release they all match (an
+/// install that got interrupted, or an uninstall that didn't finish). Files that don't match anything (edited, or from a
+/// release that isn't downloaded) don't count either way.
+pub fn mixed_install(releases: &[Release], installdir: &EldenRingDir) -> Option<Vec<(PathBuf, Vec<String>)>> {
+    let mut on_disk: Vec<(PathBuf, Option<(u64, u32)>)> = vec![];
+    let mut files: Vec<(PathBuf, Vec<String>)> = vec![];
+    for release in releases {
+        let Ok(contents) = release.contents() else { continue };
+        for entry in contents.entries.iter().filter(|e| !e.is_dir && e.path.extension().map(|n| n.to_string_lossy().to_lowercase() != "ini").unwrap_or(true)) {
+            let disk = match on_disk.iter().find(|(path, _)| *path == entry.path) {
+                Some((_, disk)) => *disk,
+                None => {
+                    let disk = std::fs::read(paths::long_path(&installdir.path().join(&entry.path))).ok().map(|data| (data.len() as u64, crc32fast::hash(&data)));
+                    on_disk.push((entry.path.clone(), disk));
+                    disk
+                },
+            };
+            if disk != Some((entry.size, entry.crc32)) { continue }
+            match files.iter_mut().find(|(path, _)| *path == entry.path) {
+                Some((_, tags)) => tags.push(release.tag.clone()),
+                None => files.push((entry.path.clone(), vec![release.tag.clone()])),
+            }
         }
-    }).collect())
+    }
+    let all_from_one = releases.iter().any(|r| files.iter().all(|(_, tags)| tags.contains(&r.tag)));
+    (!all_from_one).then_some(files)
+}
+
+pub fn default_cache_dir() -> Result<PathBuf, ErscomError> {
+    Ok(std::env::current_exe().map_err(|e| format!("Couldn't find my .exe: {}", e))?
+       .parent().ok_or(format!("Couldn't find where my .exe lives"))?
+       .join("release cache"))
+}
+
+/// What to do about a file that couldn't be installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileErrorAction {
+    Retry,
+    Skip,
+    Abort, // And put back everything that was already installed
 }
 
 impl Release {
-    pub fn install(&self, installdir: &EldenRingDir) -> Result<(), Box<dyn Error>> {
-        self.install_uninstall(installdir, |file, dest_path| -> Result<(), Box<dyn Error>> {
-            let name = file.enclosed_name().unwrap(); // Guaranteed by instal_uninstall()
-            println!("Filename: {}{}  -> {:?}", name.to_string_lossy(), if name.is_dir() { "/" } else { "" }, dest_path);
-            std::fs::create_dir_all(&dest_path.parent().ok_or(format!("No parent for {:?}??", dest_path))?)?;
-            let mut dest = File::create(&dest_path).map_err(|e| format!("Error creating {:?}: {}", dest_path, e))?;
-            if let Err(e) = std::io::copy(file, &mut dest) {
-                Err(format!("Error writing {:?}: {}", dest_path, e))?;
+    pub fn install(&self, installdir: &EldenRingDir) -> Result<(), ErscomError> {
+        self.install_with(installdir, |_, _| FileErrorAction::Abort).map(|_| ())
+    }
+
+    /// Install, asking `on_error` what to do about each file that fails. Returns the files (relative to the install
+    /// dir) that got skipped. If it fails or gets aborted, the files it already wrote are put back the way they were.
+    pub fn install_with(&self, installdir: &EldenRingDir, on_error: impl FnMut(&Path, &ErscomError) -> FileErrorAction) -> Result<Vec<PathBuf>, ErscomError> {
+        self.install_except(installdir, &HashSet::new(), on_error)
+    }
+
+    /// Can [Release::upgrade_from()] go from `old` to this one? Only if both zips are already here to compare, and
+    /// it's not a reinstall (which should write everything).
+    pub fn can_upgrade_from(&self, old: &Release) -> bool {
+        self.tag != old.tag && self.downloaded() && old.downloaded()
+    }
+
+    /// Go from `old` (installed in `installdir`) to this release by only writing the files that changed (or that don't
+    /// match `old`'s on disk), then removing the ones this release doesn't have. Most releases only change a DLL or
+    /// two, so this is quicker and the game dir spends less time half updated. Returns the skipped files, like
+    /// [Release::install_with()].
+    pub fn upgrade_from(&self, old: &Release, installdir: &EldenRingDir, on_error: impl FnMut(&Path, &ErscomError) -> FileErrorAction) -> Result<Vec<PathBuf>, ErscomError> {
+        let (old_contents, contents) = (old.contents()?, self.contents()?);
+        let unchanged: HashSet<PathBuf> = contents.entries.iter()
+            .filter(|e| !e.is_dir)
+            .filter(|e| old_contents.entries.iter().any(|o| o.path == e.path && o.size == e.size && o.crc32 == e.crc32))
+            .filter(|e| self.file_installed(installdir, &e.path) == Some(true))
+            .map(|e| e.path.clone())
+            .collect();
+        info!("Upgrading {} to {}: {} of {} files are unchanged", old.tag, self.tag, unchanged.len(), contents.file_count());
+        let skipped = self.install_except(installdir, &unchanged, on_error)?;
+        for gone in old.leftovers(installdir, self)? {
+            let path = paths::long_path(&installdir.path().join(&gone));
+            info!("{} Removing: {:?}", old.tag, path);
+            std::fs::remove_file(&path).map_err(ErscomError::io(&path))?;
+        }
+        Ok(skipped)
+    }
+
+    // install_with(), but leaving the files in `unchanged` alone.
+    fn install_except(&self, installdir: &EldenRingDir, unchanged: &HashSet<PathBuf>, on_error: impl FnMut(&Path, &ErscomError) -> FileErrorAction) -> Result<Vec<PathBuf>, ErscomError> {
+        let originals: RefCell<Vec<(PathBuf, Option<Vec<u8>>)>> = RefCell::new(vec![]); // None means it wasn't there
+        let installed = self.install_uninstall(installdir, |file, dest_path| -> Result<(), ErscomError> {
+            let name = entry_path(file).unwrap(); // Guaranteed by instal_uninstall()
+            if unchanged.contains(&name) {
+                debug!("{} Unchanged: {}", self.tag, name.to_string_lossy());
+                return Ok(())
             }
+            info!("{} Installing: {}{}  -> {:?}", self.tag, name.to_string_lossy(), if name.is_dir() { "/" } else { "" }, dest_path);
+            let parent = dest_path.parent().ok_or(format!("No parent for {:?}??", dest_path))?;
+            std::fs::create_dir_all(parent).map_err(ErscomError::io(parent))?;
+            if !originals.borrow().iter().any(|(p, _)| *p == dest_path) { // Retries would see what we wrote
+                let original = match std::fs::read(&dest_path) {
+                    Ok(bytes) => Some(bytes),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(e) => Err(ErscomError::io(&dest_path)(e))?,
+                };
+                originals.borrow_mut().push((dest_path.clone(), original));
+            }
+            let mut dest = File::create(&dest_path).map_err(ErscomError::io(&dest_path))?;
+            std::io::copy(file, &mut dest).map_err(ErscomError::io(&dest_path))?;
             Ok(())
-        })
+        }, on_error);
+        let skipped = installed.inspect_err(|_| roll_back(originals.take()))?;
+        history::record(format!("Installed {} in {}", self.tag, installdir));
+        if let Err(e) = config::update(|c| c.installed_at = Some((self.tag.clone(), chrono::Local::now().timestamp()))) {
+            warn!("Couldn't remember when {} was installed: {}", self.tag, e);
+        }
+        if !skipped.is_empty() {
+            history::record(format!("Skipped {} while installing {}", skipped.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", "), self.tag));
+        }
+        Ok(skipped)
     }
 
-    pub fn uninstall(&self, installdir: &EldenRingDir) -> Result<(), Box<dyn Error>> {
-        self.install_uninstall(installdir, |_file, dest_path| -> Result<(), Box<dyn Error>> {
-            println!("{} Removing: {:?}", self.tag, dest_path);
-            std::fs::remove_file(&dest_path)?;
-            Ok(())
-        })
+    /// Remove this release's files (but not its settings) from `installdir`. Only needs the list of files, so a
+    /// release whose zip is gone goes by the one saved when it was here (see [Release::contents()]). If there isn't
+    /// one, another release's list that matches what's installed will do, and only if nothing will is the zip
+    /// downloaded again.
+    pub fn uninstall(&self, installdir: &EldenRingDir) -> Result<(), ErscomError> {
+        let (entries, from) = match self.contents() {
+            Ok(contents) => (contents.entries.clone(), self.tag.clone()),
+            Err(ErscomError::NotFound(_)) => match ZipCache::open()?.stand_in(installdir, &self.tag) {
+                Some((tag, entries)) => { info!("No list of {}'s files, going by {}'s", self.tag, tag); (entries, tag) },
+                None => { info!("No list of {}'s files, downloading it", self.tag); self.download()?; (self.contents()?.entries.clone(), self.tag.clone()) },
+            },
+            Err(e) => Err(e)?,
+        };
+        if !std::fs::metadata(installdir.path()).map_err(ErscomError::io(installdir.path()))?.is_dir() {
+            Err(format!("{} is not a directory!", installdir))?;
+        }
+        for entry in entries.iter().filter(|e| !e.is_dir && e.path.extension().map(|n| n.to_string_lossy().to_lowercase() != "ini").unwrap_or(true)) {
+            let dest_path = paths::long_path(&installdir.path().join(&entry.path));
+            if !dest_path.exists() { debug!("{} Already gone: {:?}", self.tag, dest_path); continue }
+            info!("{} Removing: {:?}", self.tag, dest_path);
+            std::fs::remove_file(&dest_path).map_err(ErscomError::io(&dest_path))?;
+        }
+        history::record(match from == self.tag {
+            true => format!("Uninstalled {} from {}", self.tag, installdir),
+            false => format!("Uninstalled {} from {}, going by {}'s list of files", self.tag, installdir, from),
+        });
+        Ok(())
     }
 
-    fn install_uninstall<F>(&self, installdir: &EldenRingDir, handler: F) -> Result<(), Box<dyn Error>> where F: Fn(&mut zip::read::ZipFile, PathBuf) -> Result<(), Box<dyn Error>> {
+    fn install_uninstall<F, E>(&self, installdir: &EldenRingDir, handler: F, mut on_error: E) -> Result<Vec<PathBuf>, ErscomError>
+    where F: Fn(&mut zip::read::ZipFile, PathBuf) -> Result<(), ErscomError>,
+          E: FnMut(&Path, &ErscomError) -> FileErrorAction {
         let path = self.download()?;
-        println!("Local zip: {}", path.to_string_lossy());
+        debug!("Local zip: {}", path.to_string_lossy());
 
-        if !std::fs::metadata(&installdir.path()).map_err(|e| format!("Error reading {:?}: {}", installdir, e))?.is_dir() {
+        if !std::fs::metadata(&installdir.path()).map_err(ErscomError::io(installdir.path()))?.is_dir() {
             Err(format!("{} is not a directory!", installdir))?;
         }
 
-        let mut zip = zip::ZipArchive::new(File::open(&path)?).map_err(|e| format!("Couldn't read {}: {}", path.to_string_lossy(), e))?;
+        let mut zip = zip::ZipArchive::new(File::open(&path).map_err(ErscomError::io(&path))?).map_err(ErscomError::zip(&path))?;
+        let mut skipped = vec![];
         for i in 0..zip.len() {
-            let mut file = zip.by_index(i)?;
-            if let Some(name) = file.enclosed_name() {
-                let dest_path = installdir.path().join(name);
-                match (file.is_dir(), dest_path.is_file(), name.extension().map(|n| n.to_string_lossy().to_lowercase()) == Some("ini".to_string())) {
+            let (name, dest_path) = {
+                let file = zip.by_index(i).map_err(ErscomError::zip(&path))?;
+                let Some(name) = entry_path(&file) else { continue };
+                // Some people's game dirs plus the mod's paths go past MAX_PATH
+                let dest_path = paths::long_path(&installdir.path().join(&name));
+                match (entry_is_dir(&file), dest_path.is_file(), name.extension().map(|n| n.to_string_lossy().to_lowercase()) == Some("ini".to_string())) {
                     (false, false, _) |
-                    (false, true,  false) => { handler(&mut file, dest_path)?; },
-                    (_,_,_) => { println!("Ignoring {}", file.name()) },
+                    (false, true,  false) => (name, dest_path),
+                    (_,_,_) => { debug!("Ignoring {}", file.name()); continue },
+                }
+            };
+            loop {
+                // A retry needs to read the entry from the start again
+                let mut file = zip.by_index(i).map_err(ErscomError::zip(&path))?;
+                let Err(e) = handler(&mut file, dest_path.clone()) else { break };
+                match on_error(&name, &e) {
+                    FileErrorAction::Retry => info!("Retrying {}", name.display()),
+                    FileErrorAction::Skip => { warn!("Skipping {}: {}", name.display(), e); skipped.push(name); break },
+                    FileErrorAction::Abort => Err(e)?,
                 }
             }
         }
-        Ok(())
+        Ok(skipped)
     }
 
-    pub fn installed(&self, installdir: &EldenRingDir) -> Option<bool> {
-        match (self.file_installed(installdir, &Path::new("SeamlessCoop").join("elden_ring_seamless_coop.dll")),
-               self.file_installed(installdir, &Path::new("SeamlessCoop").join("ersc.dll"))) {
-            (None, None) => None,
-            (Some(true), _) | (_, Some(true)) => Some(true),
-            (_,_) => Some(false),
-        }
+    /// This release's files that are still in `installdir` and aren't also `replacement`'s (which would have written
+    /// over them). Settings files are left alone on uninstall, so they don't count.
+    pub fn leftovers(&self, installdir: &EldenRingDir, replacement: &Release) -> Result<Vec<PathBuf>, ErscomError> {
+        let replaced = replacement.contents()?;
+        Ok(self.contents()?.entries.iter()
+           .filter(|e| !e.is_dir && e.path.extension().map(|n| n.to_string_lossy().to_lowercase() != "ini").unwrap_or(true))
+           .filter(|e| !replaced.entries.iter().any(|r| r.path == e.path))
+           .filter(|e| paths::long_path(&installdir.path().join(&e.path)).is_file())
+           .map(|e| e.path.clone())
+           .collect())
     }
 
+    /// Is this the release that's in `installdir`? It's decided by the mod's DLL (whichever name this release uses
+    /// for it). If that matches, the rest of the files are checked too.
+    pub fn installed(&self, installdir: &EldenRingDir) -> InstallState {
+        let contents = match self.contents() {
+            Ok(contents) => contents,
+            Err(e) => return InstallState::Unknown { reason: e.to_string() },
+        };
+        let dlls: Vec<PathBuf> = MOD_DLLS.iter().map(|dll| Path::new("SeamlessCoop").join(dll))
+            .filter(|dll| contents.entries.iter().any(|e| e.path == *dll))
+            .collect();
+        if dlls.is_empty() { return InstallState::Unknown { reason: format!("{} doesn't have the mod's DLL in it", self.tag) } }
+        if !dlls.iter().any(|dll| self.file_installed(installdir, dll) == Some(true)) { return InstallState::NotInstalled }
+        let modified = contents.entries.iter()
+            .filter(|e| !e.is_dir && e.path.extension().map(|n| n.to_string_lossy().to_lowercase() != "ini").unwrap_or(true))
+            .any(|e| self.file_installed(installdir, &e.path) != Some(true));
+        if modified { InstallState::InstalledModified } else { InstallState::Installed }
+    }
+
+    /// Does the file at `path` (relative to the install dir) match the one in the zip? `None` if we can't tell (not
+    /// downloaded, or not on disk).
     pub fn file_installed(&self, installdir: &EldenRingDir, path: &PathBuf) -> Option<bool> {
-        let disk_path = installdir.path().join(path);
-        let zip_file_path = path.iter().map(|p| p.to_string_lossy()).collect::<Vec<_>>().join("/");
-        use std::io::Read;
-        if !self.downloaded() {
-            return None;
-        }
-        let mut disk_file = File::open(&disk_path).ok()?;
-        let mut disk_dll = Vec::new();
-        disk_file.read_to_end(&mut disk_dll).ok()?;
+        let contents = self.contents().ok()?;
+        let entry = contents.entries.iter().find(|e| e.path == *path)?;
+        let disk_path = paths::long_path(&installdir.path().join(path));
+        if std::fs::metadata(&disk_path).ok()?.len() != entry.size { return Some(false) }
+        Some(crc32fast::hash(&std::fs::read(&disk_path).ok()?) == entry.crc32)
+    }
 
-        let zip_path = self.download().ok()?;
-        let mut zip = zip::ZipArchive::new(File::open(&zip_path).ok()?).map_err(|e| format!("Couldn't read {}: {}", zip_path.to_string_lossy(), e)).ok()?;
-        let mut zip_file = zip.by_name(&zip_file_path).ok()?;
-        let mut zip_dll = Vec::new();
-        zip_file.read_to_end(&mut zip_dll).ok()?;
+    /// The file in the zip ending in `.extension`. If there's more than one, a name from [KNOWN_FILES] wins, then
+    /// one in the mod's directory. If that still doesn't settle it, it's an error rather than a guess.
+    pub fn path_for(&self, extension: &str) -> Result<PathBuf, ErscomError> {
+        let matches = self.paths_for(extension)?;
+        let only = |filter: &dyn Fn(&PathBuf) -> bool| {
+            let mut found = matches.iter().filter(|p| filter(p));
+            match (found.next(), found.next()) { (Some(path), None) => Some(path.clone()), _ => None }
+        };
+        let known = |path: &PathBuf| path.file_name().map(|name| KNOWN_FILES.iter().any(|known| name.eq_ignore_ascii_case(known))).unwrap_or(false);
+        let in_mod_dir = |path: &PathBuf| path.starts_with("SeamlessCoop");
+        let Some(path) = only(&|_| true).or_else(|| only(&known)).or_else(|| only(&in_mod_dir)) else {
+            if matches.is_empty() { Err(ErscomError::NotFound(format!("No .{} file found in {}", extension, self.tag)))? }
+            Err(format!("{} has more than one .{} file ({}) and it's not clear which to use", self.tag, extension,
+                        matches.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ")))?
+        };
+        Ok(path)
+    }
 
-        Some(disk_dll == zip_dll)
+    /// Every file in the zip ending in `.extension`.
+    pub fn paths_for(&self, extension: &str) -> Result<Vec<PathBuf>, ErscomError> {
+        Ok(self.contents()?.entries.iter()
+           .filter(|e| !e.is_dir && e.path.extension().map(|n| n.to_string_lossy().to_lowercase() == extension).unwrap_or(false))
+           .map(|e| e.path.clone())
+           .collect())
     }
 
-    pub fn path_for(&self, extension: &str) -> Result<PathBuf, Box<dyn Error>> {
-        if !self.downloaded() { Err(format!("Release {} zip is not downloaded", self.tag))? }
-        let zip_path = self.download()?;
-        let mut zip = zip::ZipArchive::new(File::open(&zip_path)?).map_err(|e| format!("Couldn't read {}: {}", zip_path.to_string_lossy(), e))?;
-        for i in 0..zip.len() {
-            let file = zip.by_index(i)?;
-            if let Some(name) = file.enclosed_name() {
-                if name.extension().map(|n| n.to_string_lossy().to_lowercase() == extension).unwrap_or(false) {
-                    return Ok(name.to_owned());
-                }
-            }
+    /// What's in the zip (without unzipping anything). Read the first time it's asked for and remembered after that.
+    /// The list gets saved in the cache too, so it's still there if the zip gets deleted.
+    pub fn contents(&self) -> Result<Arc<ReleaseContents>, ErscomError> {
+        let mut contents = self.contents.lock().unwrap();
+        if let Some(ref contents) = *contents { return Ok(contents.clone()) }
+        let cache = ZipCache::open()?;
+        if !self.downloaded() {
+            let entries = cache.manifest(&self.tag).ok_or(ErscomError::NotFound(format!("Release {} zip is not downloaded", self.tag)))?;
+            debug!("{} has {} entries (from its manifest)", self.tag, entries.len());
+            return Ok(contents.insert(Arc::new(ReleaseContents { entries })).clone());
         }
-        Err(format!("No settings file found in .zip!"))?
+        let entries = zip_entries(&self.download()?)?;
+        debug!("{} has {} entries", self.tag, entries.len());
+        if cache.manifest(&self.tag).is_none() {
+            cache.save_manifest(&self.tag, &entries).unwrap_or_else(|e| warn!("Couldn't save {}'s list of files: {}", self.tag, e));
+        }
+        Ok(contents.insert(Arc::new(ReleaseContents { entries })).clone())
     }
 
-    pub fn cache_path(&self) -> Result<PathBuf, Box<dyn Error>> {
-        Ok(add_extension(&std::env::current_exe().map_err(|e| format!("Couldn't find my .exe: {}", e))?
-           .parent().ok_or(format!("Couldn't find where my .exe lives"))?
-           .join("release cache")
-           .join(&self.tag), "zip"))
+    /// Where the zip is in the cache. Zips are kept by hash now (see [ZipCache]), and one from before that gets moved
+    /// over the first time it's asked for. If it hasn't been downloaded this is the old spot, which won't exist.
+    pub fn cache_path(&self) -> Result<PathBuf, ErscomError> {
+        let cache = ZipCache::open()?;
+        Ok(match cache.find(&self.tag)? {
+            Some(path) => path,
+            None => cache.legacy_path(&self.tag),
+        })
     }
 
+    /// Is the zip in the cache? This only looks, it doesn't read the zip or move anything around.
     pub fn downloaded(&self) -> bool {
-        if let Ok(path) = self.cache_path() {
-            if let Ok(meta) = std::fs::metadata(&path) {
-                if meta.is_file() {
-                    return true;
-                }
-            }
+        match ZipCache::open() {
+            Ok(cache) => cache.lookup(&self.tag).is_some(),
+            Err(e) => { warn!("Couldn't look for {} in the cache: {}", self.tag, e); false },
         }
-        return false;
     }
 
-    pub fn download(&self) -> Result<PathBuf, Box<dyn Error>> {
+    /// Make sure the cached zip hasn't been damaged since it was downloaded. A damaged one gets removed, so the next
+    /// [Release::download()] gets it again. Returns false if it was damaged (or was never downloaded).
+    pub fn check_download(&self) -> Result<bool, ErscomError> {
+        let cache = ZipCache::open()?;
+        let Some(path) = cache.find(&self.tag)? else { return Ok(false) };
+        let ok = cache.check(&path)?;
+        if !ok { warn!("The cached zip for {} was damaged", self.tag) }
+        Ok(ok)
+    }
+
+    pub fn download(&self) -> Result<PathBuf, ErscomError> {
+        self.download_with(&Progress::default())
+    }
+
+    /// [Release::download], for when someone's watching (or might cancel it).
+    pub fn download_with(&self, progress: &Progress) -> Result<PathBuf, ErscomError> {
+        let cache = ZipCache::open()?;
         let path = self.cache_path()?;
-        if std::fs::metadata(&path).map(|m| m.is_file()).unwrap_or(false) {
-            return Ok(path);
+        if path.is_file() {
+            if cache.check_once(&path)? { return Ok(path) }
+            warn!("The cached zip for {} was damaged, downloading it again", self.tag);
         }
-        if !path.parent().ok_or("No parent for cache dir??")?.exists() {
-            std::fs::create_dir(&path.parent().unwrap())?;
+        let download_path = cache.partial_path(&self.tag);
+        let dir = download_path.parent().ok_or("No parent for cache dir??")?;
+        if !dir.exists() {
+            std::fs::create_dir_all(dir).map_err(ErscomError::io(dir))?;
         }
-        tokio::task::block_in_place(move || {
-            let client = reqwest::blocking::Client::new();
-            let mut resp = client.get(&self.url)
-                .header("User-Agent", "erscom 1.0")
-                .send()?;
-
-            let download_path = add_extension(&path, "partial");
-            let mut file = File::create(&download_path)?;
-            resp.copy_to(&mut file)?;
-
-            std::fs::rename(&download_path, &path)?;
-            Ok(path)
-        })
+        info!("Downloading {} to {}", self.tag, download_path.display());
+        let stored = self.source.fetch_asset(self, &download_path, progress).and_then(|()| cache.store(&self.tag, &download_path));
+        if stored.is_err() {
+            let _ = std::fs::remove_file(&download_path);
+        }
+        stored
     }
 
 }
@@ -234,13 +584,32 @@ impl EldenRingDir {
                           .join("pretend-installdir")))
     }
 
+    pub fn new(path: &Path) -> EldenRingDir {
+        EldenRingDir(path.to_path_buf())
+    }
+
     pub fn path(&self) -> &Path {
         &self.0
     }
 
+    /// Can we change files in here? Games installed under Program Files need administrator access.
+    pub fn writable(&self) -> bool {
+        [self.0.clone(), self.mod_dir()].iter().filter(|dir| dir.is_dir()).all(|dir| {
+            let probe = dir.join(".erscom-write-test");
+            let writable = File::create(&probe).is_ok();
+            let _ = std::fs::remove_file(&probe);
+            writable
+        })
+    }
+
     pub fn display(&self) -> String {
         self.0.to_string_lossy().into_owned()
     }
+
+    /// Where the mod lives.
+    pub fn mod_dir(&self) -> PathBuf {
+        self.0.join("SeamlessCoop")
+    }
 }
 
 impl std::fmt::Display for EldenRingDir {
@@ -249,68 +618,193 @@ impl std::fmt::Display for EldenRingDir {
     }
 }
 
+// Leave a few github api requests for things the user explicitly asks for (downloads, Refresh).
+const RATE_LIMIT_RESERVE: u32 = 5;
+
 #[derive(Debug, Clone)]
 pub struct EldenRingManager {
     pub dir: Option<EldenRingDir>,
     pub releases: Vec<Release>,
     pub current: Option<Release>,
+    pub source: Arc<dyn ReleaseSource>,
+    pub offline: bool, // releases came from the cache instead of `source`
+    pub updated_at: Option<chrono::DateTime<chrono::Local>>, // when `releases` was last fetched
+    pub rate_limit: Option<RateLimit>,
 }
 
 impl EldenRingManager {
-    pub fn new() -> EldenRingManager {
+    pub fn with_source(source: Arc<dyn ReleaseSource>) -> EldenRingManager {
         EldenRingManager {
             dir: EldenRingDir::autodetect_install_path(),
             releases: vec![],
             current: None,
+            offline: source.offline(),
+            updated_at: None,
+            rate_limit: None,
+            source,
         }
     }
 
     pub fn found_dir(&self) -> bool { self.dir.is_some() }
 
-    pub fn fetch_releases(&mut self) -> Result<(), Box<dyn Error>> {
-        self.releases = get_releases()?;
+    pub fn fetch_releases(&mut self) -> Result<(), ErscomError> {
+        let cache = Arc::new(CacheSource::new(&cache_dir()?));
+        self.releases = match self.source.clone().releases() {
+            Ok(releases) => {
+                self.offline = self.source.offline();
+                self.updated_at = self.source.updated_at();
+                self.rate_limit = self.source.rate_limit();
+                if !self.offline {
+                    if let Err(e) = cache.save(&releases) {
+                        warn!("Couldn't save release list to cache: {}", e);
+                    }
+                }
+                releases
+            },
+            Err(e) if e.is_network() => {
+                warn!("Couldn't fetch releases ({}), using the cache instead", e);
+                self.offline = true;
+                self.updated_at = cache.updated_at();
+                self.rate_limit = self.source.rate_limit();
+                cache.releases().map_err(|cache_err| { warn!("No cached releases to fall back on: {}", cache_err); e })?
+            },
+            Err(e) => Err(e)?,
+        };
+        if self.offline {
+            // Offline we can only install what we've already got
+            self.releases.retain(|r| r.downloaded());
+        }
         self.releases.sort_by(|a,b| b.date.cmp(&a.date));
         Ok(())
     }
 
+    /// Pick up where the last run left off: the cached release list and whatever was installed then. It's only a guess
+    /// until [EldenRingManager::fetch_releases()] and [find_installed()] have had their say, but it's instant.
+    pub fn load_snapshot(&mut self) {
+        let config = config::get();
+        if self.dir.is_none() {
+            self.dir = config.last_install_path.filter(|p| p.is_dir()).map(|p| EldenRingDir::new(&p));
+        }
+        let Ok(dir) = cache_dir() else { return };
+        let cache = Arc::new(CacheSource::new(&dir));
+        let releases = match cache.clone().releases() {
+            Ok(releases) => releases,
+            Err(e) => { debug!("No snapshot of the release list: {}", e); return },
+        };
+        // The cache can't download anything, but whatever we're getting releases from can
+        self.releases = releases.into_iter().map(|r| Release { source: self.source.clone(), ..r }).collect();
+        if self.offline {
+            self.releases.retain(|r| r.downloaded());
+        }
+        self.releases.sort_by(|a,b| b.date.cmp(&a.date));
+        self.updated_at = cache.updated_at();
+        self.current = config.last_installed.and_then(|tag| self.releases.iter().find(|r| r.tag == tag).cloned());
+    }
+
+    /// Remember the install path and installed release for [EldenRingManager::load_snapshot()].
+    pub fn save_snapshot(&self) -> Result<(), ErscomError> {
+        let install_path = self.dir.as_ref().map(|d| d.path().to_path_buf());
+        let installed = self.current.as_ref().map(|r| r.tag.clone());
+        let config = config::get();
+        if config.last_install_path == install_path && config.last_installed == installed { return Ok(()) }
+        config::update(|c| { c.last_install_path = install_path; c.last_installed = installed })
+    }
+
+    /// True if the release list is older than `max_age` and github isn't going to turn us away for asking. The cache
+    /// counts as fresh too, so a second copy of the manager doesn't double up on requests.
+    pub fn refresh_due(&self, max_age: chrono::Duration) -> bool {
+        let now = chrono::Local::now();
+        if let Some(rate_limit) = self.rate_limit {
+            if rate_limit.remaining <= RATE_LIMIT_RESERVE && rate_limit.reset > now {
+                debug!("Holding off on refreshing until the rate limit resets at {}", rate_limit.reset);
+                return false;
+            }
+        }
+        let cached_at = cache_dir().ok().and_then(|dir| CacheSource::new(&dir).updated_at());
+        match self.updated_at.into_iter().chain(cached_at).max() {
+            Some(updated_at) => now - updated_at >= max_age,
+            None => true,
+        }
+    }
+
+    /// The newest release, if it's newer than the one that's installed. Betas are left out unless `include_beta`, and
+    /// ignored releases always are. Nothing is if the installed one is pinned.
+    pub fn update_available(&self, include_beta: bool) -> Option<&Release> {
+        let current = self.current.as_ref()?;
+        let config = config::get();
+        if config.pinned_release.as_ref() == Some(&current.tag) { return None }
+        let newest = self.releases.iter()
+            .filter(|r| (include_beta || !r.prerelease) && !config.ignored_releases.contains(&r.tag))
+            .max_by_key(|r| version_key(&r.tag))?;
+        (version_key(&newest.tag) > version_key(&current.tag)).then_some(newest)
+    }
+
+    /// When the first release newer than the installed one came out, if there's an update (see
+    /// [EldenRingManager::update_available()]).
+    pub fn update_available_since(&self, include_beta: bool) -> Option<chrono::DateTime<chrono::Local>> {
+        self.update_available(include_beta)?;
+        let current = self.current.as_ref()?;
+        let config = config::get();
+        self.releases.iter()
+            .filter(|r| (include_beta || !r.prerelease) && !config.ignored_releases.contains(&r.tag))
+            .filter(|r| version_key(&r.tag) > version_key(&current.tag))
+            .filter_map(|r| chrono::DateTime::parse_from_rfc3339(&r.date).ok())
+            .min()
+            .map(|d| d.with_timezone(&chrono::Local))
+    }
+
     pub fn detect_current_release(&mut self) -> &Option<Release> {
         if let Some(ref installdir) = self.dir {
-            if let Some(release) = self.releases.iter().find(|&release| release.installed(&installdir).unwrap_or(false)) {
-                self.current = Some(release.clone());
+            let found = tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(find_installed(self.releases.clone(), installdir.clone())));
+            if let Some(release) = found {
+                self.current = Some(release);
             }
         }
         &self.current
     }
 
-    pub fn ok(&self) -> Result<(&EldenRingDir, &Release), Box<dyn Error>> {
-        let Some(ref dir) = self.dir else { return Err(format!("Couldn't find Elden Ring directory").into()) };
-        let Some(ref current_release) = self.current else { Err(format!("No coop mod installed"))? };
+    pub fn ok(&self) -> Result<(&EldenRingDir, &Release), ErscomError> {
+        let Some(ref dir) = self.dir else { Err(ErscomError::NotFound(format!("Couldn't find Elden Ring directory")))? };
+        let Some(ref current_release) = self.current else { Err(ErscomError::NotFound(format!("No coop mod installed")))? };
         Ok((dir, current_release))
     }
 
-    fn get_ini_path(&self) -> Result<PathBuf, Box<dyn Error>> {
+    fn get_ini_path(&self) -> Result<PathBuf, ErscomError> {
         let (dir, current_release) = self.ok()?;
         Ok(dir.0.join(current_release.path_for("ini")?))
     }
 
-    pub fn read_settings(&self) -> Result<Ini, Box<dyn Error>> {
-        let ini_file = self.get_ini_path()?;
-        Ok(Ini::read(&ini_file)?)
+    /// Every `.ini` the installed release came with (relative to the game dir), the main settings file first.
+    pub fn ini_paths(&self) -> Result<Vec<PathBuf>, ErscomError> {
+        let (_, current_release) = self.ok()?;
+        let main = current_release.path_for("ini")?;
+        let others = current_release.paths_for("ini")?.into_iter().filter(|p| *p != main);
+        Ok(std::iter::once(main.clone()).chain(others).collect())
+    }
+
+    pub fn read_ini(&self, path: &Path) -> Result<Ini, ErscomError> {
+        let (dir, _) = self.ok()?;
+        Ini::read(&dir.0.join(path))
     }
 
-    pub fn write_settings(&self, settings: &Ini) -> Result<(), Box<dyn Error>> {
+    pub fn write_ini(&self, path: &Path, ini: &Ini) -> Result<(), ErscomError> {
+        let (dir, _) = self.ok()?;
+        ini.write(&dir.0.join(path))
+    }
+
+    pub fn read_settings(&self) -> Result<Ini, ErscomError> {
         let ini_file = self.get_ini_path()?;
-        settings.write(&ini_file)?;
-        Ok(())
+        Ok(Ini::read(&ini_file)?)
     }
 
-    pub fn get_password(&self) -> Result<String, Box<dyn Error>> {
+    pub fn get_password(&self) -> Result<Secret<String>, ErscomError> {
         let ini = self.read_settings()?;
-        Ok(ini.get("PASSWORD", "cooppassword").or(ini.get("SETTINGS", "cooppassword")).ok_or(format!("cooppassword setting not found in {}", self.get_ini_path()?.display()))?.to_string())
+        Ok(Secret::new(password_in(&ini).ok_or(ErscomError::NotFound(format!("cooppassword setting not found in {}", self.get_ini_path()?.display())))?.to_string()))
     }
 
-    pub fn set_password(&self, password: &str) -> Result<(), Box<dyn Error>> {
-        let Some(ref dir) = self.dir else { return Err(format!("Couldn't find Elden Ring directory").into()) };
+    pub fn set_password(&self, password: &Secret<String>) -> Result<(), ErscomError> {
+        let Some(ref dir) = self.dir else { Err(ErscomError::NotFound(format!("Couldn't find Elden Ring directory")))? };
+        let changed = self.get_password().ok().as_ref() != Some(password);
         let old1 = dir.path().join("SeamlessCoop").join("cooppassword.ini");
         let old2 = dir.path().join("SeamlessCoop").join("seamlesscoopsettings.ini");
         let new  = dir.path().join("SeamlessCoop").join("ersc_settings.ini");
@@ -318,33 +812,103 @@ impl EldenRingManager {
         if old1.is_file() { self.set_password_for(password, &old1, "SETTINGS")?; }
         if old2.is_file() { self.set_password_for(password, &old2, "PASSWORD")?; }
         if new.is_file()  { self.set_password_for(password, &new,  "PASSWORD")?; }
-        if !old1.is_file() && !old2.is_file() && !new.is_file() { Err(format!("No ini file to save password in!"))? }
+        if !old1.is_file() && !old2.is_file() && !new.is_file() { Err(ErscomError::NotFound(format!("No ini file to save password in!")))? }
+        if changed { history::record("Changed the co-op password") }
         Ok(())
     }
 
-    pub fn set_password_for(&self, password: &str, ini_file: &Path, section: &str) -> Result<(), Box<dyn Error>> {
+    pub fn set_password_for(&self, password: &Secret<String>, ini_file: &Path, section: &str) -> Result<(), ErscomError> {
         let mut ini = Ini::read(&ini_file)?;
-        ini.set(section, "cooppassword", password);
+        debug!("Setting password in {}", ini_file.display());
+        ini.set(section, "cooppassword", password.expose());
         ini.write(&ini_file)?;
         Ok(())
     }
 
-    pub fn launcher_path(&self) -> Result<PathBuf, Box<dyn Error>> {
+    pub fn launcher_path(&self) -> Result<PathBuf, ErscomError> {
         let (dir, current_release) = self.ok()?;
         Ok(dir.0.join(current_release.path_for("exe")?))
     }
 
 }
 
-// Stolen from https://users.rust-lang.org/t/append-an-additional-extension/23586/12
-fn add_extension(path: &PathBuf, extension: impl AsRef<Path>) -> PathBuf {
-    match path.extension() {
-        Some(ext) => {
-            let mut ext = ext.to_os_string();
-            ext.push(".");
-            ext.push(extension.as_ref());
-            path.with_extension(ext)
+/// The co-op password from the mod's settings. Older versions of the mod kept it in a different section.
+pub fn password_in(ini: &Ini) -> Option<&str> {
+    ini.get("PASSWORD", "cooppassword").or(ini.get("SETTINGS", "cooppassword"))
+}
+
+/// Could the mod use this as its co-op password? It's read out of the ini, so it has to fit on one line and spaces on
+/// the ends get trimmed off.
+pub fn valid_password(password: &str) -> bool {
+    !password.is_empty() && password.trim() == password && !password.chars().any(char::is_control)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn make_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, data) in files {
+            zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
         }
-        None => path.with_extension(extension.as_ref()),
+        zip.finish().unwrap();
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("erscom-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn pre_releases_sort_before_their_release() {
+        let order = ["v1.9.0", "v2.0.0-beta.1", "v2.0.0-beta.2", "v2.0.0-beta.10", "v2.0.0-rc.1", "v2.0.0", "v2.0.1"];
+        for pair in order.windows(2) {
+            assert!(version_key(pair[0]) < version_key(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(version_key("v1.7.10"), version_key("1.7.10"));
+        assert!(version_key("v1.7.10") > version_key("v1.7.9"));
+    }
+
+    #[test]
+    fn entry_path_keeps_non_ascii_names() {
+        let dir = scratch_dir("entry-path");
+        let zip_path = dir.join("release.zip");
+        make_zip(&zip_path, &[("SeamlessCoop/locale/日本語.json", b"{}"), (r"SeamlessCoop\Ñandú.txt", b""), ("../escape.dll", b"")]);
+        let mut zip = zip::ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        let paths: Vec<_> = (0..zip.len()).map(|i| entry_path(&zip.by_index(i).unwrap())).collect();
+        assert_eq!(paths, vec![Some(Path::new("SeamlessCoop").join("locale").join("日本語.json")),
+                               Some(Path::new("SeamlessCoop").join("Ñandú.txt")),
+                               None]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn installs_into_non_ascii_dir() {
+        let dir = scratch_dir("install");
+        let zip_path = dir.join("v0.0.0-test.zip");
+        make_zip(&zip_path, &[("SeamlessCoop/ersc.dll", b"dll"), ("SeamlessCoop/locale/日本語.json", b"{}")]);
+        let release = Release {
+            tag: "v0.0.0-test".to_string(),
+            url: zip_path.to_string_lossy().into_owned(),
+            date: String::new(),
+            changelog: String::new(),
+            prerelease: false,
+            downloads: None,
+            page_url: None,
+            source: Arc::new(crate::source::DirSource::new(&dir)),
+            contents: Arc::new(Mutex::new(None)),
+        };
+        let game = dir.join("Jeux").join("ÉLDEN RING 日本語").join("Game");
+        std::fs::create_dir_all(&game).unwrap();
+        paths::with_data_dir(&dir.join("data"), || release.install(&EldenRingDir::new(&game))).unwrap();
+        assert!(dir.join("data").join("release cache").join("index.json").is_file());
+        assert_eq!(std::fs::read(game.join("SeamlessCoop").join("ersc.dll")).unwrap(), b"dll");
+        assert_eq!(std::fs::read(game.join("SeamlessCoop").join("locale").join("日本語.json")).unwrap(), b"{}");
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,101 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// The mod's README, for explaining settings better than the one-line comments in the ini do. It's kept in the data dir
+// and fetched again once a day; if that fails the old copy is better than nothing.
+
+use std::{path::PathBuf, sync::OnceLock, time::{Duration, SystemTime}};
+
+use tracing::{info, warn};
+
+use crate::{error::ErscomError, paths, source::http_client};
+
+const README_URL: &str = "https://raw.githubusercontent.com/LukeYui/EldenRingSeamlessCoopRelease/main/README.md";
+const MAX_AGE: Duration = Duration::from_secs(24*60*60);
+
+fn path() -> PathBuf {
+    paths::data_dir().join("mod-readme.md")
+}
+
+/// The README, or an empty string if it's never been fetched and can't be now. Loaded once per run.
+fn readme() -> &'static str {
+    static README: OnceLock<String> = OnceLock::new();
+    README.get_or_init(|| {
+        let path = path();
+        let fresh = std::fs::metadata(&path).and_then(|m| m.modified()).ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .map(|age| age < MAX_AGE).unwrap_or(false);
+        if !fresh {
+            match fetch() {
+                Ok(readme) => {
+                    if let Err(e) = std::fs::create_dir_all(paths::data_dir()).and_then(|_| std::fs::write(&path, &readme)) {
+                        warn!("Couldn't cache the mod's README at {}: {}", path.display(), e);
+                    }
+                    return readme;
+                },
+                Err(e) => warn!("Couldn't get the mod's README, using the cached one (if any): {}", e),
+            }
+        }
+        std::fs::read_to_string(&path).unwrap_or_default()
+    })
+}
+
+fn fetch() -> Result<String, ErscomError> {
+    tokio::task::block_in_place(|| {
+        info!("GET {}", README_URL);
+        let resp = http_client()?.get(README_URL).header("User-Agent", "erscom 1.0").send()?;
+        let status = resp.status();
+        if !status.is_success() { Err(ErscomError::GitHubApi { status: status.as_u16(), message: format!("Fetching {} failed", README_URL) })? }
+        Ok(resp.text()?)
+    })
+}
+
+// "enemy_health_scaling" and "Enemy Health Scaling" are the same thing
+fn normalize(text: &str) -> String {
+    text.to_lowercase().replace(['_', '-', '`', '*'], " ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// What the README says about `key` (from the ini section `section`): the part under a heading naming it, or failing
+/// that, the paragraphs that mention it. Blocks on the network the first time, so keep it off the UI thread.
+pub fn section_for(section: &str, key: &str) -> Option<String> {
+    let key = normalize(key);
+    if key.is_empty() { return None }
+    // (heading, paragraphs)
+    let mut parts: Vec<(String, Vec<String>)> = vec![(String::new(), vec![])];
+    for block in readme().replace("\r\n", "\n").split("\n\n") {
+        let block = block.trim();
+        if block.is_empty() { continue }
+        let (first, rest) = block.split_once('\n').unwrap_or((block, ""));
+        if first.starts_with('#') {
+            parts.push((first.trim_start_matches('#').trim().to_string(), vec![]));
+            if !rest.trim().is_empty() { parts.last_mut().unwrap().1.push(rest.trim().to_string()) }
+        } else {
+            parts.last_mut().unwrap().1.push(block.to_string());
+        }
+    }
+
+    if let Some((heading, paragraphs)) = parts.iter().find(|(heading, _)| normalize(heading).contains(&key)) {
+        return Some(format!("{}\n\n{}", heading, paragraphs.join("\n\n")));
+    }
+    // The same key can turn up in more than one place; prefer the part that's about the right ini section
+    let section = normalize(section);
+    let mut mentions: Vec<(&String, &String)> = parts.iter()
+        .flat_map(|(heading, paragraphs)| paragraphs.iter().map(move |p| (heading, p)))
+        .filter(|(_, p)| normalize(p).contains(&key))
+        .collect();
+    mentions.sort_by_key(|(heading, _)| !normalize(heading).contains(&section));
+    let mentions: Vec<&str> = mentions.into_iter().take(3).map(|(_, p)| p.as_str()).collect();
+    (!mentions.is_empty()).then(|| mentions.join("\n\n"))
+}
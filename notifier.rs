@@ -0,0 +1,173 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+use slint::ComponentHandle;
+
+use crate::{config, error, issue, logview, report::UIError, ErrorDialog, FatalDialog};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Warning, // A toast
+    Error,   // A dialog
+    Fatal,   // A dialog that quits when it's dismissed
+}
+
+/// Something to tell the user about. It's just strings so it can be sent over from other threads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notice {
+    pub level: Level,
+    pub message: String,
+    pub hint: Option<&'static str>,
+}
+
+impl Notice {
+    pub fn new(level: Level, error: &(dyn Error + 'static)) -> Notice {
+        Notice { level, message: error.to_string(), hint: error::hint_for(error) }
+    }
+}
+
+enum Dialog {
+    Error(ErrorDialog),
+    Fatal(FatalDialog),
+}
+
+impl Dialog {
+    fn hide(&self) -> Result<(), slint::PlatformError> {
+        match self {
+            Dialog::Error(dialog) => dialog.hide(),
+            Dialog::Fatal(dialog) => dialog.hide(),
+        }
+    }
+}
+
+/// Where all the GUI's errors go. Nothing gets shown from inside the callback that hit the error--it waits for the
+/// event loop to come back around. It also owns the dialogs, and won't open a second one for an error that's already
+/// up (an auto-refresh that fails the same way every time shouldn't bury the user in dialogs).
+#[derive(Default)]
+pub struct UiNotifier {
+    open: Vec<(Notice, Dialog)>,
+}
+
+thread_local! {
+    static NOTIFIER: RefCell<UiNotifier> = RefCell::new(UiNotifier::default());
+}
+
+impl UiNotifier {
+    /// Queue up `notice` to be shown. Can be called from any thread.
+    pub fn post(notice: Notice) {
+        let queued = notice.clone();
+        match slint::invoke_from_event_loop(move || UiNotifier::show(queued)) {
+            Ok(()) => {},
+            // Nothing slint-y has happened yet, so there's no callback to get out of
+            Err(slint::EventLoopError::NoEventLoopProvider) => UiNotifier::show(notice),
+            Err(e) => tracing::error!("Couldn't queue {:?}: {}", notice, e),
+        }
+    }
+
+    /// Like [post()](UiNotifier::post), with a Retry button for errors. `retry` isn't Send, so this only works from the
+    /// GUI thread.
+    pub fn post_with_retry(notice: Notice, retry: Rc<dyn Fn()>) {
+        slint::Timer::single_shot(std::time::Duration::ZERO, move || UiNotifier::show_with_retry(notice, Some(retry)));
+    }
+
+    fn show(notice: Notice) {
+        UiNotifier::show_with_retry(notice, None)
+    }
+
+    fn show_with_retry(notice: Notice, retry: Option<Rc<dyn Fn()>>) {
+        if NOTIFIER.with(|n| n.borrow().open.iter().any(|(open, _)| *open == notice)) {
+            tracing::debug!("Already showing {:?}", notice);
+            return;
+        }
+        let dialog = match notice.level {
+            Level::Warning => { crate::toast(&notice.message); return },
+            Level::Error => error_dialog(&notice, retry).map(Dialog::Error),
+            Level::Fatal => fatal_dialog(&notice).map(Dialog::Fatal),
+        };
+        let Some(dialog) = dialog else { return };
+        NOTIFIER.with(|n| n.borrow_mut().open.push((notice, dialog)));
+    }
+
+    // Take down the dialog for `notice` (if it isn't already) and let it go
+    fn dismiss(notice: &Notice) {
+        let dismissed: Vec<_> = NOTIFIER.with(|n| {
+            let open = &mut n.borrow_mut().open;
+            let (dismissed, keep) = std::mem::take(open).into_iter().partition(|(open, _)| open == notice);
+            *open = keep;
+            dismissed
+        });
+        for (_, dialog) in dismissed {
+            dialog.hide().try_log("hiding dialog")?;
+        }
+    }
+}
+
+fn error_dialog(notice: &Notice, retry: Option<Rc<dyn Fn()>>) -> Option<ErrorDialog> {
+    let dialog = ErrorDialog::new().try_log("creating error dialog")?;
+    dialog.set_can_retry(retry.is_some());
+    dialog.on_retry_clicked({
+        let notice = notice.clone();
+        move || {
+            UiNotifier::dismiss(&notice);
+            if let Some(ref retry) = retry { retry() }
+        }
+    });
+    dialog.set_theme(config::get().theme.color_theme());
+    dialog.set_high_contrast(config::get().high_contrast);
+    dialog.set_error(notice.message.clone().into());
+    dialog.set_hint(notice.hint.unwrap_or("").into());
+    dialog.set_report_preview(issue::body(&notice.message).into());
+    dialog.on_report_issue({
+        let url = issue::url(&notice.message);
+        move || { let _ = webbrowser::open(&url); }
+    });
+    dialog.on_view_logs(logview::show_log_window);
+    dialog.on_ok_clicked({
+        let notice = notice.clone();
+        move || UiNotifier::dismiss(&notice)
+    });
+    dialog.window().on_close_requested({
+        let notice = notice.clone();
+        move || {
+            UiNotifier::dismiss(&notice);
+            slint::CloseRequestResponse::HideWindow
+        }
+    });
+    dialog.show().try_log(&format!("showing error dialog for {}", notice.message))?;
+    Some(dialog)
+}
+
+fn fatal_dialog(notice: &Notice) -> Option<FatalDialog> {
+    let dialog = FatalDialog::new().try_log("creating fatal dialog")?;
+    dialog.set_theme(config::get().theme.color_theme());
+    dialog.set_high_contrast(config::get().high_contrast);
+    dialog.set_error(notice.message.clone().into());
+    dialog.set_hint(notice.hint.unwrap_or("").into());
+    dialog.set_report_preview(issue::body(&notice.message).into());
+    dialog.on_report_issue({
+        let url = issue::url(&notice.message);
+        move || { let _ = webbrowser::open(&url); }
+    });
+    dialog.on_view_logs(logview::show_log_window);
+    dialog.on_abort_clicked(move || {
+        slint::quit_event_loop().try_log("quitting event loop");
+    });
+    dialog.show().try_log(&format!("showing fatal dialog for {}", notice.message))?;
+    Some(dialog)
+}
@@ -0,0 +1,169 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The directory our exe lives in.
+pub fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()?.parent().map(|p| p.to_path_buf())
+}
+
+/// Put next to the exe to turn on portable mode.
+pub const PORTABLE_MARKER: &str = "portable.txt";
+
+static PORTABLE_FLAG: OnceLock<()> = OnceLock::new();
+
+/// Turn on portable mode regardless of [PORTABLE_MARKER] (for `--portable`). Call before anything looks at paths.
+pub fn set_portable() {
+    let _ = PORTABLE_FLAG.set(());
+}
+
+/// In portable mode everything (config, logs, the release cache) is kept next to the exe instead of in the platform's
+/// usual places, for running off a USB stick or out of the game folder.
+pub fn portable() -> bool {
+    PORTABLE_FLAG.get().is_some() || exe_dir().is_some_and(|d| d.join(PORTABLE_MARKER).is_file())
+}
+
+/// Where we keep our own files (`%LOCALAPPDATA%\erscom` on Windows). Next to the exe in portable mode, or if the
+/// platform doesn't have such a thing.
+pub fn data_dir() -> PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = test_data_dir() { return dir }
+    dirs::data_local_dir().filter(|_| !portable()).map(|d| d.join("erscom"))
+        .or_else(exe_dir)
+        .unwrap_or(PathBuf::from("."))
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_DATA_DIR: std::cell::RefCell<Option<PathBuf>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Run `f` with our files (and the release cache) kept in `dir` instead of the real places. Only for this thread, so
+/// tests running alongside each other don't see each other's files.
+#[cfg(test)]
+pub fn with_data_dir<T>(dir: &Path, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<PathBuf>);
+    impl Drop for Restore {
+        fn drop(&mut self) { TEST_DATA_DIR.with(|d| *d.borrow_mut() = self.0.take()) }
+    }
+    let _restore = Restore(TEST_DATA_DIR.with(|d| d.borrow_mut().replace(dir.to_path_buf())));
+    f()
+}
+
+/// The dir [with_data_dir()] is using, if it's in the middle of it.
+#[cfg(test)]
+pub fn test_data_dir() -> Option<PathBuf> {
+    TEST_DATA_DIR.with(|d| d.borrow().clone())
+}
+
+pub fn log_dir() -> PathBuf {
+    data_dir().join("logs")
+}
+
+/// `path` in a form that gets past Windows' 260 character limit (`\\?\C:\...`). Windows doesn't tidy these up at
+/// all, so it gets made absolute and its separators fixed here. Everywhere else it's just `path`.
+#[cfg(target_os = "windows")]
+pub fn long_path(path: &Path) -> PathBuf {
+    use std::path::{Component, Prefix};
+    let Ok(absolute) = std::path::absolute(path) else { return path.to_path_buf() };
+    let mut components = absolute.components();
+    let mut long = match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(drive) => PathBuf::from(format!(r"\\?\{}:\", drive as char)),
+            Prefix::UNC(server, share) => {
+                let mut unc = std::ffi::OsString::from(r"\\?\UNC\");
+                unc.push(server); unc.push(r"\"); unc.push(share); unc.push(r"\");
+                PathBuf::from(unc)
+            },
+            _ => return absolute, // Already \\?\ (or something exotic)
+        },
+        _ => return absolute,
+    };
+    for component in components {
+        match component {
+            Component::Normal(name) => long.push(name),
+            Component::ParentDir => { long.pop(); },
+            _ => {},
+        }
+    }
+    long
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn long_path_prefixes_drive_paths() {
+        assert_eq!(long_path(Path::new(r"C:\Games\ELDEN RING\Game\ersc.dll")), PathBuf::from(r"\\?\C:\Games\ELDEN RING\Game\ersc.dll"));
+        // \\?\ paths don't get tidied up by Windows, so long_path() has to
+        assert_eq!(long_path(Path::new(r"C:/Games/ELDEN RING/Game/../Game/./ersc.dll")), PathBuf::from(r"\\?\C:\Games\ELDEN RING\Game\ersc.dll"));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn long_path_leaves_prefixed_paths_alone() {
+        assert_eq!(long_path(Path::new(r"\\?\C:\Games\ELDEN RING\Game")), PathBuf::from(r"\\?\C:\Games\ELDEN RING\Game"));
+        assert_eq!(long_path(Path::new(r"\\?\UNC\nas\games\Game")), PathBuf::from(r"\\?\UNC\nas\games\Game"));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn long_path_prefixes_unc_paths() {
+        assert_eq!(long_path(Path::new(r"\\nas\games\ELDEN RING\Game")), PathBuf::from(r"\\?\UNC\nas\games\ELDEN RING\Game"));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn long_path_makes_relative_paths_absolute() {
+        let cwd = long_path(&std::env::current_dir().unwrap());
+        assert_eq!(long_path(Path::new(r"Game\SeamlessCoop")), cwd.join("Game").join("SeamlessCoop"));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn long_path_keeps_non_ascii_names() {
+        assert_eq!(long_path(Path::new(r"D:\Jeux\ÉLDEN RING 日本語\Game")), PathBuf::from(r"\\?\D:\Jeux\ÉLDEN RING 日本語\Game"));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn long_path_gets_past_max_path() {
+        let mut dir = std::env::temp_dir().join(format!("erscom-long-path-{}", std::process::id()));
+        let root = dir.clone();
+        while dir.as_os_str().len() < 300 { dir.push("ELDEN RING Seamless Co-op") }
+        std::fs::create_dir_all(long_path(&dir)).unwrap();
+        let file = long_path(&dir.join("ersc.dll"));
+        std::fs::write(&file, b"dll").unwrap();
+        assert_eq!(std::fs::read(&file).unwrap(), b"dll");
+        std::fs::remove_dir_all(long_path(&root)).unwrap();
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn long_path_is_a_no_op_elsewhere() {
+        for path in ["/games/ÉLDEN RING 日本語/Game", "Game/SeamlessCoop", "../Game"] {
+            assert_eq!(long_path(Path::new(path)), PathBuf::from(path));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use slint::ComponentHandle;
+
+use crate::{backup::{self, BackupSchedule}, config::{self, AfterLaunch, AutoRefresh, ManagerChannel, Theme}, i18n, ipc, manage::{self, EldenRingManager}, paths, report::UIError, secret::Secret, shortcut, steam, transfer, MainWindow, PreferencesWindow};
+
+fn labels(labels: impl Iterator<Item=&'static str>) -> slint::ModelRc<slint::SharedString> {
+    Rc::new(slint::VecModel::from(labels.map(slint::SharedString::from).collect::<Vec<_>>())).into()
+}
+
+/// The preferences window for the manager itself (the mod's settings are in SettingsWindow).
+pub fn show_preferences(main_win: slint::Weak<MainWindow>, manager: Rc<RefCell<EldenRingManager>>) {
+    let win = PreferencesWindow::new().try_error()?;
+    let config = config::get();
+    win.set_theme(config.theme.color_theme());
+    win.set_high_contrast(config.high_contrast);
+
+    // Portable mode always uses the default, so don't show one that isn't being used
+    win.set_cache_dir(config.cache_dir.as_ref().filter(|_| !paths::portable()).map(|d| d.to_string_lossy().into_owned()).unwrap_or_default().into());
+    // What you get when it's left blank
+    if let Ok(default) = manage::default_cache_dir() {
+        win.set_default_cache_dir(default.to_string_lossy().into_owned().into());
+    }
+    win.set_portable(paths::portable());
+    win.set_proxy(config.proxy.clone().unwrap_or_default().into());
+    win.set_github_token(config.github_token.as_ref().map(|t| t.expose().as_str()).unwrap_or_default().into());
+    win.set_check_for_updates(config.check_for_updates);
+    win.set_manager_channel_options(labels(ManagerChannel::ALL.iter().map(|c| c.label())));
+    win.set_manager_channel_index(ManagerChannel::ALL.iter().position(|c| *c == config.manager_channel).unwrap_or(0) as i32);
+    win.set_after_launch_options(labels(AfterLaunch::ALL.iter().map(|a| a.label())));
+    win.set_after_launch_index(AfterLaunch::ALL.iter().position(|a| *a == config.after_launch).unwrap_or(0) as i32);
+    win.set_copy_password_on_launch(config.copy_password_on_launch);
+    win.set_allow_password_reveal_setting(config.allow_password_reveal);
+    win.set_watch_clipboard(config.watch_clipboard);
+    win.set_relative_dates(config.relative_dates);
+    win.set_update_before_launch(config.update_before_launch);
+    win.set_auto_refresh_options(labels(AutoRefresh::ALL.iter().map(|a| a.label())));
+    win.set_auto_refresh_index(AutoRefresh::ALL.iter().position(|a| *a == config.auto_refresh).unwrap_or(0) as i32);
+    win.set_backup_options(labels(BackupSchedule::ALL.iter().map(|b| b.label())));
+    win.set_backup_index(BackupSchedule::ALL.iter().position(|b| *b == config.backup_schedule).unwrap_or(0) as i32);
+    // First entry is "follow the OS", the rest line up with i18n::LANGUAGES
+    win.set_language_options(labels(std::iter::once("Automatic").chain(i18n::LANGUAGES.iter().map(|(_, name)| *name))));
+    win.set_language_index(config.language.as_ref().and_then(|l| i18n::LANGUAGES.iter().position(|(code, _)| code == l)).map(|i| i + 1).unwrap_or(0) as i32);
+    win.set_theme_options(labels(Theme::ALL.iter().map(|t| t.label())));
+    win.set_theme_index(Theme::ALL.iter().position(|t| *t == config.theme).unwrap_or(0) as i32);
+    win.set_high_contrast_setting(config.high_contrast);
+    win.set_control_server(config.control_server);
+    win.set_last_backup(match config.last_backup.and_then(|t| chrono::DateTime::from_timestamp(t, 0)) {
+        Some(t) => format!("Last backup: {}", crate::status::date_time(t.with_timezone(&chrono::Local))),
+        None => "No backups yet".to_string(),
+    }.into());
+
+    win.on_create_shortcut(|| {
+        let path = shortcut::create_desktop_shortcut().try_error()?;
+        crate::toast(&format!("Made a shortcut on the desktop: {}", path.file_name().unwrap_or_default().to_string_lossy()));
+    });
+    win.on_add_to_steam(|| {
+        let accounts = steam::register().try_error()?;
+        tracing::info!("Added to Steam for {} account(s)", accounts);
+        crate::toast("Added to Steam. Restart Steam to see it in your library.");
+    });
+    win.on_copy_control_token(|| {
+        let token = ipc::token().try_error()?;
+        arboard::Clipboard::new().and_then(|mut c| c.set_text(token)).try_warn()?;
+        crate::toast("Copied the token. Paste it into the other program's settings.");
+    });
+    win.on_open_backups(|| {
+        let dir = backup::backup_dir();
+        std::fs::create_dir_all(&dir).map_err(crate::error::ErscomError::io(&dir)).try_error()?;
+        crate::open_path(&dir).try_error()?;
+    });
+    win.on_export_state({
+        let (weak_win, manager) = (win.as_weak(), manager.clone());
+        move || {
+            let path = transfer::default_export_path();
+            transfer::export(&manager.borrow(), &path, weak_win.unwrap().get_export_releases()).try_error()?;
+            crate::toast(&format!("Saved {}. Import it in the manager on the other PC.", path.file_name().unwrap_or_default().to_string_lossy()));
+            if let Some(dir) = path.parent() { crate::open_path(dir).try_warn()?; }
+        }
+    });
+    win.on_import_state({
+        let (weak_win, main_win) = (win.as_weak(), main_win.clone());
+        move || {
+            let win = weak_win.unwrap();
+            let path = PathBuf::from(win.get_import_path().trim().trim_matches('"'));
+            let imported = transfer::import(&manager.borrow(), &path).try_error()?;
+            let config = config::get();
+            i18n::apply(config.language.as_deref());
+            if let Some(main_win) = main_win.upgrade() {
+                main_win.set_beta_channel(config.manager_channel == ManagerChannel::Beta);
+                main_win.set_theme(config.theme.color_theme());
+                main_win.set_high_contrast(config.high_contrast);
+                main_win.set_allow_password_reveal(config.allow_password_reveal);
+                crate::app::view(&main_win);
+            }
+            crate::toast(&imported.describe());
+            // Everything in here is out of date now, and saving it would undo the import
+            win.hide().try_log("closing preferences window")?;
+        }
+    });
+    win.on_save({
+        let weak_win = win.as_weak();
+        move || {
+            let win = weak_win.unwrap();
+            let non_empty = |s: slint::SharedString| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+            let proxy = non_empty(win.get_proxy());
+            if let Some(ref proxy) = proxy {
+                reqwest::Proxy::all(proxy).map_err(|e| format!("Bad proxy \"{}\": {}", proxy, e)).try_error()?;
+            }
+            let github_token = non_empty(win.get_github_token());
+            let cache_dir = match paths::portable() {
+                true => config::get().cache_dir, // For when it's not run portably
+                false => non_empty(win.get_cache_dir()).map(PathBuf::from),
+            };
+            let language = (win.get_language_index() as usize).checked_sub(1).and_then(|i| i18n::LANGUAGES.get(i)).map(|(code, _)| code.to_string());
+            i18n::apply(language.as_deref());
+            let theme = Theme::ALL.get(win.get_theme_index() as usize).copied().unwrap_or(Theme::Dark);
+            let high_contrast = win.get_high_contrast_setting();
+            let allow_password_reveal = win.get_allow_password_reveal_setting();
+            let manager_channel = ManagerChannel::ALL.get(win.get_manager_channel_index() as usize).copied().unwrap_or(ManagerChannel::Stable);
+            if let Some(main_win) = main_win.upgrade() {
+                main_win.set_beta_channel(manager_channel == ManagerChannel::Beta);
+                main_win.set_theme(theme.color_theme());
+                main_win.set_high_contrast(high_contrast);
+                main_win.set_allow_password_reveal(allow_password_reveal);
+            }
+            config::update(|c| {
+                c.cache_dir = cache_dir;
+                c.proxy = proxy;
+                c.github_token = github_token.map(Secret::new);
+                c.check_for_updates = win.get_check_for_updates();
+                c.manager_channel = manager_channel;
+                c.after_launch = AfterLaunch::ALL.get(win.get_after_launch_index() as usize).copied().unwrap_or(AfterLaunch::StayOpen);
+                c.copy_password_on_launch = win.get_copy_password_on_launch();
+                c.allow_password_reveal = allow_password_reveal;
+                c.watch_clipboard = win.get_watch_clipboard();
+                c.relative_dates = win.get_relative_dates();
+                c.update_before_launch = win.get_update_before_launch();
+                c.auto_refresh = AutoRefresh::ALL.get(win.get_auto_refresh_index() as usize).copied().unwrap_or(AutoRefresh::Hourly);
+                c.backup_schedule = BackupSchedule::ALL.get(win.get_backup_index() as usize).copied().unwrap_or(BackupSchedule::Never);
+                c.language = language;
+                c.theme = theme;
+                c.high_contrast = high_contrast;
+                c.control_server = win.get_control_server();
+            }).try_error()?;
+            if config::get().control_server { ipc::start().try_error()?; }
+            tracing::info!("Saved preferences (cache dir is now {:?})", manage::cache_dir().ok());
+            if let Some(main_win) = main_win.upgrade() {
+                crate::app::view(&main_win); // For the dates in the release list
+            }
+            win.hide().try_log("closing preferences window")?;
+        }
+    });
+    win.on_close({
+        let weak_win = win.as_weak();
+        move || {
+            weak_win.unwrap().hide().try_log("closing preferences window")?;
+        }
+    });
+    win.show().try_log("showing preferences window")?;
+}
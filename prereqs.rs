@@ -0,0 +1,80 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Things the mod's DLL needs from the system. If they're missing Windows just quietly doesn't load the mod, and the
+// game starts up without co-op and without saying why.
+
+use std::path::PathBuf;
+
+use tracing::{info, warn};
+
+use crate::{error::ErscomError, manage::{self, EldenRingDir}, source};
+
+pub struct Prerequisite {
+    pub name: &'static str,
+    dlls: &'static [&'static str],
+    installer: &'static str,
+}
+
+const PREREQUISITES: &[Prerequisite] = &[
+    Prerequisite { name: "Microsoft Visual C++ Redistributable (x64)",
+                   dlls: &["vcruntime140.dll", "vcruntime140_1.dll", "msvcp140.dll"],
+                   installer: "https://aka.ms/vs/17/release/vc_redist.x64.exe" },
+];
+
+// Where Windows looks for DLLs that aren't next to the .exe
+#[cfg(target_os = "windows")]
+fn system_dir() -> Option<PathBuf> {
+    std::env::var_os("SystemRoot").map(|root| PathBuf::from(root).join("System32"))
+}
+
+// Proton comes with all of this already
+#[cfg(not(target_os = "windows"))]
+fn system_dir() -> Option<PathBuf> {
+    None
+}
+
+/// The prerequisites that aren't installed. Results get logged, since they're the first thing to check when the mod
+/// doesn't load.
+pub fn missing(installdir: &EldenRingDir) -> Vec<&'static Prerequisite> {
+    let Some(system_dir) = system_dir() else { return vec![] };
+    PREREQUISITES.iter().filter(|prereq| {
+        let missing: Vec<_> = prereq.dlls.iter()
+            .filter(|dll| !system_dir.join(dll).is_file() && !installdir.path().join(dll).is_file())
+            .collect();
+        if missing.is_empty() {
+            info!("Prerequisite {}: installed", prereq.name);
+        } else {
+            warn!("Prerequisite {}: missing {:?}", prereq.name, missing);
+        }
+        !missing.is_empty()
+    }).collect()
+}
+
+impl Prerequisite {
+    /// Download the official installer and start it. It asks for administrator access itself.
+    pub fn install(&self) -> Result<(), ErscomError> {
+        let filename = self.installer.rsplit('/').next().unwrap_or("installer.exe");
+        let path = manage::cache_dir()?.join(filename);
+        info!("Downloading {} to {}", self.installer, path.display());
+        let resp = source::http_client()?.get(self.installer).send()?.error_for_status()?;
+        std::fs::create_dir_all(path.parent().unwrap()).map_err(ErscomError::io(path.parent().unwrap()))?;
+        std::fs::write(&path, resp.bytes()?).map_err(ErscomError::io(&path))?;
+        info!("Running {}", path.display());
+        std::process::Command::new(&path).arg("/passive").arg("/norestart").spawn().map_err(ErscomError::io(&path))?;
+        crate::history::record(format!("Started the {} installer", self.name));
+        Ok(())
+    }
+}
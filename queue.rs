@@ -0,0 +1,88 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Installing while the game is running would just fail on the files it has open, so the install waits for the game to
+// exit instead. The queued release is kept in the config, so quitting the manager in the meantime doesn't lose it.
+
+use std::{cell::RefCell, time::Duration};
+
+use tracing::{info, warn};
+
+use crate::{config, history, manage::EldenRingDir};
+
+const POLL_EVERY: Duration = Duration::from_secs(5);
+
+thread_local! {
+    static WATCHER: RefCell<Option<slint::Timer>> = const { RefCell::new(None) };
+}
+
+/// Is Elden Ring running out of `dir`?
+#[cfg(target_os = "windows")]
+pub fn game_running(dir: &EldenRingDir) -> bool {
+    // The Restart Manager counts running an exe as having it open
+    !crate::lock::holders(&dir.path().join("eldenring.exe")).is_empty()
+}
+
+/// Is Elden Ring running? Under Proton it's a wine process with the exe on its command line.
+#[cfg(not(target_os = "windows"))]
+pub fn game_running(_dir: &EldenRingDir) -> bool {
+    let Ok(procs) = std::fs::read_dir("/proc") else { return false };
+    procs.filter_map(|p| p.ok())
+        .filter_map(|p| std::fs::read(p.path().join("cmdline")).ok())
+        .any(|cmdline| cmdline.split(|&b| b == 0).any(|arg| String::from_utf8_lossy(arg).to_lowercase().ends_with("eldenring.exe")))
+}
+
+/// The release waiting for the game to exit, if there is one.
+pub fn pending() -> Option<String> {
+    config::get().pending_install
+}
+
+/// Install `tag` once the game exits (in place of anything already waiting). `install` gets called with it then.
+pub fn enqueue(dir: &EldenRingDir, tag: &str, install: impl Fn(String) + 'static) {
+    info!("Elden Ring is running, installing {} when it exits", tag);
+    if let Err(e) = config::update(|c| c.pending_install = Some(tag.to_string())) {
+        warn!("Couldn't remember to install {} later: {}", tag, e);
+    }
+    history::record(format!("Queued {} to install when Elden Ring exits", tag));
+    watch(dir, install);
+}
+
+/// Stop waiting to install anything.
+pub fn cancel() {
+    WATCHER.with(|w| if let Some(timer) = w.borrow_mut().take() { timer.stop() });
+    if let Some(tag) = pending() {
+        info!("Not installing {} after all", tag);
+        history::record(format!("Took {} off the install queue", tag));
+        config::update(|c| c.pending_install = None).unwrap_or_else(|e| warn!("Couldn't clear the install queue: {}", e));
+    }
+}
+
+/// Keep checking on the game, and call `install` with the queued release once it's not running (and the manager isn't
+/// in the middle of something else, which would turn the install away). Does nothing if nothing's queued.
+pub fn watch(dir: &EldenRingDir, install: impl Fn(String) + 'static) {
+    if pending().is_none() { return }
+    let timer = slint::Timer::default();
+    let dir = dir.clone();
+    let check = move || {
+        if game_running(&dir) || crate::app::busy() { return }
+        WATCHER.with(|w| if let Some(timer) = w.borrow_mut().take() { timer.stop() });
+        let Some(tag) = pending() else { return };
+        config::update(|c| c.pending_install = None).unwrap_or_else(|e| warn!("Couldn't clear the install queue: {}", e));
+        info!("Elden Ring exited, installing {}", tag);
+        install(tag);
+    };
+    timer.start(slint::TimerMode::Repeated, POLL_EVERY, check);
+    WATCHER.with(|w| *w.borrow_mut() = Some(timer));
+}
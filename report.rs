@@ -0,0 +1,135 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+use crate::breaker::Breaker;
+use crate::notifier::{Level, Notice, UiNotifier};
+
+// Process exit codes for CLI mode
+pub const EXIT_OK:    i32 = 0;
+pub const EXIT_ERROR: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+static CLI_MODE: AtomicBool = AtomicBool::new(false);
+static EXIT_CODE: AtomicI32 = AtomicI32::new(EXIT_OK);
+
+/// Switch error reporting from dialogs to stderr. Call this before anything can fail.
+pub fn set_cli_mode() {
+    CLI_MODE.store(true, Ordering::Relaxed);
+}
+
+pub fn cli_mode() -> bool {
+    CLI_MODE.load(Ordering::Relaxed)
+}
+
+/// The exit code the process should finish with, given the errors reported so far.
+pub fn exit_code() -> i32 {
+    EXIT_CODE.load(Ordering::Relaxed)
+}
+
+// A `?` on a Breaker inside a function returning Result turns into ErscomError::Reported. By then the real error has
+// been dealt with.
+fn already_reported(error: &(dyn Error + 'static)) -> bool {
+    matches!(error.downcast_ref::<crate::error::ErscomError>(), Some(crate::error::ErscomError::Reported))
+}
+
+/// Report something that went wrong but doesn't need anyone to do anything about it: a toast in GUI mode, stderr in
+/// CLI mode. It doesn't affect the exit code.
+pub fn warn(error: Box<dyn Error>) {
+    if already_reported(&*error) { return }
+    tracing::warn!("{}", error);
+    if cli_mode() {
+        eprintln!("Warning: {}", error);
+    } else {
+        UiNotifier::post(Notice::new(Level::Warning, &*error));
+    }
+}
+
+/// Report a recoverable error: a dialog in GUI mode, stderr (and a failing exit code) in CLI mode.
+pub fn error(error: Box<dyn Error>) {
+    if already_reported(&*error) { return }
+    tracing::error!("{}", error);
+    if cli_mode() {
+        eprintln!("Error: {}", error);
+        if let Some(hint) = crate::error::hint_for(&*error) { eprintln!("{}", hint) }
+        EXIT_CODE.store(EXIT_ERROR, Ordering::Relaxed);
+    } else {
+        UiNotifier::post(Notice::new(Level::Error, &*error));
+    }
+}
+
+/// Like [error()], but the dialog gets a Retry button that calls `retry`. Only for the GUI thread.
+pub fn error_with_retry(error: Box<dyn Error>, retry: impl Fn() + 'static) {
+    if already_reported(&*error) { return }
+    if cli_mode() { return self::error(error) } // Nobody to click it
+    tracing::error!("{}", error);
+    UiNotifier::post_with_retry(Notice::new(Level::Error, &*error), std::rc::Rc::new(retry));
+}
+
+/// Report an unrecoverable error. In CLI mode there's nobody to click "Abort", so we just exit.
+pub fn fatal(error: Box<dyn Error>) {
+    if already_reported(&*error) { return }
+    tracing::error!("Fatal: {}", error);
+    if cli_mode() {
+        eprintln!("Fatal: {}", error);
+        if let Some(hint) = crate::error::hint_for(&*error) { eprintln!("{}", hint) }
+        std::process::exit(EXIT_ERROR);
+    } else {
+        UiNotifier::post(Notice::new(Level::Fatal, &*error));
+    }
+}
+
+/// Convenience functions added to Result to report errors (via [error()]/[fatal()]) or log them (eating the error
+/// so you can use `?` in a function that returns `()`)
+pub trait UIError<T> {
+    fn try_log(self, context: &str) -> Breaker<T>;
+    fn try_warn(self) -> Breaker<T>;
+    fn try_error(self) -> Breaker<T>;
+    fn try_fatal(self) -> Breaker<T>;
+}
+
+impl<T,E> UIError<T> for Result<T, E>
+where E: std::fmt::Display,
+      E: Into<Box<dyn Error>> {
+    fn try_log(self, context: &str) -> Breaker<T> {
+        match self {
+            Ok(t) => Breaker::cont(t),
+            Err(e) => { tracing::warn!("Error while {context}: {e}"); Breaker::brk() },
+        }
+    }
+
+    fn try_warn(self) -> Breaker<T> {
+        match self {
+            Ok(t) => Breaker::cont(t),
+            Err(e) => { warn(e.into()); Breaker::brk() },
+        }
+    }
+
+    fn try_error(self) -> Breaker<T> {
+        match self {
+            Ok(t) => Breaker::cont(t),
+            Err(e) => { error(e.into()); Breaker::brk() },
+        }
+    }
+
+    fn try_fatal(self) -> Breaker<T> {
+        match self {
+            Ok(t) => Breaker::cont(t),
+            Err(e) => { fatal(e.into()); Breaker::brk() },
+        }
+    }
+}
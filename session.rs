@@ -0,0 +1,301 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Checking that everyone in a session has the same mod. The host copies their version string and sends it around
+// (or everyone sends theirs to the host) and pasting them in here says whether they match.
+//
+// The host can also send their whole session info (see [SessionInfo]): the version plus the scaling settings and a
+// check on the co-op password. Pasting that in says exactly what's different and can change this setup to match.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+use slint::ComponentHandle;
+use tracing::info;
+
+use crate::{error::ErscomError, history, ini::{Entry, Ini}, manage::{self, EldenRingManager}, paths, report::UIError, SessionCheckWindow};
+
+// Session info starts with this, so it can be picked out of a chat message
+const INFO_PREFIX: &str = "ERSC-SESSION";
+// Everyone has to agree on these or the game scales differently for each of them
+const SCALING_SECTION: &str = "scaling";
+
+/// What's installed, exactly: the tag plus a checksum of the mod's DLL, so a botched or hand-edited install of the
+/// right version still shows up as different.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Version {
+    pub tag: String,
+    pub checksum: Option<u32>,
+}
+
+impl Version {
+    pub fn installed(manager: &EldenRingManager) -> Option<Version> {
+        let (dir, current) = manager.ok().ok()?;
+        let checksum = current.path_for("dll").ok()
+            .and_then(|dll| std::fs::read(paths::long_path(&dir.path().join(dll))).ok())
+            .map(|bytes| crc32fast::hash(&bytes));
+        Some(Version { tag: current.tag.clone(), checksum })
+    }
+
+    /// Pull a version out of whatever someone pasted ("Bob: ERSC 1.7.9 (0a1b2c3d)"). A bare tag counts too, since
+    /// people will just type it.
+    pub fn parse(text: &str) -> Option<Version> {
+        static RE: OnceLock<regex::Regex> = OnceLock::new();
+        let re = RE.get_or_init(|| regex::Regex::new(r"(?i)(?:^|ERSC\s+)v?(\S+)(?:\s+\(([0-9a-f]{8})\))?$").unwrap());
+        let captures = re.captures(text.trim())?;
+        Some(Version { tag: captures[1].to_string(),
+                       checksum: captures.get(2).and_then(|c| u32::from_str_radix(c.as_str(), 16).ok()) })
+    }
+
+    /// How `self` compares to `theirs`, in words. `None` means they match.
+    pub fn mismatch(&self, theirs: &Version) -> Option<String> {
+        let tag = |t: &str| t.trim_start_matches(['v', 'V']).to_lowercase();
+        if tag(&self.tag) != tag(&theirs.tag) {
+            return Some(format!("different version (you have {})", self.tag));
+        }
+        match (self.checksum, theirs.checksum) {
+            (Some(mine), Some(theirs)) if mine != theirs => Some("same version, but different mod files. One of you should reinstall it.".to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.checksum {
+            Some(checksum) => write!(f, "ERSC {} ({:08x})", self.tag, checksum),
+            None => write!(f, "ERSC {}", self.tag),
+        }
+    }
+}
+
+/// Everything about a setup that has to match for a session to work, for the host to send around. The scaling
+/// settings are there in full (not just a hash of them) so the differences can be spelled out and fixed. The password
+/// is only a short hash, enough to tell whether it's the same without giving it away.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub tag: String,
+    pub dll: Option<String>, // The checksum from [Version], in hex
+    pub scaling: BTreeMap<String, String>,
+    pub scaling_hash: String,
+    pub password: Option<String>,
+}
+
+/// Something about a setup that doesn't match the host's.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    Version { theirs: String },
+    Files { tag: String },
+    Setting { key: String, mine: Option<String>, theirs: String },
+    Password,
+}
+
+impl Difference {
+    pub fn describe(&self) -> String {
+        match self {
+            Difference::Version { theirs } => format!("Install {} (the host's version)", theirs),
+            Difference::Files { tag } => format!("Reinstall {}: the mod's files don't match the host's", tag),
+            Difference::Setting { key, mine: Some(mine), theirs } => format!("Change {} from {} to {}", key, mine, theirs),
+            Difference::Setting { key, mine: None, theirs } => format!("Set {} to {}", key, theirs),
+            Difference::Password => "Your co-op password is different. Ask the host for theirs.".to_string(),
+        }
+    }
+
+    /// Can "Make My Setup Match" fix it?
+    pub fn fixable(&self) -> bool {
+        !matches!(self, Difference::Password)
+    }
+}
+
+// A short hash, so a password can be compared without being sent around
+fn password_check(password: &str) -> String {
+    format!("{:04x}", crc32fast::hash(password.as_bytes()) & 0xffff)
+}
+
+fn scaling_settings(ini: &Ini) -> BTreeMap<String, String> {
+    ini.sections().filter(|s| s.name().eq_ignore_ascii_case(SCALING_SECTION))
+        .flat_map(|s| s.entries())
+        .filter_map(|e| match e { Entry::KV { key, value, .. } => Some((key.clone(), value.clone())), _ => None })
+        .collect()
+}
+
+fn scaling_hash(scaling: &BTreeMap<String, String>) -> String {
+    let text: String = scaling.iter().map(|(k, v)| format!("{}={}\n", k, v)).collect();
+    format!("{:08x}", crc32fast::hash(text.as_bytes()))
+}
+
+impl SessionInfo {
+    pub fn installed(manager: &EldenRingManager) -> Result<SessionInfo, ErscomError> {
+        let version = Version::installed(manager).ok_or("Install the mod first")?;
+        let ini = manager.read_settings()?;
+        let scaling = scaling_settings(&ini);
+        Ok(SessionInfo { tag: version.tag,
+                         dll: version.checksum.map(|c| format!("{:08x}", c)),
+                         scaling_hash: scaling_hash(&scaling),
+                         scaling,
+                         password: manage::password_in(&ini).filter(|p| !p.is_empty()).map(password_check) })
+    }
+
+    /// One line, to paste into a chat.
+    pub fn export(&self) -> String {
+        format!("{} {}", INFO_PREFIX, serde_json::to_string(self).unwrap())
+    }
+
+    /// Session info from somewhere in `text`, if there is any.
+    pub fn parse(text: &str) -> Option<SessionInfo> {
+        let (_, json) = text.split_once(INFO_PREFIX)?;
+        // It's followed by whatever else was in the message
+        let info: SessionInfo = serde_json::Deserializer::from_str(json.trim()).into_iter().next()?.ok()?;
+        // Numbers edited by hand (or mangled by a chat app) shouldn't pass for the host's
+        (scaling_hash(&info.scaling) == info.scaling_hash).then_some(info)
+    }
+
+    /// What this setup would have to change to match `host`'s.
+    pub fn differences(&self, host: &SessionInfo) -> Vec<Difference> {
+        let mut differences = vec![];
+        let (mine, theirs) = (Version { tag: self.tag.clone(), checksum: None }, Version { tag: host.tag.clone(), checksum: None });
+        if mine.mismatch(&theirs).is_some() {
+            differences.push(Difference::Version { theirs: host.tag.clone() });
+        } else if self.dll.is_some() && host.dll.is_some() && self.dll != host.dll {
+            differences.push(Difference::Files { tag: host.tag.clone() });
+        }
+        if self.scaling_hash != host.scaling_hash {
+            for (key, value) in host.scaling.iter() {
+                let mine = self.scaling.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v.clone());
+                if mine.as_ref() != Some(value) {
+                    differences.push(Difference::Setting { key: key.clone(), mine, theirs: value.clone() });
+                }
+            }
+        }
+        if host.password.is_some() && self.password != host.password {
+            differences.push(Difference::Password);
+        }
+        differences
+    }
+}
+
+/// A verdict for each line of `pasted`, and whether they all match. Session info gets a list of what to change
+/// instead, and is returned so it can be matched.
+fn compare(mine: &Version, my_info: Option<&SessionInfo>, pasted: &str) -> (String, bool, Option<SessionInfo>) {
+    if let Some(host) = SessionInfo::parse(pasted) {
+        let Some(my_info) = my_info else {
+            return (format!("✘ Couldn't read your settings to compare with the host's {}", host.tag), false, None);
+        };
+        let differences = my_info.differences(&host);
+        if differences.is_empty() { return (format!("✔ Your setup matches the host's ({})", host.tag), true, None) }
+        let verdict = differences.iter().map(|d| format!("✘ {}", d.describe())).collect::<Vec<_>>().join("\n");
+        return (verdict, false, differences.iter().any(Difference::fixable).then_some(host));
+    }
+    let mut all_match = true;
+    let verdicts: Vec<String> = pasted.lines().map(str::trim).filter(|l| !l.is_empty()).map(|line| {
+        let verdict = match Version::parse(line) {
+            None => Err("doesn't look like a version".to_string()),
+            Some(theirs) => match mine.mismatch(&theirs) { None => Ok(()), Some(why) => Err(why) },
+        };
+        match verdict {
+            Ok(()) => format!("✔ {}: matches", line),
+            Err(why) => { all_match = false; format!("✘ {}: {}", line, why) },
+        }
+    }).collect();
+    (verdicts.join("\n"), all_match, None)
+}
+
+// Change the scaling settings to the host's, then get the host's version installed (which leaves settings alone).
+fn match_setup(manager: &EldenRingManager, host: &SessionInfo) -> Result<(), ErscomError> {
+    let mine = SessionInfo::installed(manager)?;
+    let differences = mine.differences(host);
+    let settings: Vec<(&String, &String)> = differences.iter().filter_map(|d| match d {
+        Difference::Setting { key, theirs, .. } => Some((key, theirs)),
+        _ => None,
+    }).collect();
+    if !settings.is_empty() {
+        let path = manager.ini_paths()?.into_iter().next().ok_or("No settings file")?;
+        let mut ini = manager.read_ini(&path)?;
+        let section = ini.sections().find(|s| s.name().eq_ignore_ascii_case(SCALING_SECTION)).map(|s| s.name().to_string())
+            .unwrap_or(SCALING_SECTION.to_uppercase());
+        for (key, value) in settings.iter() {
+            info!("Matching the host's {}: {} = {}", section, key, value);
+            ini.set(&section, key, value);
+        }
+        manager.write_ini(&path, &ini)?;
+        history::record(format!("Changed {} scaling settings to match the host's", settings.len()));
+    }
+    for difference in differences.iter() {
+        match difference {
+            Difference::Version { theirs: tag } | Difference::Files { tag } => {
+                if !crate::confirm_install_tag(tag) {
+                    Err(format!("{} isn't in the list of releases. Refresh, or turn on betas if it's one of those.", tag))?
+                }
+            },
+            Difference::Setting { .. } | Difference::Password => {},
+        }
+    }
+    Ok(())
+}
+
+pub fn show_session_check(manager_ref: Rc<RefCell<EldenRingManager>>) {
+    let Some(mine) = Version::installed(&manager_ref.borrow()) else {
+        return crate::toast("Install the mod first");
+    };
+    let win = SessionCheckWindow::new().try_error()?;
+    win.set_theme(crate::config::get().theme.color_theme());
+    win.set_high_contrast(crate::config::get().high_contrast);
+    win.set_my_version(mine.to_string().into());
+    win.on_copy({
+        let mine = mine.clone();
+        move || {
+            arboard::Clipboard::new().and_then(|mut c| c.set_text(mine.to_string())).try_warn()?;
+            crate::toast("Copied your version. Send it to the people you're playing with.");
+        }
+    });
+    win.on_copy_session_info({
+        let manager_ref = manager_ref.clone();
+        move || {
+            let info = SessionInfo::installed(&manager_ref.borrow()).try_error()?;
+            arboard::Clipboard::new().and_then(|mut c| c.set_text(info.export())).try_warn()?;
+            crate::toast("Copied your session info. Send it to the people you're playing with.");
+        }
+    });
+    let host: Rc<RefCell<Option<SessionInfo>>> = Rc::new(RefCell::new(None));
+    win.on_compare({
+        let (weak_win, manager_ref, host) = (win.as_weak(), manager_ref.clone(), host.clone());
+        move |pasted| {
+            let win = weak_win.unwrap();
+            let my_info = SessionInfo::installed(&manager_ref.borrow()).ok();
+            let (verdict, all_match, matchable) = compare(&mine, my_info.as_ref(), &pasted);
+            win.set_verdict(verdict.into());
+            win.set_all_match(all_match);
+            win.set_can_match(matchable.is_some());
+            *host.borrow_mut() = matchable;
+        }
+    });
+    win.on_match_setup({
+        let weak_win = win.as_weak();
+        move || {
+            let Some(ref host) = *host.borrow() else { return };
+            match_setup(&manager_ref.borrow(), host).try_error()?;
+            let win = weak_win.unwrap();
+            // Anything left is waiting on the install, or can't be fixed from here
+            win.invoke_compare(win.get_pasted());
+        }
+    });
+    win.on_close({
+        let weak_win = win.as_weak();
+        move || {
+            weak_win.unwrap().hide().try_log("closing session check window")?;
+        }
+    });
+    win.show().try_log("showing session check window")?;
+}
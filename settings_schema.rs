@@ -0,0 +1,101 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::manage::version_key;
+
+/// How a setting in the mod's ini should be edited.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Boolean,
+    Number,
+    String,
+    Password,
+    /// One of a fixed set of `(value, label)`s.
+    Choice(Vec<(String, String)>),
+    /// A percentage, snapped to multiples of `step`.
+    Percent { min: u32, max: u32, step: u32 },
+}
+
+// The mod's language override takes the name of one of the files in SeamlessCoop/locale. These are the ones it has
+// shipped, with names people will recognize.
+const LANGUAGES_1_5: &[(&str, &str)] = &[
+    ("",           "Same as the game"),
+    ("english",    "English"),
+    ("french",     "Français"),
+    ("german",     "Deutsch"),
+    ("italian",    "Italiano"),
+    ("spanish",    "Español (España)"),
+    ("latam",      "Español (Latinoamérica)"),
+    ("brazilian",  "Português (Brasil)"),
+    ("polish",     "Polski"),
+    ("russian",    "Русский"),
+    ("japanese",   "日本語"),
+    ("koreana",    "한국어"),
+    ("schinese",   "简体中文"),
+    ("tchinese",   "繁體中文"),
+    ("thai",       "ไทย"),
+    ("arabic",     "العربية"),
+];
+
+struct Override {
+    since: &'static str, // First mod version this applies to. The newest matching entry wins.
+    section: &'static str,
+    key: Option<&'static str>, // None for every key in the section
+    edit: Edit,
+}
+
+enum Edit {
+    Choices(&'static [(&'static str, &'static str)]),
+    Percent { max: u32, step: u32 }, // From 0
+}
+
+// Settings where guessing from the ini comments isn't good enough. Add a new entry (rather than editing an old one)
+// when the mod changes a setting, so older versions keep working.
+const OVERRIDES: &[Override] = &[
+    Override { since: "1.0.0", section: "scaling",  key: None, edit: Edit::Percent { max: 200, step: 5 } },
+    Override { since: "1.5.0", section: "language", key: None, edit: Edit::Choices(LANGUAGES_1_5) },
+];
+
+/// Figure out how to edit `section`/`key`, given its ini comment (`help`) and the installed mod version.
+pub fn kind(mod_version: Option<&str>, section: &str, key: &str, help: &str, value: &str) -> Kind {
+    let version = mod_version.map(version_key);
+    let known = OVERRIDES.iter()
+        .filter(|o| o.section.eq_ignore_ascii_case(section) && o.key.map(|k| k.eq_ignore_ascii_case(key)).unwrap_or(true))
+        .filter(|o| version.as_ref().map(|v| *v >= version_key(o.since)).unwrap_or(true))
+        .max_by_key(|o| version_key(o.since));
+    match known.map(|o| &o.edit) {
+        Some(Edit::Choices(choices)) => {
+            let mut choices: Vec<(String, String)> = choices.iter().map(|(v, l)| (v.to_string(), l.to_string())).collect();
+            if !choices.iter().any(|(v, _)| v.eq_ignore_ascii_case(value)) {
+                choices.push((value.to_string(), value.to_string())); // Don't lose something we don't know about
+            }
+            return Kind::Choice(choices);
+        },
+        // Something that isn't a plain number would get lost by a slider
+        Some(&Edit::Percent { max, step }) => if let Ok(current) = value.trim().parse::<u32>() {
+            return Kind::Percent { min: 0, max: max.max(current), step };
+        },
+        None => {},
+    }
+    // There's no real good way to do this as there aren't really enough solid hints in the ini comments to get this exactly right.
+    if key.contains("password") { Kind::Password }
+    else if help.contains('%') { Kind::Number }
+    else if section.to_lowercase() == "save" ||
+        section.to_lowercase() == "language" { Kind::String }
+    else if help.contains("2 =") { Kind::Number } // Maybe try to parse this and make a menuselect out of it?
+    else if help.contains("1 =") { Kind::Boolean } // Maybe try to parse this and make a menuselect out of it?
+    else if section.to_lowercase() == "gameplay" { Kind::Boolean }
+    else { Kind::String }
+}
@@ -0,0 +1,130 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Getting settings from one machine to another without retyping them: a QR code for phones, or a secret gist for the
+// whole group to import from (the host re-uploads to the same gist, so the link stays the same).
+
+use serde::Deserialize;
+use slint::{Image, Rgb8Pixel, SharedPixelBuffer};
+use tracing::info;
+
+use crate::error::ErscomError;
+use crate::ini::Ini;
+use crate::{manage, source};
+
+const GIST_FILE: &str = "ersc_settings.ini";
+
+const PIXELS_PER_MODULE: u32 = 8;
+const QUIET_ZONE: u32 = 4; // In modules. Scanners want a blank border.
+
+/// What gets shared: all the settings, or just the co-op password.
+pub fn text(ini: &Ini, password_only: bool) -> String {
+    match password_only {
+        true => manage::password_in(ini).unwrap_or("").to_string(),
+        false => ini.export(),
+    }
+}
+
+/// `text` as a QR code, black on white no matter what the theme is (phones have trouble with inverted ones).
+pub fn qr_code(text: &str) -> Result<Image, ErscomError> {
+    let code = qrcode::QrCode::new(text.as_bytes()).map_err(|e| format!("Couldn't make a QR code ({} bytes): {}", text.len(), e))?;
+    let modules = code.width() as u32;
+    let colors = code.to_colors();
+    let size = (modules + QUIET_ZONE * 2) * PIXELS_PER_MODULE;
+    let mut buffer = SharedPixelBuffer::<Rgb8Pixel>::new(size, size);
+    for (i, pixel) in buffer.make_mut_slice().iter_mut().enumerate() {
+        let (x, y) = (i as u32 % size / PIXELS_PER_MODULE, i as u32 / size / PIXELS_PER_MODULE);
+        let dark = (QUIET_ZONE..modules + QUIET_ZONE).contains(&x) && (QUIET_ZONE..modules + QUIET_ZONE).contains(&y)
+            && colors[((y - QUIET_ZONE) * modules + (x - QUIET_ZONE)) as usize] == qrcode::Color::Dark;
+        *pixel = if dark { Rgb8Pixel::new(0, 0, 0) } else { Rgb8Pixel::new(255, 255, 255) };
+    }
+    Ok(Image::from_rgb8(buffer))
+}
+
+#[derive(Deserialize)]
+struct Gist {
+    id: String,
+    owner: GistOwner,
+}
+
+#[derive(Deserialize)]
+struct GistOwner {
+    login: String,
+}
+
+/// Put `text` in a secret gist belonging to whoever `token` is for. The gist from last time (`gist_id`) gets updated if
+/// it's still around. Returns the gist's id and a link that always gets its newest version.
+pub fn upload(text: &str, token: &str, gist_id: Option<&str>) -> Result<(String, String), ErscomError> {
+    tokio::task::block_in_place(move || {
+        let client = source::http_client()?;
+        let body = serde_json::json!({
+            "description": "Elden Ring Seamless Co-op settings",
+            "public": false,
+            "files": { GIST_FILE: { "content": text } },
+        });
+        let send = |method: reqwest::Method, url: &str| -> Result<reqwest::blocking::Response, ErscomError> {
+            info!("{} {}", method, url);
+            let resp = client.request(method.clone(), url)
+                .header("Accept", "application/vnd.github+json")
+                .header("User-Agent", "erscom 1.0")
+                .bearer_auth(token)
+                .json(&body)
+                .send()?;
+            info!("{} {} -> {}", method, url, resp.status());
+            Ok(resp)
+        };
+        let resp = match gist_id {
+            Some(id) => match send(reqwest::Method::PATCH, &format!("https://api.github.com/gists/{}", id))? {
+                resp if resp.status() == reqwest::StatusCode::NOT_FOUND => send(reqwest::Method::POST, "https://api.github.com/gists")?, // It got deleted
+                resp => resp,
+            },
+            None => send(reqwest::Method::POST, "https://api.github.com/gists")?,
+        };
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(ErscomError::GitHubApi { status: status.as_u16(), message: resp.text().unwrap_or(format!("Got status {}", status)) });
+        }
+        let gist: Gist = resp.json()?;
+        let url = format!("https://gist.githubusercontent.com/{}/{}/raw/{}", gist.owner.login, gist.id, GIST_FILE);
+        Ok((gist.id, url))
+    })
+}
+
+/// The settings at `url`, which can be anything that serves up an [Ini::export()]. A gist's page works too.
+pub fn download(url: &str) -> Result<Ini, ErscomError> {
+    let url = raw_url(url);
+    tokio::task::block_in_place(|| {
+        let client = source::http_client()?;
+        info!("GET {}", url);
+        let resp = client.get(&url).header("User-Agent", "erscom 1.0").send()?;
+        let status = resp.status();
+        info!("GET {} -> {}", url, status);
+        if !status.is_success() { Err(format!("Couldn't get settings from {} (status {})", url, status))? }
+        let ini = Ini::parse(&resp.text()?);
+        if ini.export().is_empty() {
+            Err(format!("There aren't any settings at {}", url))?
+        }
+        Ok(ini)
+    })
+}
+
+// https://gist.github.com/<user>/<id> is the page, with /raw on the end it's the newest version of the text
+fn raw_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    if url.starts_with("https://gist.github.com/") && !url.contains("/raw") {
+        return format!("{}/raw", url);
+    }
+    url.to_string()
+}
@@ -0,0 +1,79 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// A desktop shortcut that runs us with `--launch`, so the manager can stand in for the game's own icon.
+
+use std::path::PathBuf;
+
+use tracing::info;
+
+use crate::error::ErscomError;
+
+const NAME: &str = "Elden Ring Seamless Co-op";
+
+/// Make (or replace) the shortcut on the desktop. Returns where it went.
+pub fn create_desktop_shortcut() -> Result<PathBuf, ErscomError> {
+    let desktop = dirs::desktop_dir().ok_or("Couldn't find the desktop folder")?;
+    let exe = std::env::current_exe().map_err(|e| format!("Couldn't find my .exe: {}", e))?;
+    let path = imp::create(&desktop, &exe)?;
+    info!("Created shortcut {}", path.display());
+    Ok(path)
+}
+
+#[cfg(target_os = "windows")]
+mod imp {
+    use std::path::{Path, PathBuf};
+    use std::os::windows::process::CommandExt;
+
+    use super::*;
+
+    // Writing a .lnk means COM, which powershell already knows how to do
+    pub fn create(desktop: &Path, exe: &Path) -> Result<PathBuf, ErscomError> {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        let path = desktop.join(format!("{}.lnk", NAME));
+        let quote = |p: &Path| format!("'{}'", p.to_string_lossy().replace('\'', "''"));
+        let script = format!("$s = (New-Object -ComObject WScript.Shell).CreateShortcut({lnk}); \
+                              $s.TargetPath = {exe}; $s.Arguments = '--launch'; $s.WorkingDirectory = {dir}; \
+                              $s.IconLocation = {exe} + ',0'; $s.Save()",
+                             lnk = quote(&path), exe = quote(exe), dir = quote(exe.parent().unwrap_or(exe)));
+        let output = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output().map_err(|e| format!("Couldn't run powershell to make the shortcut: {}", e))?;
+        if !output.status.success() {
+            Err(format!("Couldn't make the shortcut: {}", String::from_utf8_lossy(&output.stderr).trim()))?
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod imp {
+    use std::path::{Path, PathBuf};
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    // A freedesktop.org .desktop file
+    pub fn create(desktop: &Path, exe: &Path) -> Result<PathBuf, ErscomError> {
+        let path = desktop.join("erscom-launch.desktop");
+        let exec = format!("\"{}\" --launch", exe.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\""));
+        let contents = format!("[Desktop Entry]\nType=Application\nName={}\nExec={}\nTerminal=false\n", NAME, exec);
+        std::fs::write(&path, contents).map_err(ErscomError::io(&path))?;
+        // Most desktops won't run it otherwise
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).map_err(ErscomError::io(&path))?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,388 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{fs::File, io::{Read, Write}, path::{Path, PathBuf}, sync::{Arc, Mutex, atomic::{AtomicBool, AtomicU64, Ordering}}};
+
+use serde::{Serialize, Deserialize};
+
+use tracing::{debug, info, warn};
+
+use crate::error::ErscomError;
+use crate::manage::Release;
+use crate::zipcache::ZipCache;
+
+/// Somewhere we can get a list of mod releases and their zip files from.
+pub trait ReleaseSource: std::fmt::Debug + Send + Sync {
+    /// All the releases this source knows about, in no particular order.
+    fn releases(self: Arc<Self>) -> Result<Vec<Release>, ErscomError>;
+    /// Write the release's zip file to `dest`, keeping `progress` up to date.
+    fn fetch_asset(&self, release: &Release, dest: &Path, progress: &Progress) -> Result<(), ErscomError>;
+    /// True if this source only has what's already on disk.
+    fn offline(&self) -> bool { false }
+    /// How fresh the list from [ReleaseSource::releases()] is.
+    fn updated_at(&self) -> Option<chrono::DateTime<chrono::Local>> { Some(chrono::Local::now()) }
+    /// The api rate limit as of the last request, for sources that have one.
+    fn rate_limit(&self) -> Option<RateLimit> { None }
+    /// Where the releases come from, for showing people.
+    fn location(&self) -> String;
+}
+
+/// How far along a download is, and a way to stop it partway. Shared between the download and whatever's watching it.
+#[derive(Debug, Default)]
+pub struct Progress {
+    done: AtomicU64,
+    total: AtomicU64, // 0 when we don't know
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    /// How much of the download has finished, if we know how big it is.
+    pub fn fraction(&self) -> Option<f32> {
+        match self.total.load(Ordering::Relaxed) {
+            0 => None,
+            total => Some(self.done.load(Ordering::Relaxed) as f32 / total as f32),
+        }
+    }
+
+    pub fn done(&self) -> u64 {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// Make the download stop with [ErscomError::Cancelled] the next chance it gets.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed)
+    }
+}
+
+/// Copy all of `from` (`from_path`, for errors) into a new file at `dest`.
+fn copy_with_progress(mut from: impl Read, from_path: &Path, dest: &Path, total: Option<u64>, progress: &Progress) -> Result<(), ErscomError> {
+    progress.total.store(total.unwrap_or(0), Ordering::Relaxed);
+    progress.done.store(0, Ordering::Relaxed);
+    let mut file = File::create(dest).map_err(ErscomError::io(dest))?;
+    let mut buf = vec![0; 64*1024];
+    loop {
+        if progress.cancelled.load(Ordering::Relaxed) { Err(ErscomError::Cancelled)? }
+        let n = from.read(&mut buf).map_err(ErscomError::io(from_path))?;
+        if n == 0 { break }
+        file.write_all(&buf[..n]).map_err(ErscomError::io(dest))?;
+        progress.done.fetch_add(n as u64, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// From github's `x-ratelimit-*` headers.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset: chrono::DateTime<chrono::Local>,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<RateLimit> {
+        let header = |name: &str| headers.get(name)?.to_str().ok()?.parse::<i64>().ok();
+        Some(RateLimit {
+            limit: header("x-ratelimit-limit")? as u32,
+            remaining: header("x-ratelimit-remaining")? as u32,
+            reset: chrono::DateTime::from_timestamp(header("x-ratelimit-reset")?, 0)?.with_timezone(&chrono::Local),
+        })
+    }
+}
+
+// These are the parts of the github release api that we care about.
+// See https://docs.github.com/en/rest/releases/releases
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    published_at: String,
+    body: String,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    html_url: Option<String>,
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct GithubAsset {
+    browser_download_url: String,
+    #[serde(default)]
+    download_count: Option<u64>,
+}
+
+impl GithubRelease {
+    fn into_release(self, source: Arc<dyn ReleaseSource>) -> Option<Release> {
+        Some(Release {
+            url: self.assets.first()?.browser_download_url.clone(),
+            downloads: self.assets.first()?.download_count,
+            tag: self.tag_name,
+            date: self.published_at,
+            changelog: self.body,
+            prerelease: self.prerelease,
+            page_url: self.html_url,
+            source,
+            contents: Default::default(),
+        })
+    }
+}
+
+/// An http client that goes through the proxy from the config, if there is one.
+pub fn http_client() -> Result<reqwest::blocking::Client, ErscomError> {
+    let mut builder = reqwest::blocking::Client::builder();
+    if let Some(proxy) = crate::config::get().proxy {
+        debug!("Using proxy {}", proxy);
+        builder = builder.proxy(reqwest::Proxy::all(&proxy).map_err(|e| format!("Bad proxy \"{}\": {}", proxy, e))?);
+    }
+    Ok(builder.build()?)
+}
+
+/// The releases page of a github project.
+#[derive(Debug)]
+pub struct GithubSource {
+    project: String,
+    rate_limit: Mutex<Option<RateLimit>>,
+}
+
+impl GithubSource {
+    pub fn new(project: &str) -> GithubSource {
+        GithubSource { project: project.to_string(), rate_limit: Mutex::new(None) }
+    }
+
+    fn github_releases(&self) -> Result<Vec<GithubRelease>, ErscomError> {
+        tokio::task::block_in_place(move || {
+            let client = http_client()?;
+            let url = format!("https://api.github.com/repos/{}/releases", self.project);
+            info!("GET {}", url);
+            let resp = client.get(&url)
+                .header("Accept", "application/vnd.github+json")
+                .header("User-Agent", "erscom 1.0")
+                .send()?;
+            let status = resp.status();
+            info!("GET {} -> {}", url, status);
+            if let Some(rate_limit) = RateLimit::from_headers(resp.headers()) {
+                debug!("Rate limit: {:?}", rate_limit);
+                *self.rate_limit.lock().unwrap() = Some(rate_limit);
+            }
+            if !status.is_success() {
+                Err(ErscomError::GitHubApi { status: status.as_u16(), message: resp.text().unwrap_or(format!("Got status {}", status)) })?;
+                unreachable!();
+            }
+            Ok(resp.json()?)
+        })
+    }
+
+    /// Just the tags, newest first, including releases that have no assets. Pre-releases are left out unless
+    /// `include_prerelease`.
+    pub fn tags(&self, include_prerelease: bool) -> Result<Vec<String>, ErscomError> {
+        Ok(self.github_releases()?.into_iter().filter(|r| include_prerelease || !r.prerelease).map(|r| r.tag_name).collect())
+    }
+}
+
+impl ReleaseSource for GithubSource {
+    fn releases(self: Arc<Self>) -> Result<Vec<Release>, ErscomError> {
+        Ok(self.github_releases()?.into_iter().filter_map(|r| r.into_release(self.clone())).collect())
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        *self.rate_limit.lock().unwrap()
+    }
+
+    fn location(&self) -> String {
+        format!("https://github.com/{}", self.project)
+    }
+
+    fn fetch_asset(&self, release: &Release, dest: &Path, progress: &Progress) -> Result<(), ErscomError> {
+        tokio::task::block_in_place(move || {
+            let client = http_client()?;
+            info!("GET {}", release.url);
+            let resp = client.get(&release.url)
+                .header("User-Agent", "erscom 1.0")
+                .send()?;
+            let status = resp.status();
+            info!("GET {} -> {} ({} bytes)", release.url, status, resp.content_length().map(|l| l.to_string()).unwrap_or("unknown".to_string()));
+            if !status.is_success() { Err(ErscomError::GitHubApi { status: status.as_u16(), message: format!("Downloading {} failed", release.url) })? }
+            let total = resp.content_length();
+            copy_with_progress(resp, Path::new(&release.url), dest, total, progress)
+        })
+    }
+}
+
+/// A copy of a github project's release list (in the releases api's format) published as a plain file, by
+/// `.github/workflows/releases-json.yml` on GitHub Pages. Getting it doesn't count against the api's rate limit. If it
+/// can't be had the api (`fallback`) gets asked instead, and the zips always come from github either way.
+#[derive(Debug)]
+pub struct StaticSource {
+    url: String,
+    fallback: Arc<GithubSource>,
+}
+
+impl StaticSource {
+    pub fn new(url: &str, fallback: GithubSource) -> StaticSource {
+        StaticSource { url: url.to_string(), fallback: Arc::new(fallback) }
+    }
+
+    fn static_releases(&self) -> Result<Vec<GithubRelease>, ErscomError> {
+        tokio::task::block_in_place(move || {
+            info!("GET {}", self.url);
+            let resp = http_client()?.get(&self.url).header("User-Agent", "erscom 1.0").send()?;
+            let status = resp.status();
+            info!("GET {} -> {}", self.url, status);
+            if !status.is_success() { Err(ErscomError::GitHubApi { status: status.as_u16(), message: format!("Fetching {} failed", self.url) })? }
+            Ok(resp.json()?)
+        })
+    }
+}
+
+impl ReleaseSource for StaticSource {
+    fn releases(self: Arc<Self>) -> Result<Vec<Release>, ErscomError> {
+        match self.static_releases() {
+            Ok(releases) => Ok(releases.into_iter().filter_map(|r| r.into_release(self.clone())).collect()),
+            Err(e) => {
+                warn!("Couldn't get {} ({}), asking the github api instead", self.url, e);
+                self.fallback.clone().releases()
+            },
+        }
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        self.fallback.rate_limit()
+    }
+
+    fn location(&self) -> String {
+        self.url.clone()
+    }
+
+    fn fetch_asset(&self, release: &Release, dest: &Path, progress: &Progress) -> Result<(), ErscomError> {
+        self.fallback.fetch_asset(release, dest, progress)
+    }
+}
+
+/// A local directory laid out like a github project: a `releases.json` (in the format the github releases api
+/// returns) plus a `<tag>.zip` for each release. Handy for testing without the network.
+#[derive(Debug, Clone)]
+pub struct DirSource {
+    dir: PathBuf,
+}
+
+impl DirSource {
+    pub fn new(dir: &Path) -> DirSource {
+        DirSource { dir: dir.to_path_buf() }
+    }
+}
+
+impl ReleaseSource for DirSource {
+    fn releases(self: Arc<Self>) -> Result<Vec<Release>, ErscomError> {
+        let path = self.dir.join("releases.json");
+        let releases: Vec<GithubRelease> = serde_json::from_reader(File::open(&path).map_err(ErscomError::io(&path))?).map_err(ErscomError::json(&path))?;
+        Ok(releases.into_iter()
+           .map(|r| GithubRelease { assets: vec![GithubAsset { browser_download_url: self.dir.join(format!("{}.zip", r.tag_name)).to_string_lossy().into_owned(),
+                                                               download_count: r.assets.first().and_then(|a| a.download_count) }], ..r })
+           .filter_map(|r| r.into_release(self.clone()))
+           .collect())
+    }
+
+    fn fetch_asset(&self, release: &Release, dest: &Path, progress: &Progress) -> Result<(), ErscomError> {
+        debug!("Copying {} to {}", release.url, dest.display());
+        let from = Path::new(&release.url);
+        let file = File::open(from).map_err(ErscomError::io(from))?;
+        let total = file.metadata().map(|m| m.len()).ok();
+        copy_with_progress(file, from, dest, total, progress)
+    }
+
+    fn location(&self) -> String {
+        self.dir.display().to_string()
+    }
+}
+
+// What we remember about each release in the cache.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CachedRelease {
+    tag: String,
+    url: String,
+    date: String,
+    changelog: String,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    downloads: Option<u64>,
+    #[serde(default)]
+    page_url: Option<String>,
+}
+
+/// The release cache on disk. It only knows about releases that some other source has told it about (see
+/// [CacheSource::save()]) and can only supply the zips that have already been downloaded.
+#[derive(Debug, Clone)]
+pub struct CacheSource {
+    dir: PathBuf,
+}
+
+impl CacheSource {
+    pub fn new(dir: &Path) -> CacheSource {
+        CacheSource { dir: dir.to_path_buf() }
+    }
+
+    fn metadata_path(&self) -> PathBuf {
+        self.dir.join("releases.json")
+    }
+
+    /// Remember a list of releases so they can be listed later without the original source.
+    pub fn save(&self, releases: &[Release]) -> Result<(), ErscomError> {
+        std::fs::create_dir_all(&self.dir).map_err(ErscomError::io(&self.dir))?;
+        let cached: Vec<CachedRelease> = releases.iter().map(|r| CachedRelease { tag: r.tag.clone(), url: r.url.clone(), date: r.date.clone(), changelog: r.changelog.clone(), prerelease: r.prerelease, downloads: r.downloads, page_url: r.page_url.clone() }).collect();
+        let path = self.metadata_path();
+        serde_json::to_writer_pretty(File::create(&path).map_err(ErscomError::io(&path))?, &cached).map_err(ErscomError::json(&path))?;
+        Ok(())
+    }
+}
+
+impl ReleaseSource for CacheSource {
+    fn releases(self: Arc<Self>) -> Result<Vec<Release>, ErscomError> {
+        let path = self.metadata_path();
+        let mut cached: Vec<CachedRelease> = match File::open(&path) {
+            Ok(file) => serde_json::from_reader(file).map_err(ErscomError::json(&path))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => Err(ErscomError::io(&path)(e))?,
+        };
+        // Zips downloaded before we started saving the release list still count, we just don't know much about them.
+        for entry in std::fs::read_dir(&self.dir).map_err(ErscomError::io(&self.dir))? {
+            let path = entry.map_err(ErscomError::io(&self.dir))?.path();
+            if path.extension().map(|e| e.to_string_lossy().to_lowercase()) != Some("zip".to_string()) { continue }
+            let Some(tag) = path.file_stem().map(|s| s.to_string_lossy().into_owned()) else { continue };
+            if cached.iter().any(|r| r.tag == tag) { continue }
+            cached.push(CachedRelease { tag, url: path.to_string_lossy().into_owned(), date: "".to_string(), changelog: "".to_string(), prerelease: false, downloads: None, page_url: None });
+        }
+        // Same for ones that are only in the zip cache's index
+        for tag in ZipCache::new(&self.dir).tags() {
+            if cached.iter().any(|r| r.tag == tag) { continue }
+            cached.push(CachedRelease { tag, url: "".to_string(), date: "".to_string(), changelog: "".to_string(), prerelease: false, downloads: None, page_url: None });
+        }
+        Ok(cached.into_iter().map(|r| Release { tag: r.tag, url: r.url, date: r.date, changelog: r.changelog, prerelease: r.prerelease, downloads: r.downloads, page_url: r.page_url, source: self.clone(), contents: Default::default() }).collect())
+    }
+
+    fn fetch_asset(&self, release: &Release, _dest: &Path, _progress: &Progress) -> Result<(), ErscomError> {
+        Err(ErscomError::NotFound(format!("Release {} hasn't been downloaded and we're offline", release.tag)))?
+    }
+
+    fn offline(&self) -> bool { true }
+
+    fn location(&self) -> String {
+        format!("{} (offline)", self.dir.display())
+    }
+
+    // The list is as old as the last time it got saved
+    fn updated_at(&self) -> Option<chrono::DateTime<chrono::Local>> {
+        std::fs::metadata(self.metadata_path()).and_then(|m| m.modified()).ok().map(chrono::DateTime::from)
+    }
+}
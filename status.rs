@@ -0,0 +1,120 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Local};
+
+use crate::{config, i18n, manage::EldenRingManager};
+
+/// "5 min ago", etc. Coarse on purpose, it's only for glancing at.
+pub fn ago(when: DateTime<Local>) -> String {
+    match since(when) {
+        Some(span) => format!("{} ago", span),
+        None => "just now".to_string(),
+    }
+}
+
+/// How long it's been since `when`: "5 min", "3 days", etc. `None` if it hasn't been a minute yet.
+fn since(when: DateTime<Local>) -> Option<String> {
+    let minutes = (Local::now() - when).num_minutes();
+    if minutes <= 0 { return None }
+    Some(match minutes {
+        ..=59          => format!("{} min", minutes),
+        60..=119        => "1 hour".to_string(),
+        120..=1439      => format!("{} hours", minutes / 60),
+        1440..=2879     => "1 day".to_string(),
+        2880..=20159    => format!("{} days", minutes / 1440),
+        20160..=86399   => format!("{} weeks", minutes / 10080),
+        86400..=1051199 => format!("{} months", minutes / 43200),
+        _               => format!("{} years", minutes / 525600),
+    })
+}
+
+/// "Installed 12 days ago  •  Update available for 5 days", so groups notice when they've fallen behind. Empty if
+/// we don't know either.
+pub fn install_age(manager: &EldenRingManager) -> String {
+    let config = config::get();
+    let mut parts = vec![];
+    if let (Some(current), Some((tag, at))) = (manager.current.as_ref(), config.installed_at.as_ref()) {
+        if *tag == current.tag {
+            if let Some(at) = DateTime::from_timestamp(*at, 0) {
+                parts.push(format!("Installed {}", ago(at.with_timezone(&Local))));
+            }
+        }
+    }
+    if let Some(since) = manager.update_available_since(config.show_beta) {
+        parts.push(match self::since(since) {
+            Some(span) => format!("Update available for {}", span),
+            None => "Update available".to_string(),
+        });
+    }
+    parts.join("  •  ")
+}
+
+/// "May 3, 2024" (in the UI's language), or "2 weeks ago" if that's how the preferences say to show dates.
+pub fn date(when: DateTime<Local>) -> String {
+    if config::get().relative_dates { return ago(when) }
+    let (format, locale) = i18n::date_format(&i18n::current());
+    when.format_localized(format, locale).to_string()
+}
+
+/// Like [date()], but with the time too (unless it's relative, where that'd be redundant).
+pub fn date_time(when: DateTime<Local>) -> String {
+    if config::get().relative_dates { return ago(when) }
+    format!("{} {}", date(when), when.format("%H:%M"))
+}
+
+/// For the timestamps github (and the release cache) use. Anything that doesn't parse comes back as is, so at least
+/// something shows up.
+pub fn release_date(published_at: &str) -> String {
+    match DateTime::parse_from_rfc3339(published_at) {
+        Ok(when) => date(when.with_timezone(&Local)),
+        Err(_) => published_at.to_string(),
+    }
+}
+
+/// "1.2 MB", etc.
+pub fn size(bytes: u64) -> String {
+    match bytes {
+        ..=1023              => format!("{} bytes", bytes),
+        1024..=1048575       => format!("{:.1} KB", bytes as f64 / 1024.0),
+        1048576..=1073741823 => format!("{:.1} MB", bytes as f64 / 1048576.0),
+        _                    => format!("{:.1} GB", bytes as f64 / 1073741824.0),
+    }
+}
+
+/// "1234 downloads", or "12.3k downloads" once it gets big.
+pub fn downloads(count: u64) -> String {
+    match count {
+        1             => "1 download".to_string(),
+        ..=9999       => format!("{} downloads", count),
+        10000..=999999 => format!("{:.1}k downloads", count as f64 / 1000.0),
+        _             => format!("{:.1}M downloads", count as f64 / 1000000.0),
+    }
+}
+
+/// The main window's status bar: how old the release list is, whether we're online, and how much of the github rate
+/// limit is left.
+pub fn text(manager: &EldenRingManager) -> String {
+    let mut parts = vec![];
+    parts.push(match manager.updated_at {
+        Some(when) => format!("Releases updated {}", ago(when)),
+        None => "Releases not loaded".to_string(),
+    });
+    parts.push(if manager.offline { "Offline" } else { "Online" }.to_string());
+    if let Some(rate_limit) = manager.rate_limit {
+        parts.push(format!("GitHub API: {}/{} requests left (resets {})", rate_limit.remaining, rate_limit.limit, rate_limit.reset.format("%H:%M")));
+    }
+    parts.join("  •  ")
+}
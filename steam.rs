@@ -0,0 +1,190 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Adding ourselves (in --launch mode) to Steam as a non-Steam game, so Big Picture and Steam Deck users can start
+// modded co-op from Steam. Non-Steam games live in `userdata/<account>/config/shortcuts.vdf`, in Valve's binary
+// KeyValues format.
+
+use std::path::{Path, PathBuf};
+
+use tracing::{debug, info};
+
+use crate::error::ErscomError;
+
+const NAME: &str = "Elden Ring Seamless Co-op";
+
+/// Add (or update) our entry for every Steam account on this machine. Returns how many got it. Steam only reads the
+/// file when it starts, and writes over it when it exits, so it has to be restarted afterwards.
+pub fn register() -> Result<usize, ErscomError> {
+    let steam = steam_dir().ok_or("Couldn't find Steam")?;
+    let exe = std::env::current_exe().map_err(|e| format!("Couldn't find my .exe: {}", e))?;
+    let userdata = steam.join("userdata");
+    let mut count = 0;
+    for entry in std::fs::read_dir(&userdata).map_err(ErscomError::io(&userdata))? {
+        let account = entry.map_err(ErscomError::io(&userdata))?.path();
+        // There's a "0" (and sometimes "anonymous") that aren't real accounts
+        let real = account.file_name().and_then(|n| n.to_str()).map(|n| n != "0" && n.chars().all(|c| c.is_ascii_digit())).unwrap_or(false);
+        if !real || !account.join("config").is_dir() { continue }
+        add_shortcut(&account.join("config").join("shortcuts.vdf"), &exe)?;
+        count += 1;
+    }
+    if count == 0 { Err(format!("No Steam accounts found in {}. Log in to Steam once first.", userdata.display()))? }
+    Ok(count)
+}
+
+#[cfg(target_os = "windows")]
+fn steam_dir() -> Option<PathBuf> {
+    let hkcu = winreg::RegKey::predef(winreg::enums::HKEY_CURRENT_USER);
+    hkcu.open_subkey(r"Software\Valve\Steam")
+        .and_then(|key| key.get_value::<std::ffi::OsString,_>("SteamPath"))
+        .map(PathBuf::from).ok()
+        .filter(|p| p.is_dir())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn steam_dir() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    [home.join(".steam").join("steam"), home.join(".local").join("share").join("Steam")].into_iter().find(|p| p.join("userdata").is_dir())
+}
+
+fn add_shortcut(path: &Path, exe: &Path) -> Result<(), ErscomError> {
+    let mut root = match std::fs::read(path) {
+        Ok(bytes) => {
+            // Keep the original in case we mangle it
+            let backup = path.with_extension("vdf.erscom-backup");
+            std::fs::write(&backup, &bytes).map_err(ErscomError::io(&backup))?;
+            Vdf::parse(&bytes).ok_or_else(|| ErscomError::Other(format!("Couldn't make sense of {}", path.display())))?
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vdf::Map(vec![("shortcuts".to_string(), Vdf::Map(vec![]))]),
+        Err(e) => Err(ErscomError::io(path)(e))?,
+    };
+    let Some(shortcuts) = root.get_mut("shortcuts") else { Err(format!("{} has no shortcuts in it", path.display()))? };
+    let Vdf::Map(shortcuts) = shortcuts else { Err(format!("{} has no shortcuts in it", path.display()))? };
+
+    let quoted = |p: &Path| format!("\"{}\"", p.display());
+    let exe_quoted = quoted(exe);
+    let ours = Vdf::Map(vec![
+        ("appid".to_string(),              Vdf::Int(app_id(&exe_quoted))),
+        ("AppName".to_string(),            Vdf::String(NAME.to_string())),
+        ("Exe".to_string(),                Vdf::String(exe_quoted)),
+        ("StartDir".to_string(),           Vdf::String(quoted(exe.parent().unwrap_or(exe)))),
+        ("icon".to_string(),               Vdf::String(exe.display().to_string())),
+        ("ShortcutPath".to_string(),       Vdf::String(String::new())),
+        ("LaunchOptions".to_string(),      Vdf::String("--launch".to_string())),
+        ("IsHidden".to_string(),           Vdf::Int(0)),
+        ("AllowDesktopConfig".to_string(), Vdf::Int(1)),
+        ("AllowOverlay".to_string(),       Vdf::Int(1)),
+        ("OpenVR".to_string(),             Vdf::Int(0)),
+        ("Devkit".to_string(),             Vdf::Int(0)),
+        ("DevkitGameID".to_string(),       Vdf::String(String::new())),
+        ("DevkitOverrideAppID".to_string(), Vdf::Int(0)),
+        ("LastPlayTime".to_string(),       Vdf::Int(0)),
+        ("FlatpakAppID".to_string(),       Vdf::String(String::new())),
+        ("tags".to_string(),               Vdf::Map(vec![])),
+    ]);
+    // Replace the one from last time if it's there. Entries are keyed "0", "1", ... in order.
+    match shortcuts.iter_mut().find(|(_, s)| matches!(s.get("AppName").or(s.get("appname")), Some(Vdf::String(name)) if name == NAME)) {
+        Some((_, existing)) => { debug!("Replacing existing shortcut in {}", path.display()); *existing = ours },
+        None => shortcuts.push((shortcuts.len().to_string(), ours)),
+    }
+    std::fs::write(path, root.serialize()).map_err(ErscomError::io(path))?;
+    info!("Added {} to {}", NAME, path.display());
+    crate::history::record(format!("Added to Steam in {}", path.display()));
+    Ok(())
+}
+
+// What Steam itself calculates for non-Steam games (it's how the grid artwork etc gets matched up)
+fn app_id(exe_quoted: &str) -> u32 {
+    crc32fast::hash(format!("{}{}", exe_quoted, NAME).as_bytes()) | 0x80000000
+}
+
+/// Binary KeyValues. Only the types that show up in shortcuts.vdf.
+#[derive(Debug, Clone, PartialEq)]
+enum Vdf {
+    Map(Vec<(String, Vdf)>),
+    String(String),
+    Int(u32),
+}
+
+const TYPE_MAP: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_INT: u8 = 0x02;
+const TYPE_END: u8 = 0x08;
+
+impl Vdf {
+    fn get(&self, key: &str) -> Option<&Vdf> {
+        let Vdf::Map(entries) = self else { return None };
+        entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut Vdf> {
+        let Vdf::Map(entries) = self else { return None };
+        entries.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// The whole file is a map without the closing marker on the outside.
+    fn parse(bytes: &[u8]) -> Option<Vdf> {
+        let mut rest = bytes;
+        let root = Vdf::parse_map(&mut rest)?;
+        Some(root)
+    }
+
+    fn parse_map(rest: &mut &[u8]) -> Option<Vdf> {
+        let mut entries = vec![];
+        loop {
+            let Some((&kind, after)) = rest.split_first() else { return Some(Vdf::Map(entries)) }; // End of file
+            *rest = after;
+            if kind == TYPE_END { return Some(Vdf::Map(entries)) }
+            let key = Vdf::parse_string(rest)?;
+            let value = match kind {
+                TYPE_MAP => Vdf::parse_map(rest)?,
+                TYPE_STRING => Vdf::String(Vdf::parse_string(rest)?),
+                TYPE_INT => {
+                    let (int, after) = rest.split_first_chunk::<4>()?;
+                    *rest = after;
+                    Vdf::Int(u32::from_le_bytes(*int))
+                },
+                _ => return None,
+            };
+            entries.push((key, value));
+        }
+    }
+
+    fn parse_string(rest: &mut &[u8]) -> Option<String> {
+        let end = rest.iter().position(|&b| b == 0)?;
+        let s = String::from_utf8_lossy(&rest[..end]).into_owned();
+        *rest = &rest[end + 1..];
+        Some(s)
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.serialize_into(&mut out);
+        out.push(TYPE_END); // The outermost map has one too
+        out
+    }
+
+    fn serialize_into(&self, out: &mut Vec<u8>) {
+        let Vdf::Map(entries) = self else { return };
+        for (key, value) in entries {
+            let push_str = |out: &mut Vec<u8>, s: &str| { out.extend_from_slice(s.as_bytes()); out.push(0) };
+            match value {
+                Vdf::Map(_) => { out.push(TYPE_MAP); push_str(out, key); value.serialize_into(out); out.push(TYPE_END) },
+                Vdf::String(s) => { out.push(TYPE_STRING); push_str(out, key); push_str(out, s) },
+                Vdf::Int(i) => { out.push(TYPE_INT); push_str(out, key); out.extend_from_slice(&i.to_le_bytes()) },
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Support bundles: one zip with everything someone helping out would ask for, to attach to an issue or a Discord post.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use tracing::info;
+
+use crate::{error::ErscomError, issue, logview, manage::{self, EldenRingManager}, paths, transfer, troubleshoot};
+
+const REDACTED: &str = "<redacted>";
+
+/// Zip up the manager's log, the mod's settings (minus the password), the mod's log, what's installed, and some
+/// system info. Returns where the zip went.
+pub fn create_bundle(manager: &EldenRingManager) -> Result<PathBuf, ErscomError> {
+    let dir = dirs::desktop_dir().filter(|d| d.is_dir()).unwrap_or(paths::data_dir());
+    std::fs::create_dir_all(&dir).map_err(ErscomError::io(&dir))?;
+    let path = dir.join(format!("erscom-support {}.zip", chrono::Local::now().format("%Y-%m-%d %H.%M.%S")));
+    info!("Creating support bundle {}", path.display());
+
+    let mut zip = zip::ZipWriter::new(File::create(&path).map_err(ErscomError::io(&path))?);
+    let mut add = |name: &str, contents: &[u8]| -> Result<(), ErscomError> {
+        zip.start_file(name, zip::write::FileOptions::default()).map_err(ErscomError::zip(&path))?;
+        zip.write_all(contents).map_err(ErscomError::io(&path))
+    };
+
+    add("system.txt", system_info(manager).as_bytes())?;
+    add("manifest.txt", manifest(manager).as_bytes())?;
+    if let Some(log) = logview::latest_log_file() {
+        add("erscom.log", &std::fs::read(&log).map_err(ErscomError::io(&log))?)?;
+    }
+    if let Some(log) = manager.dir.as_ref().and_then(troubleshoot::mod_log_path) {
+        add(&format!("mod/{}", file_name(&log)), &std::fs::read(&log).map_err(ErscomError::io(&log))?)?;
+    }
+    for ini_path in manager.ini_paths().unwrap_or_default() {
+        // Only the settings survive this, but the comments are all in the release zip anyway
+        let Ok(mut ini) = manager.read_ini(&ini_path) else { continue };
+        for section in ["PASSWORD", "SETTINGS"] {
+            if ini.get(section, "cooppassword").is_some() { ini.set(section, "cooppassword", REDACTED) }
+        }
+        add(&format!("mod/{}", transfer::zip_name(&ini_path)), ini.export().as_bytes())?;
+    }
+
+    zip.finish().map_err(ErscomError::zip(&path))?;
+    Ok(path)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+fn system_info(manager: &EldenRingManager) -> String {
+    format!("Manager version: {}\n\
+             Commit: {}\n\
+             OS: {}\n\
+             Install path: {}\n\
+             Installed mod version: {}\n\
+             Mod releases from: {}\n\
+             Cache dir: {}\n\
+             Portable: {}\n",
+            option_env!("VERSION").unwrap_or("0.0.0-local"),
+            option_env!("GIT_COMMIT").unwrap_or("unknown"),
+            issue::os_description(),
+            manager.dir.as_ref().map(|d| d.display()).unwrap_or("<Not Found>".to_string()),
+            match (manager.current.as_ref(), manager.dir.as_ref()) {
+                (Some(current), Some(dir)) => format!("{} ({})", current.tag, current.installed(dir)),
+                (Some(current), None) => current.tag.clone(),
+                (None, _) => "<Unknown>".to_string(),
+            },
+            manager.source.location(),
+            manage::cache_dir().map(|d| d.display().to_string()).unwrap_or_else(|e| e.to_string()),
+            if paths::portable() { "yes" } else { "no" })
+}
+
+/// Every file the installed release came with, and whether the one on disk still matches.
+fn manifest(manager: &EldenRingManager) -> String {
+    let (dir, release) = match manager.ok() {
+        Ok(found) => found,
+        Err(e) => return format!("Nothing to list: {}\n", e),
+    };
+    let contents = match release.contents() {
+        Ok(contents) => contents,
+        Err(e) => return format!("{} is installed but its files can't be listed: {}\n", release.tag, e),
+    };
+    let mut out = format!("{} in {}\n\n", release.tag, dir.display());
+    for entry in contents.entries.iter().filter(|e| !e.is_dir) {
+        let status = match release.file_installed(dir, &entry.path) {
+            Some(true) => "ok",
+            Some(false) => "DIFFERENT",
+            None => "MISSING",
+        };
+        out.push_str(&format!("{:<9}  {:>10}  {:08x}  {}\n", status, entry.size, entry.crc32, entry.path.display()));
+    }
+    out
+}
@@ -0,0 +1,237 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Moving to a new PC (or a Steam Deck): the manager's config, history, last working setup, the mod's settings, and
+// (if asked for) the downloaded releases, all in one zip that the manager on the other end can import.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Serialize, Deserialize};
+use tracing::{info, warn};
+
+use crate::{config::{self, Config}, error::ErscomError, history, ini::Ini, known_good, manage::EldenRingManager, paths, zipcache::{self, ZipCache}};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    manager_version: String,
+    /// Seconds since the epoch.
+    exported_at: i64,
+    /// The mod version that was installed.
+    installed: Option<String>,
+}
+
+/// What [import] brought over, for telling people.
+#[derive(Debug, Default)]
+pub struct Imported {
+    /// The mod version the other machine had.
+    pub installed: Option<String>,
+    pub settings_files: usize,
+    pub releases: usize,
+}
+
+impl Imported {
+    pub fn describe(&self) -> String {
+        let mut parts = vec!["Imported the manager's settings".to_string()];
+        if self.settings_files > 0 { parts.push("the mod's settings".to_string()) }
+        if self.releases > 0 { parts.push(format!("{} downloaded releases", self.releases)) }
+        let mut text = parts.join(", ");
+        if let Some(ref tag) = self.installed.as_ref().filter(|_| self.settings_files == 0) {
+            text.push_str(&format!(". The other PC had {} installed; install it and import again to bring over the mod's settings.", tag));
+        }
+        text
+    }
+}
+
+/// Somewhere sensible to export to: the desktop, like support bundles.
+pub fn default_export_path() -> PathBuf {
+    let dir = dirs::desktop_dir().filter(|d| d.is_dir()).unwrap_or(paths::data_dir());
+    dir.join(format!("erscom-state {}.zip", chrono::Local::now().format("%Y-%m-%d %H.%M.%S")))
+}
+
+/// Write everything to `path`. The mod's settings include the co-op password--it's for the same person on another
+/// machine, so that's what they'd want. The GitHub token stays behind though: zips like this get passed around.
+pub fn export(manager: &EldenRingManager, path: &Path, include_releases: bool) -> Result<(), ErscomError> {
+    info!("Exporting manager state to {}{}", path.display(), if include_releases { " (with releases)" } else { "" });
+    let mut zip = zip::ZipWriter::new(File::create(path).map_err(ErscomError::io(path))?);
+    let mut add = |name: &str, contents: &mut dyn Read, from: &Path| -> Result<(), ErscomError> {
+        // Release zips are already compressed, and everything else is small
+        zip.start_file(name, zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored)).map_err(ErscomError::zip(path))?;
+        std::io::copy(contents, &mut zip).map_err(ErscomError::io(from))?;
+        Ok(())
+    };
+
+    let manifest = Manifest { manager_version: option_env!("VERSION").unwrap_or("0.0.0-local").to_string(),
+                              exported_at: chrono::Local::now().timestamp(),
+                              installed: manager.current.as_ref().map(|r| r.tag.clone()) };
+    add("manifest.json", &mut serde_json::to_vec_pretty(&manifest).map_err(ErscomError::json(path))?.as_slice(), path)?;
+    let config = Config { github_token: None, ..config::get() };
+    add("config.json", &mut serde_json::to_vec_pretty(&config).map_err(ErscomError::json(&config::path()))?.as_slice(), &config::path())?;
+    for file in [history::path(), known_good::path()] {
+        let Ok(mut contents) = File::open(&file) else { continue };
+        add(&file_name(&file), &mut contents, &file)?;
+    }
+    for ini_path in manager.ini_paths().unwrap_or_default() {
+        let Ok(ini) = manager.read_ini(&ini_path) else { continue };
+        add(&format!("mod/{}", zip_name(&ini_path)), &mut ini.export().as_bytes(), &ini_path)?;
+    }
+    if include_releases {
+        let cache = ZipCache::open()?;
+        for tag in cache.tags() {
+            let Some(release_path) = cache.find(&tag)? else { continue };
+            add(&format!("releases/{}.zip", tag), &mut File::open(&release_path).map_err(ErscomError::io(&release_path))?, &release_path)?;
+        }
+    }
+    let mut zip = zip.finish().map_err(ErscomError::zip(path))?;
+    zip.flush().map_err(ErscomError::io(path))?;
+    history::record(format!("Exported the manager's state to {}", path.display()));
+    Ok(())
+}
+
+/// Bring in what [export] wrote to `path`. This machine keeps its own window position, release cache location,
+/// install and GitHub token, and its history and last working setup if it already has them.
+pub fn import(manager: &EldenRingManager, path: &Path) -> Result<Imported, ErscomError> {
+    info!("Importing manager state from {}", path.display());
+    let mut zip = zip::ZipArchive::new(File::open(path).map_err(ErscomError::io(path))?).map_err(ErscomError::zip(path))?;
+    let mut read = |name: &str| -> Result<Option<Vec<u8>>, ErscomError> {
+        let mut file = match zip.by_name(name) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => Err(ErscomError::zip(path)(e))?,
+        };
+        let mut contents = vec![];
+        file.read_to_end(&mut contents).map_err(ErscomError::io(path))?;
+        Ok(Some(contents))
+    };
+
+    let manifest: Manifest = serde_json::from_slice(&read("manifest.json")?.ok_or(format!("{} isn't an exported manager state", path.display()))?)
+        .map_err(ErscomError::json(path))?;
+    let mut imported = Imported { installed: manifest.installed, ..Imported::default() };
+
+    if let Some(contents) = read("config.json")? {
+        let other = Config::from_json(serde_json::from_slice(&contents).map_err(ErscomError::json(path))?, path)?;
+        config::update(|c| *c = Config {
+            window: c.window,
+            cache_dir: c.cache_dir.clone(),
+            last_install_path: c.last_install_path.clone(),
+            last_installed: c.last_installed.clone(),
+            installed_at: c.installed_at.clone(),
+            github_token: c.github_token.clone(),
+            ..other
+        })?;
+    }
+    for file in [history::path(), known_good::path()] {
+        if file.exists() { continue }
+        let Some(contents) = read(&file_name(&file))? else { continue };
+        std::fs::create_dir_all(paths::data_dir()).map_err(ErscomError::io(&paths::data_dir()))?;
+        std::fs::write(&file, contents).map_err(ErscomError::io(&file))?;
+    }
+    for ini_path in manager.ini_paths().unwrap_or_default() {
+        let Some(contents) = read(&format!("mod/{}", zip_name(&ini_path)))? else { continue };
+        let mut ini = manager.read_ini(&ini_path)?;
+        ini.merge(&Ini::parse(&String::from_utf8_lossy(&contents)));
+        manager.write_ini(&ini_path, &ini)?;
+        imported.settings_files += 1;
+    }
+
+    let cache = ZipCache::open()?;
+    let release_names: Vec<String> = zip.file_names().map(|n| n.to_string()).collect();
+    for name in release_names {
+        let Some(tag) = name.strip_prefix("releases/").and_then(|n| n.strip_suffix(".zip")) else { continue };
+        let tag = tag.to_string();
+        if !zipcache::safe_tag(&tag) {
+            warn!("Not importing {:?} from {}, that's not a release name", name, path.display());
+            continue
+        }
+        if cache.find(&tag)?.is_some() { continue }
+        let partial = cache.partial_path(&tag);
+        if let Some(dir) = partial.parent() { std::fs::create_dir_all(dir).map_err(ErscomError::io(dir))? }
+        let mut file = zip.by_name(&name).map_err(ErscomError::zip(path))?;
+        std::io::copy(&mut file, &mut File::create(&partial).map_err(ErscomError::io(&partial))?).map_err(ErscomError::io(&partial))?;
+        match cache.store(&tag, &partial) {
+            Ok(_) => imported.releases += 1,
+            Err(e) => { warn!("Couldn't import release {}: {}", tag, e); let _ = std::fs::remove_file(&partial); },
+        }
+    }
+
+    history::record(format!("Imported the manager's state from {}", path.display()));
+    Ok(imported)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+/// `path` as a name inside a zip, which always uses forward slashes.
+pub fn zip_name(path: &Path) -> String {
+    path.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::secret::Secret;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("erscom-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn manager(dir: &Path) -> EldenRingManager {
+        EldenRingManager { dir: None, releases: vec![], current: None, source: Arc::new(crate::source::DirSource::new(dir)),
+                           offline: false, updated_at: None, rate_limit: None }
+    }
+
+    fn cache_release(tag: &str, contents: &[u8]) {
+        let cache = ZipCache::open().unwrap();
+        let partial = cache.partial_path(tag);
+        std::fs::create_dir_all(partial.parent().unwrap()).unwrap();
+        std::fs::write(&partial, contents).unwrap();
+        cache.store(tag, &partial).unwrap();
+    }
+
+    #[test]
+    fn export_then_import() {
+        let dir = scratch_dir("transfer");
+        let exported = dir.join("state.zip");
+        paths::with_data_dir(&dir.join("old"), || {
+            config::update(|c| { c.github_token = Some(Secret::new("ghp_old".to_string())); c.settings_gist = Some("gist".to_string()) }).unwrap();
+            history::record("On the old PC".to_string());
+            cache_release("v0.0.0-test", b"not really a zip");
+            export(&manager(&dir), &exported, true).unwrap();
+        });
+
+        let mut zip = zip::ZipArchive::new(File::open(&exported).unwrap()).unwrap();
+        let mut config_json = String::new();
+        zip.by_name("config.json").unwrap().read_to_string(&mut config_json).unwrap();
+        assert!(!config_json.contains("ghp_old"), "the GitHub token was exported: {}", config_json);
+
+        let imported = paths::with_data_dir(&dir.join("new"), || {
+            config::update(|c| { c.github_token = Some(Secret::new("ghp_new".to_string())); c.settings_gist = None }).unwrap();
+            let imported = import(&manager(&dir), &exported).unwrap();
+            assert!(ZipCache::open().unwrap().find("v0.0.0-test").unwrap().is_some());
+            imported
+        });
+        assert_eq!(imported.releases, 1);
+        assert_eq!(config::get().settings_gist.as_deref(), Some("gist"));
+        assert_eq!(config::get().github_token.as_ref().map(|t| t.expose().as_str()), Some("ghp_new"));
+        assert!(std::fs::read_to_string(dir.join("new").join("history.jsonl")).unwrap().contains("On the old PC"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{cell::RefCell, path::PathBuf, rc::Rc, sync::OnceLock, time::{Duration, Instant}};
+
+use slint::ComponentHandle;
+
+use crate::{eac, elevate, gamecheck, manage::{EldenRingDir, Release}, prereqs, report::{self, UIError}, verify, GameCheck, ModProblem, TroubleshootWindow};
+
+const MAX_LINES: usize = 1000;
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+const WATCH_FOR: Duration = Duration::from_secs(5*60); // Problems show up early, if they're going to
+
+struct Problem {
+    pattern: &'static str,
+    explanation: &'static str,
+    fix: &'static str,
+}
+
+// Things that show up in the mod's log when it goes wrong, and what the user should do about them.
+const PROBLEMS: &[Problem] = &[
+    Problem { pattern: r"(?i)version mismatch|mismatched versions?|different (mod )?version",
+              explanation: "You and another player have different versions of the mod.",
+              fix: "Everyone in the session has to install the exact same mod version. Compare versions and install the matching one." },
+    Problem { pattern: r"(?i)failed to connect|connection (timed out|failed|refused|lost)|unable to (join|connect)",
+              explanation: "The mod couldn't connect to the other player.",
+              fix: "Check that everyone is using the same password, and try turning off any VPN. If it keeps happening, have someone else host." },
+    Problem { pattern: r"(?i)steam\w*.*(not running|not found|failed|init)|steamapi_init",
+              explanation: "The mod couldn't talk to Steam.",
+              fix: "Make sure Steam is running and you're logged in (not in offline mode), then launch again." },
+    Problem { pattern: r"(?i)(failed to load|could not load|couldn't load|loadlibrary).*\.dll",
+              explanation: "One of the mod's files failed to load.",
+              fix: "Reinstall the mod. If that doesn't help, install the latest Microsoft Visual C++ Redistributable (x64)." },
+    Problem { pattern: r"(?i)password.*(empty|not set|missing|invalid)",
+              explanation: "The co-op password isn't set.",
+              fix: "Enter a password in the manager. Everyone you play with needs the same one." },
+    Problem { pattern: r"(?i)(easy ?)?anti-?cheat.*(running|enabled|detected)",
+              explanation: "Elden Ring was started with EasyAntiCheat, which blocks the mod.",
+              fix: "Always start the game with the mod's launcher (the Launch button), not from Steam directly." },
+];
+
+fn regexes() -> &'static Vec<(regex::Regex, &'static Problem)> {
+    static REGEXES: OnceLock<Vec<(regex::Regex, &'static Problem)>> = OnceLock::new();
+    REGEXES.get_or_init(|| PROBLEMS.iter().map(|p| (regex::Regex::new(p.pattern).unwrap(), p)).collect())
+}
+
+/// The mod's log file, if it has written one. We look for the newest thing that looks like a log in `SeamlessCoop`.
+pub fn mod_log_path(installdir: &EldenRingDir) -> Option<PathBuf> {
+    std::fs::read_dir(installdir.mod_dir()).ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_lowercase();
+            name.contains("log") && (name.ends_with(".txt") || name.ends_with(".log"))
+        })
+        .max_by_key(|e| e.metadata().and_then(|m| m.modified()).ok())
+        .map(|e| e.path())
+}
+
+fn read_from(path: &PathBuf, offset: u64) -> Option<String> {
+    let contents = std::fs::read(path).ok()?;
+    let offset = (offset as usize).min(contents.len());
+    Some(String::from_utf8_lossy(&contents[offset..]).into_owned())
+}
+
+/// Known problems in the log text, one per kind of problem (the last occurrence wins).
+fn diagnose(log: &str) -> Vec<ModProblem> {
+    let mut found: Vec<(usize, ModProblem)> = vec![];
+    for (line_number, line) in log.lines().enumerate() {
+        for (i, (re, problem)) in regexes().iter().enumerate() {
+            if !re.is_match(line) { continue }
+            let finding = ModProblem { explanation: problem.explanation.into(),
+                                       fix: problem.fix.into(),
+                                       line: format!("{}: {}", line_number + 1, line.trim()).into() };
+            match found.iter_mut().find(|(j, _)| *j == i) {
+                Some(existing) => existing.1 = finding,
+                None => found.push((i, finding)),
+            }
+        }
+    }
+    found.into_iter().map(|(_, f)| f).collect()
+}
+
+/// Show the troubleshooting window, looking at the log from byte `since` onwards. `current` is the installed mod
+/// version, for reinstalling it.
+pub fn show_troubleshooter(installdir: EldenRingDir, current: Option<Release>, since: u64) {
+    let win = TroubleshootWindow::new().try_error()?;
+    win.set_theme(crate::config::get().theme.color_theme());
+    win.set_high_contrast(crate::config::get().high_contrast);
+    let show_game_checks = {
+        let weak_win = win.as_weak();
+        let (installdir, current) = (installdir.clone(), current.clone());
+        move || {
+            let checks = gamecheck::run(&installdir).into_iter().map(|c| GameCheck {
+                name: c.name.into(), passed: c.passed, detail: c.detail.into(), fix: c.fix.into(),
+                can_elevate: c.action == Some(gamecheck::Action::Elevate) && current.is_some(),
+            }).collect::<Vec<_>>();
+            weak_win.unwrap().set_game_checks(Rc::new(slint::VecModel::from(checks)).into());
+        }
+    };
+    show_game_checks();
+    win.on_check_game(show_game_checks.clone());
+    win.on_elevate_install({
+        let (installdir, current) = (installdir.clone(), current.clone());
+        move || {
+            let Some(ref release) = current else { return };
+            crate::toast(&format!("Reinstalling {} as administrator...", release.tag));
+            let (installdir, release, show_game_checks) = (installdir.clone(), release.clone(), show_game_checks.clone());
+            slint::spawn_local(async move {
+                match tokio::task::spawn_blocking({
+                    let (installdir, release) = (installdir.clone(), release.clone());
+                    move || elevate::install(&installdir, &release, false)
+                }).await {
+                    Ok(Ok(())) => crate::toast(&format!("Reinstalled {}", release.tag)),
+                    Ok(Err(e)) => { report::error(e.into()); },
+                    Err(e) => tracing::warn!("Reinstalling {} didn't finish: {}", release.tag, e),
+                }
+                show_game_checks();
+            }).try_log("starting elevated install");
+        }
+    });
+    win.set_can_verify(current.is_some());
+    win.on_verify_files({
+        let (installdir, current) = (installdir.clone(), current.clone());
+        move || if let Some(ref release) = current { verify::show_verify_window(installdir.clone(), release.clone()) }
+    });
+    let missing = prereqs::missing(&installdir);
+    win.set_missing_prerequisites(Rc::new(slint::VecModel::from(missing.iter().map(|p| p.name.into()).collect::<Vec<slint::SharedString>>())).into());
+    win.on_install_prerequisite(move |index| {
+        let Some(&prereq) = missing.get(index as usize) else { return };
+        crate::install_prerequisite(prereq);
+    });
+    let show_eac_changes = {
+        let weak_win = win.as_weak();
+        let installdir = installdir.clone();
+        move || {
+            let changes = eac::changes(&installdir).into_iter().map(|c| c.description.into()).collect::<Vec<slint::SharedString>>();
+            weak_win.unwrap().set_eac_changes(Rc::new(slint::VecModel::from(changes)).into());
+        }
+    };
+    show_eac_changes();
+    win.on_restore_eac({
+        let installdir = installdir.clone();
+        move || {
+            let verifying = eac::restore(&installdir).try_error()?;
+            show_eac_changes();
+            crate::toast(if verifying { "Steam is verifying the game files to get the rest back" } else { "Put the anti-cheat files back the way they were" });
+        }
+    });
+    let update = {
+        let weak_win = win.as_weak();
+        let installdir = installdir.clone();
+        let last = Rc::new(RefCell::new(String::new()));
+        move || {
+            let Some(win) = weak_win.upgrade() else { return };
+            let Some(path) = mod_log_path(&installdir) else {
+                win.set_log_path(format!("The mod hasn't written a log in {} yet", installdir.mod_dir().display()).into());
+                return;
+            };
+            let Some(log) = read_from(&path, since) else { return };
+            if *last.borrow() == log { return }
+            let lines: Vec<&str> = log.lines().collect();
+            win.set_log_path(path.display().to_string().into());
+            win.set_log_text(lines[lines.len().saturating_sub(MAX_LINES)..].join("\n").into());
+            win.set_problems(Rc::new(slint::VecModel::from(diagnose(&log))).into());
+            *last.borrow_mut() = log;
+        }
+    };
+    update();
+
+    let timer = slint::Timer::default();
+    timer.start(slint::TimerMode::Repeated, WATCH_INTERVAL, update);
+
+    win.on_open_folder(move || {
+        crate::open_path(&installdir.mod_dir()).try_error()?;
+    });
+    win.on_close({
+        let weak_win = win.as_weak();
+        move || {
+            timer.stop();
+            weak_win.unwrap().hide().try_log("closing troubleshooting window")?;
+        }
+    });
+    win.show().try_log("showing troubleshooting window")?;
+}
+
+thread_local! {
+    static WATCHER: RefCell<Option<slint::Timer>> = const { RefCell::new(None) };
+}
+
+/// Keep an eye on the mod's log for a while after launching and pop up the troubleshooter if anything goes wrong.
+/// `on_clean` gets called if nothing does.
+pub fn watch_after_launch(installdir: EldenRingDir, current: Option<Release>, on_clean: impl FnOnce() + 'static) {
+    // Only look at what gets written from now on
+    let log_at_launch = mod_log_path(&installdir);
+    let since = log_at_launch.as_ref().and_then(|p| std::fs::metadata(p).ok()).map(|m| m.len()).unwrap_or(0);
+    let started = Instant::now();
+    let mut on_clean = Some(on_clean);
+    let timer = slint::Timer::default();
+    timer.start(slint::TimerMode::Repeated, WATCH_INTERVAL, move || {
+        let stop = || WATCHER.with(|w| w.borrow().as_ref().map(|t| t.stop()));
+        if started.elapsed() > WATCH_FOR {
+            stop();
+            if let Some(on_clean) = on_clean.take() { on_clean() }
+            return
+        }
+        let Some(path) = mod_log_path(&installdir) else { return };
+        // A brand new log file means the mod started over, so read it from the beginning
+        let since = if Some(&path) == log_at_launch.as_ref() { since } else { 0 };
+        let Some(log) = read_from(&path, since) else { return };
+        if !diagnose(&log).is_empty() {
+            tracing::info!("Found problems in {}, showing troubleshooter", path.display());
+            stop();
+            show_troubleshooter(installdir.clone(), current.clone(), since);
+        }
+    });
+    WATCHER.with(|w| *w.borrow_mut() = Some(timer));
+}
@@ -0,0 +1,217 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Checking the installed mod's files against the release zip's list of them, and reinstalling if they don't match.
+// There can be dozens of files, so a few threads hash them at once and each one shows up in the window as it's done.
+
+use std::{fs::File, io::Read, path::PathBuf, rc::Rc, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc, Mutex}, time::Duration};
+
+use slint::ComponentHandle;
+use tracing::{info, warn};
+
+use crate::{app, error::ErscomError, history, manage::{EldenRingDir, Release, ZipEntry}, paths, report::{self, UIError}, VerifyRow, VerifyWindow};
+
+// Past this the disk is the bottleneck, not the CPU
+const MAX_THREADS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileState {
+    Ok,
+    Different,
+    Missing,
+}
+
+impl FileState {
+    fn label(self) -> &'static str {
+        match self {
+            FileState::Ok => "ok",
+            FileState::Different => "different",
+            FileState::Missing => "missing",
+        }
+    }
+}
+
+/// Everything a verify has found so far. The hashing threads add to it and the window reads it.
+#[derive(Default)]
+pub struct Results {
+    pub total: usize,
+    pub files: Vec<(PathBuf, FileState)>,
+    /// None until it's over, then whether it got to every file.
+    pub finished: Option<Result<(), String>>,
+}
+
+/// Hash `release`'s files in `installdir` on a pool of threads, adding each to `results` as it's done. Settings files
+/// are expected to be changed, so they're left out. Stops early with [ErscomError::Cancelled] once `cancel` is set.
+pub fn verify(release: &Release, installdir: &EldenRingDir, results: &Mutex<Results>, cancel: &AtomicBool) -> Result<(), ErscomError> {
+    let contents = release.contents()?;
+    let entries: Vec<&ZipEntry> = contents.entries.iter()
+        .filter(|e| !e.is_dir && e.path.extension().map(|n| n.to_string_lossy().to_lowercase() != "ini").unwrap_or(true))
+        .collect();
+    results.lock().unwrap().total = entries.len();
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(MAX_THREADS).min(entries.len());
+    info!("Verifying {} files from {} in {} on {} threads", entries.len(), release.tag, installdir, threads);
+    let next = AtomicUsize::new(0);
+    std::thread::scope(|scope| -> Result<(), ErscomError> {
+        let workers: Vec<_> = (0..threads).map(|_| scope.spawn(|| -> Result<(), ErscomError> {
+            while let Some(entry) = entries.get(next.fetch_add(1, Ordering::Relaxed)) {
+                let state = check(installdir, entry, cancel)?;
+                if state != FileState::Ok { warn!("{} is {}", entry.path.display(), state.label()) }
+                results.lock().unwrap().files.push((entry.path.clone(), state));
+            }
+            Ok(())
+        })).collect();
+        for worker in workers {
+            worker.join().map_err(|_| "A verify thread panicked".to_string())??;
+        }
+        Ok(())
+    })
+}
+
+// Read the file a chunk at a time (instead of all at once like [Release::file_installed()]) so a cancel doesn't have
+// to wait for a big one to finish.
+fn check(installdir: &EldenRingDir, entry: &ZipEntry, cancel: &AtomicBool) -> Result<FileState, ErscomError> {
+    if cancel.load(Ordering::Relaxed) { Err(ErscomError::Cancelled)? }
+    let path = paths::long_path(&installdir.path().join(&entry.path));
+    let mut file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(FileState::Missing),
+        Err(e) => Err(ErscomError::io(&path)(e))?,
+    };
+    if file.metadata().map_err(ErscomError::io(&path))?.len() != entry.size { return Ok(FileState::Different) }
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buf = vec![0; 256*1024];
+    loop {
+        if cancel.load(Ordering::Relaxed) { Err(ErscomError::Cancelled)? }
+        let n = file.read(&mut buf).map_err(ErscomError::io(&path))?;
+        if n == 0 { break }
+        hasher.update(&buf[..n]);
+    }
+    Ok(if hasher.finalize() == entry.crc32 { FileState::Ok } else { FileState::Different })
+}
+
+struct Run {
+    results: Arc<Mutex<Results>>,
+    cancel: Arc<AtomicBool>,
+}
+
+fn start(release: &Release, installdir: &EldenRingDir) -> Run {
+    let run = Run { results: Arc::new(Mutex::new(Results::default())), cancel: Arc::new(AtomicBool::new(false)) };
+    let (release, installdir, results, cancel) = (release.clone(), installdir.clone(), run.results.clone(), run.cancel.clone());
+    tokio::task::spawn_blocking(move || {
+        let verified = verify(&release, &installdir, &results, &cancel);
+        let mut results = results.lock().unwrap();
+        let bad = results.files.iter().filter(|(_, state)| *state != FileState::Ok).count();
+        match verified {
+            Ok(()) => history::record(format!("Verified {} in {}: {}", release.tag, installdir,
+                                              if bad == 0 { "all files match".to_string() } else { format!("{} of {} files don't match", bad, results.total) })),
+            Err(ErscomError::Cancelled) => info!("Cancelled verifying {}", release.tag),
+            Err(ref e) => warn!("Verifying {} failed: {}", release.tag, e),
+        }
+        results.finished = Some(verified.map_err(|e| e.to_string()));
+    });
+    run
+}
+
+fn summary(tag: &str, results: &Results) -> String {
+    let bad = results.files.iter().filter(|(_, state)| *state != FileState::Ok).count();
+    match results.finished {
+        None => format!("Checked {} of {} files...", results.files.len(), results.total),
+        Some(Err(ref e)) => format!("Stopped after {} of {} files: {}", results.files.len(), results.total, e),
+        Some(Ok(())) if bad == 0 => format!("All {} files match {}.", results.total, tag),
+        Some(Ok(())) => format!("{} of {} files don't match {}. Repair reinstalls it to put them back.", bad, results.total, tag),
+    }
+}
+
+// Reinstall `release` the same way picking it in the main window does (so it waits for the game to exit, asks for
+// administrator access if it needs it, and so on), and then call `then`.
+fn repair(installdir: EldenRingDir, release: Release, then: Rc<dyn Fn()>) {
+    if app::busy() { return crate::toast("Wait for the manager to finish what it's doing first") }
+    let Some(manager) = app::manager() else { return };
+    app::update(app::Msg::Started(format!("Checking the {} download...", release.tag)));
+    slint::spawn_local(async move {
+        // Whatever messed up the installed files could have got to the zip too. A damaged one gets downloaded again.
+        let checked = tokio::task::spawn_blocking({
+            let release = release.clone();
+            move || release.check_download()
+        }).await;
+        app::update(app::Msg::Finished);
+        match checked {
+            Ok(Ok(_)) => {},
+            Ok(Err(e)) => return report::error(e.into()),
+            Err(e) => return warn!("Checking the {} download didn't finish: {}", release.tag, e),
+        }
+        info!("Repairing {} in {}", release.tag, installdir);
+        let retry = { let (installdir, release, then) = (installdir.clone(), release.clone(), then.clone()); move || repair(installdir.clone(), release.clone(), then.clone()) };
+        crate::install_release(&manager, &installdir, &release, retry, move || then());
+    }).try_log("starting repair")?;
+}
+
+/// The window that checks `release`'s files in `installdir`, and can reinstall it if any are wrong.
+pub fn show_verify_window(installdir: EldenRingDir, release: Release) {
+    let win = VerifyWindow::new().try_error()?;
+    win.set_theme(crate::config::get().theme.color_theme());
+    win.set_high_contrast(crate::config::get().high_contrast);
+    win.set_heading(format!("{} in {}", release.tag, installdir).into());
+    let run = Rc::new(std::cell::RefCell::new(start(&release, &installdir)));
+
+    let update = {
+        let (weak_win, run, tag) = (win.as_weak(), run.clone(), release.tag.clone());
+        move || {
+            let Some(win) = weak_win.upgrade() else { return };
+            let results = run.borrow().results.clone();
+            let results = results.lock().unwrap();
+            // Problems first, they're what anyone's looking for
+            let mut rows: Vec<VerifyRow> = results.files.iter().map(|(path, state)| VerifyRow {
+                path: path.display().to_string().into(), status: state.label().into(), ok: *state == FileState::Ok,
+            }).collect();
+            rows.sort_by_key(|row| row.ok);
+            win.set_files(Rc::new(slint::VecModel::from(rows)).into());
+            win.set_progress(if results.total == 0 { 0.0 } else { results.files.len() as f32 / results.total as f32 });
+            win.set_summary(summary(&tag, &results).into());
+            win.set_running(results.finished.is_none());
+            win.set_can_repair(results.finished.is_some() && results.files.iter().any(|(_, state)| *state != FileState::Ok));
+        }
+    };
+    update();
+
+    let timer = slint::Timer::default();
+    timer.start(slint::TimerMode::Repeated, Duration::from_millis(100), update.clone());
+
+    win.on_cancel({
+        let run = run.clone();
+        move || run.borrow().cancel.store(true, Ordering::Relaxed)
+    });
+    win.on_repair({
+        let (run, update) = (run.clone(), update.clone());
+        move || {
+            let (release, installdir, run, update) = (release.clone(), installdir.clone(), run.clone(), update.clone());
+            repair(installdir.clone(), release.clone(), Rc::new(move || {
+                crate::toast(&format!("Reinstalled {}", release.tag));
+                *run.borrow_mut() = start(&release, &installdir);
+                update();
+            }));
+        }
+    });
+    win.on_close({
+        let weak_win = win.as_weak();
+        move || {
+            // No one's going to look at the rest of it
+            run.borrow().cancel.store(true, Ordering::Relaxed);
+            timer.stop();
+            weak_win.unwrap().hide().try_log("closing verify window")?;
+        }
+    });
+    win.show().try_log("showing verify window")?;
+}
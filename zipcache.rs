@@ -0,0 +1,218 @@
+// Copyright © 2026 David Caldwell <david@porkrind.org>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Downloaded release zips, stored under their SHA-256 (`objects/<hash>.zip`) with an index from tags to hashes. A
+// release that gets re-published with the same zip doesn't take up space twice, and a zip that's been damaged on disk
+// no longer matches its name.
+//
+// Each release's list of files is kept too (`manifests/<tag>.json`), and outlives the zip. That's all uninstalling
+// needs, so a release can be uninstalled after its zip has been cleaned out of the cache.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use tracing::{info, warn};
+
+use crate::{checksums, error::ErscomError, manage::{self, EldenRingDir, ZipEntry}, paths};
+
+/// Could `tag` name a file in the cache without ending up somewhere else? Tags end up in file names here, and the ones
+/// from an imported file (see [crate::transfer]) could be anything.
+pub fn safe_tag(tag: &str) -> bool {
+    !tag.is_empty() && !tag.contains(['/', '\\', ':', '\0']) && !tag.contains("..")
+}
+
+pub struct ZipCache {
+    dir: PathBuf,
+}
+
+impl ZipCache {
+    pub fn new(dir: &Path) -> ZipCache {
+        ZipCache { dir: dir.to_path_buf() }
+    }
+
+    /// The cache in the configured cache dir.
+    pub fn open() -> Result<ZipCache, ErscomError> {
+        Ok(ZipCache::new(&manage::cache_dir()?))
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        paths::long_path(&self.dir.join("objects").join(format!("{}.zip", hash)))
+    }
+
+    /// Where zips went before they were stored by hash.
+    pub fn legacy_path(&self, tag: &str) -> PathBuf {
+        paths::long_path(&self.dir.join(format!("{}.zip", tag)))
+    }
+
+    /// Where a download goes until it's finished and checked.
+    pub fn partial_path(&self, tag: &str) -> PathBuf {
+        paths::long_path(&self.dir.join(format!("{}.zip.partial", tag)))
+    }
+
+    fn load_index(&self) -> BTreeMap<String, String> {
+        let path = self.index_path();
+        match File::open(&path) {
+            Ok(file) => serde_json::from_reader(file).unwrap_or_else(|e| { warn!("Ignoring damaged {}: {}", path.display(), e); BTreeMap::new() }),
+            Err(_) => BTreeMap::new(),
+        }
+    }
+
+    fn save_index(&self, index: &BTreeMap<String, String>) -> Result<(), ErscomError> {
+        let path = self.index_path();
+        std::fs::create_dir_all(&self.dir).map_err(ErscomError::io(&self.dir))?;
+        serde_json::to_writer_pretty(File::create(&path).map_err(ErscomError::io(&path))?, index).map_err(ErscomError::json(&path))
+    }
+
+    /// The tags that have a zip in the cache.
+    pub fn tags(&self) -> Vec<String> {
+        self.load_index().into_iter().filter(|(_, hash)| self.object_path(hash).is_file()).map(|(tag, _)| tag).collect()
+    }
+
+    /// Where `tag`'s zip is, if it's been downloaded, without changing anything (one from before zips were stored by
+    /// hash is left where it is). See [ZipCache::find()].
+    pub fn lookup(&self, tag: &str) -> Option<PathBuf> {
+        self.load_index().get(tag).map(|hash| self.object_path(hash))
+            .filter(|path| path.is_file())
+            .or_else(|| Some(self.legacy_path(tag)).filter(|path| path.is_file()))
+    }
+
+    /// Where `tag`'s zip is, if it's been downloaded. One still in the old `<tag>.zip` spot gets moved in first.
+    pub fn find(&self, tag: &str) -> Result<Option<PathBuf>, ErscomError> {
+        if let Some(hash) = self.load_index().get(tag) {
+            let path = self.object_path(hash);
+            if path.is_file() { return Ok(Some(path)) }
+        }
+        let legacy = self.legacy_path(tag);
+        if !legacy.is_file() { return Ok(None) }
+        info!("Moving {} into the cache by hash", legacy.display());
+        Ok(Some(self.store(tag, &legacy)?))
+    }
+
+    /// Move `file` (`tag`'s zip) into the cache, or just delete it if an identical one is already there. Returns
+    /// where it ended up. This is where zips get checked against their published checksums (see [checksums]), so
+    /// one that doesn't match is left where it is, for the caller to clean up.
+    pub fn store(&self, tag: &str, file: &Path) -> Result<PathBuf, ErscomError> {
+        let hash = checksums::sha256(file)?;
+        checksums::verify(tag, &hash)?;
+        let path = self.object_path(&hash);
+        if path.is_file() {
+            info!("{} is the same as a zip that's already cached", tag);
+            std::fs::remove_file(file).map_err(ErscomError::io(file))?;
+        } else {
+            let dir = path.parent().ok_or("No parent for cache objects dir??")?;
+            std::fs::create_dir_all(dir).map_err(ErscomError::io(dir))?;
+            std::fs::rename(file, &path).map_err(ErscomError::io(&path))?;
+        }
+        let mut index = self.load_index();
+        index.insert(tag.to_string(), hash);
+        self.save_index(&index)?;
+        checked().lock().unwrap().insert(path.clone()); // Just hashed it
+        Ok(path)
+    }
+
+    fn manifest_dir(&self) -> PathBuf {
+        self.dir.join("manifests")
+    }
+
+    fn manifest_path(&self, tag: &str) -> PathBuf {
+        paths::long_path(&self.manifest_dir().join(format!("{}.json", tag)))
+    }
+
+    /// The list of files in `tag`'s zip, if it was ever downloaded.
+    pub fn manifest(&self, tag: &str) -> Option<Vec<ZipEntry>> {
+        let path = self.manifest_path(tag);
+        let file = File::open(&path).ok()?;
+        serde_json::from_reader(file).map_err(|e| warn!("Ignoring damaged {}: {}", path.display(), e)).ok()
+    }
+
+    pub fn save_manifest(&self, tag: &str, entries: &[ZipEntry]) -> Result<(), ErscomError> {
+        let dir = self.manifest_dir();
+        std::fs::create_dir_all(&dir).map_err(ErscomError::io(&dir))?;
+        let path = self.manifest_path(tag);
+        serde_json::to_writer(File::create(&path).map_err(ErscomError::io(&path))?, entries).map_err(ErscomError::json(&path))
+    }
+
+    /// Another release's list of files to go by when `tag`'s isn't around: whichever one has the most of its files
+    /// in `installdir`. Releases mostly keep the same file names, so that's the best guess at what's installed.
+    pub fn stand_in(&self, installdir: &EldenRingDir, tag: &str) -> Option<(String, Vec<ZipEntry>)> {
+        let manifests = std::fs::read_dir(self.manifest_dir()).into_iter().flatten().filter_map(|e| e.ok())
+            .filter_map(|e| e.path().file_stem().map(|s| s.to_string_lossy().into_owned()));
+        let mut tags: Vec<String> = self.tags().into_iter().chain(manifests).filter(|t| t != tag).collect();
+        tags.sort_by_key(|t| manage::version_key(t));
+        tags.dedup();
+        let present = |entries: &[ZipEntry]| entries.iter().filter(|e| !e.is_dir && paths::long_path(&installdir.path().join(&e.path)).is_file()).count();
+        tags.into_iter()
+            .filter_map(|t| {
+                let entries = self.manifest(&t).or_else(|| self.find(&t).ok().flatten().and_then(|zip| manage::zip_entries(&zip).ok()))?;
+                Some((t, entries))
+            })
+            .map(|(t, entries)| (present(&entries), t, entries))
+            .filter(|(present, _, _)| *present > 0)
+            .max_by_key(|(present, _, _)| *present) // The last (newest) of any ties
+            .map(|(_, t, entries)| (t, entries))
+    }
+
+    /// Is the zip at `path` still what it was when it was stored? If not it gets removed (and forgotten by every tag
+    /// that used it) so it can be downloaded again. This reads the whole zip, see [ZipCache::check_once()] for when
+    /// that's too much.
+    pub fn check(&self, path: &Path) -> Result<bool, ErscomError> {
+        let expected = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        if checksums::sha256(path)? == expected {
+            checked().lock().unwrap().insert(path.to_path_buf());
+            return Ok(true)
+        }
+        checked().lock().unwrap().remove(path);
+        warn!("{} doesn't match its hash any more, removing it", path.display());
+        std::fs::remove_file(path).map_err(ErscomError::io(path))?;
+        let mut index = self.load_index();
+        index.retain(|_, hash| *hash != expected);
+        self.save_index(&index)?;
+        Ok(false)
+    }
+
+    /// [ZipCache::check()], but only the first time each zip is asked about while we're running. One install looks
+    /// at the zip several times, and it's not going anywhere in between.
+    pub fn check_once(&self, path: &Path) -> Result<bool, ErscomError> {
+        if checked().lock().unwrap().contains(path) { return Ok(true) }
+        self.check(path)
+    }
+}
+
+// The zips that [ZipCache::check()] found were fine
+fn checked() -> &'static Mutex<HashSet<PathBuf>> {
+    static CHECKED: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+    CHECKED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_tag_turns_away_paths() {
+        for tag in ["v1.8.0", "v1.9.0-beta", "2024.06.01"] {
+            assert!(safe_tag(tag), "{}", tag);
+        }
+        for tag in ["", "../../../x", "..", "a/b", r"a\b", r"C:\Windows\x", "C:x", "v1\0"] {
+            assert!(!safe_tag(tag), "{:?}", tag);
+        }
+    }
+}